@@ -0,0 +1,1224 @@
+use std::fmt;
+
+use bevy::{
+    asset::{io::Reader, AssetLoader, AsyncReadExt, LoadContext},
+    prelude::*,
+    utils::BoxedFuture,
+};
+use serde::Deserialize;
+
+use crate::{
+    battle::simulation::BattleRules,
+    battle::simulation::Formation,
+    dungeon_map::MAX_FLOOR_NODES,
+    enemy::{EliteModifier, EnemyPassive},
+    loading::BalanceAssets,
+    shop::MAX_STOCK_SIZE,
+    stats::{Ability, AbilityKind, BossPhase, RecipeKind, Row, StatKind, Stats},
+    status_effects::{StatusEffect, StatusEffectKind},
+    summoning::{SummoningItem, SummoningItemType},
+    GameState,
+};
+
+pub struct BalancePlugin;
+
+/// This plugin provides the [`Balance`] resource, which is loaded from `assets/balance.ron`. When
+/// the asset changes on disk (dev builds watch for changes), the resource is updated right away.
+impl Plugin for BalancePlugin {
+    fn build(&self, app: &mut App) {
+        app.init_asset::<Balance>()
+            .init_asset_loader::<BalanceLoader>()
+            .add_systems(OnExit(GameState::Loading), insert_balance)
+            .add_systems(Update, reload_balance);
+    }
+}
+
+/// Base values of stats and how much each tier adds to them.
+#[derive(Deserialize, Clone, Debug)]
+pub struct StatsBalance {
+    pub hp_base: f32,
+    pub hp_inc: f32,
+    pub hp_regeneration_base: f32,
+    pub hp_regeneration_inc: f32,
+    pub damage_base: f32,
+    pub damage_inc: f32,
+    pub speed_base: f32,
+    pub speed_inc: f32,
+    pub armor_base: f32,
+    pub armor_inc: f32,
+    pub crit_chance_base: f32,
+    pub crit_chance_inc: f32,
+    pub crit_multiplier_base: f32,
+    pub crit_multiplier_inc: f32,
+    pub evasion_base: f32,
+    pub evasion_inc: f32,
+}
+
+/// Mana pool of participants with an ability and power of each ability kind.
+#[derive(Deserialize, Clone, Debug)]
+pub struct AbilityBalance {
+    pub mana: f32,
+    pub mana_regeneration: f32,
+    pub area_hit_base: f32,
+    pub area_hit_inc: f32,
+    pub heal_base: f32,
+    pub heal_inc: f32,
+    pub shield_base: f32,
+    pub shield_inc: f32,
+    pub stun_base: f32,
+    pub stun_inc: f32,
+}
+
+/// Power of a status effect applied on hit and how much each tier adds to it.
+#[derive(Deserialize, Clone, Debug)]
+pub struct EffectBalance {
+    pub power_base: f32,
+    pub power_inc: f32,
+    pub duration: f32,
+}
+
+/// Status effects which can be applied on hit, stun is only applied by abilities.
+#[derive(Deserialize, Clone, Debug)]
+pub struct StatusEffectBalance {
+    pub poison: EffectBalance,
+    pub burn: EffectBalance,
+    pub slow: EffectBalance,
+    pub bleed: EffectBalance,
+}
+
+/// Caps of chance stats rolled in battles, so that every participant can be hit.
+#[derive(Deserialize, Clone, Debug)]
+pub struct BattleRulesBalance {
+    pub max_evasion: f32,
+    pub max_crit_chance: f32,
+}
+
+/// Shape of the dungeon map of every act and what its nodes give.
+#[derive(Deserialize, Clone, Debug)]
+pub struct DungeonBalance {
+    /// Floors of an act, the boss floor comes after them.
+    pub floor_count: usize,
+    pub min_floor_nodes: usize,
+    pub max_floor_nodes: usize,
+    /// Relative chances of node kinds on floors between the first one, which only has fights, and
+    /// the last one, which only has rests.
+    pub fight_weight: u32,
+    pub elite_weight: u32,
+    pub rest_weight: u32,
+    pub treasure_weight: u32,
+    pub shop_weight: u32,
+    /// Tiers added to stats of elite enemies and to the items they drop.
+    pub elite_tier_bonus: usize,
+    pub elite_item_tier_bonus: usize,
+    /// Tiers added to stats of the boss and to the items it drops.
+    pub boss_tier_bonus: usize,
+    pub boss_item_tier_bonus: usize,
+    /// Fraction of max HP restored to every minion at a rest node.
+    pub rest_heal: f32,
+    pub treasure_item_count: usize,
+    /// Times the fights of a floor can be rolled again during a run.
+    pub rerolls: usize,
+    /// Fraction of max HP every minion loses when a fight is skipped, minions survive it.
+    pub skip_hp_cost: f32,
+}
+
+impl DungeonBalance {
+    fn validate(&self) -> Result<(), BalanceError> {
+        if self.floor_count == 0 {
+            return Err(BalanceError::Invalid(
+                "dungeon.floor_count must be greater than zero".to_string(),
+            ));
+        }
+        if self.min_floor_nodes == 0
+            || self.min_floor_nodes > self.max_floor_nodes
+            || self.max_floor_nodes > MAX_FLOOR_NODES
+        {
+            return Err(BalanceError::Invalid(format!(
+                "dungeon floors must have from 1 to {MAX_FLOOR_NODES} nodes, got {} to {}",
+                self.min_floor_nodes, self.max_floor_nodes
+            )));
+        }
+        let total_weight = self.fight_weight
+            + self.elite_weight
+            + self.rest_weight
+            + self.treasure_weight
+            + self.shop_weight;
+        if total_weight == 0 {
+            return Err(BalanceError::Invalid(
+                "dungeon node weights must not all be zero".to_string(),
+            ));
+        }
+        if !(0. ..=1.).contains(&self.rest_heal) {
+            return Err(BalanceError::Invalid(format!(
+                "dungeon.rest_heal must be between 0 and 1, got {}",
+                self.rest_heal
+            )));
+        }
+        if !(0. ..=1.).contains(&self.skip_hp_cost) {
+            return Err(BalanceError::Invalid(format!(
+                "dungeon.skip_hp_cost must be between 0 and 1, got {}",
+                self.skip_hp_cost
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Gold earned during the run and prices of items in shops.
+#[derive(Deserialize, Clone, Debug)]
+pub struct ShopBalance {
+    /// Items offered by every shop.
+    pub stock_size: usize,
+    /// Price of a single item for each of its tiers.
+    pub price_per_tier: usize,
+    /// Fraction of the price paid for sold items.
+    pub sell_ratio: f32,
+    /// Gold for a won battle, it is multiplied by the tier of currently dropped items.
+    pub battle_gold: usize,
+}
+
+impl ShopBalance {
+    pub fn price(&self, item: &SummoningItem) -> usize {
+        self.price_per_tier * item.tier as usize
+    }
+
+    /// Gold paid for the whole stack.
+    pub fn sell_price(&self, item: &SummoningItem) -> usize {
+        (self.price(item) as f32 * self.sell_ratio) as usize * item.quantity
+    }
+
+    fn validate(&self) -> Result<(), BalanceError> {
+        if self.stock_size == 0 || self.stock_size > MAX_STOCK_SIZE {
+            return Err(BalanceError::Invalid(format!(
+                "shop.stock_size must be from 1 to {MAX_STOCK_SIZE}, got {}",
+                self.stock_size
+            )));
+        }
+        if self.price_per_tier == 0 {
+            return Err(BalanceError::Invalid(
+                "shop.price_per_tier must be greater than zero".to_string(),
+            ));
+        }
+        if !(0. ..=1.).contains(&self.sell_ratio) {
+            return Err(BalanceError::Invalid(format!(
+                "shop.sell_ratio must be between 0 and 1, got {}",
+                self.sell_ratio
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// How many items of the same type and tier are merged into a single item of the next tier.
+#[derive(Deserialize, Clone, Debug)]
+pub struct CraftingBalance {
+    /// Ratio for every tier starting with the first one, higher tiers use the last ratio.
+    pub ratios: Vec<usize>,
+}
+
+impl CraftingBalance {
+    /// Items of the given tier needed to craft one item of the next tier.
+    pub fn ratio(&self, tier: u8) -> usize {
+        let index = (tier as usize).saturating_sub(1).min(self.ratios.len() - 1);
+
+        self.ratios[index]
+    }
+
+    fn validate(&self) -> Result<(), BalanceError> {
+        if self.ratios.is_empty() {
+            return Err(BalanceError::Invalid(
+                "crafting.ratios must not be empty".to_string(),
+            ));
+        }
+        if let Some(ratio) = self.ratios.iter().find(|&&ratio| ratio < 2) {
+            return Err(BalanceError::Invalid(format!(
+                "crafting.ratios must be at least 2, got {ratio}"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Drops of gear and how much it improves minions.
+#[derive(Deserialize, Clone, Debug)]
+pub struct GearBalance {
+    /// Chance of a normal fight to drop gear, elites and bosses always drop it.
+    pub drop_chance: f32,
+    /// Tiers of the secondary stat of gear as a fraction of its tier, rounded up.
+    pub secondary_stat_ratio: f32,
+}
+
+impl GearBalance {
+    fn validate(&self) -> Result<(), BalanceError> {
+        if !(0. ..=1.).contains(&self.drop_chance) {
+            return Err(BalanceError::Invalid(format!(
+                "gear.drop_chance must be between 0 and 1, got {}",
+                self.drop_chance
+            )));
+        }
+        if !(0. ..=1.).contains(&self.secondary_stat_ratio) {
+            return Err(BalanceError::Invalid(format!(
+                "gear.secondary_stat_ratio must be between 0 and 1, got {}",
+                self.secondary_stat_ratio
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// HP minions recover outside of battles, as fractions of their max HP.
+#[derive(Deserialize, Clone, Debug)]
+pub struct RecoveryBalance {
+    /// Healed after every won battle.
+    pub battle_heal: f32,
+    /// Healed by a healing herb for each of its tiers.
+    pub herb_heal_per_tier: f32,
+}
+
+impl RecoveryBalance {
+    pub fn herb_heal(&self, tier: u8) -> f32 {
+        (self.herb_heal_per_tier * tier as f32).min(1.)
+    }
+
+    fn validate(&self) -> Result<(), BalanceError> {
+        if !(0. ..=1.).contains(&self.battle_heal) {
+            return Err(BalanceError::Invalid(format!(
+                "recovery.battle_heal must be between 0 and 1, got {}",
+                self.battle_heal
+            )));
+        }
+        if !(self.herb_heal_per_tier > 0. && self.herb_heal_per_tier <= 1.) {
+            return Err(BalanceError::Invalid(format!(
+                "recovery.herb_heal_per_tier must be greater than 0 and at most 1, got {}",
+                self.herb_heal_per_tier
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// How the back row of the formation changes damage of minions while a front row minion is alive.
+#[derive(Deserialize, Clone, Debug)]
+pub struct FormationBalance {
+    /// Damage of attacks of back row minions as a fraction of their damage.
+    pub back_row_damage: f32,
+    /// Damage taken by back row minions as a fraction of the damage of the hit.
+    pub back_row_damage_taken: f32,
+}
+
+impl FormationBalance {
+    pub fn formation(&self, rows: Vec<Row>) -> Formation {
+        Formation {
+            rows,
+            back_row_damage: self.back_row_damage,
+            back_row_damage_taken: self.back_row_damage_taken,
+        }
+    }
+
+    fn validate(&self) -> Result<(), BalanceError> {
+        validate_values(
+            "formation",
+            &[
+                ("back_row_damage", self.back_row_damage),
+                ("back_row_damage_taken", self.back_row_damage_taken),
+            ],
+            &["back_row_damage"],
+        )
+    }
+}
+
+/// What sacrificed minions give back.
+#[derive(Deserialize, Clone, Debug)]
+pub struct SacrificeBalance {
+    /// Fraction of the ingredients of the minion which is refunded, rounded down.
+    pub refund_ratio: f32,
+}
+
+impl SacrificeBalance {
+    fn validate(&self) -> Result<(), BalanceError> {
+        if !(0. ..=1.).contains(&self.refund_ratio) {
+            return Err(BalanceError::Invalid(format!(
+                "sacrifice.refund_ratio must be between 0 and 1, got {}",
+                self.refund_ratio
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Experience minions earn by winning battles and what their level ups give.
+#[derive(Deserialize, Clone, Debug)]
+pub struct ExperienceBalance {
+    /// Experience for every tier of a defeated enemy, enemies below tier one count as tier one.
+    pub experience_per_tier: f32,
+    /// Experience needed to reach the second level.
+    pub level_base: f32,
+    /// Additional experience needed for every further level.
+    pub level_inc: f32,
+    /// Tiers added to the chosen stat on level up.
+    pub level_up_tiers: u8,
+    /// Number of stats offered to choose from on level up.
+    pub level_up_choices: usize,
+}
+
+impl ExperienceBalance {
+    /// Experience needed to advance from the given level to the next one.
+    pub fn level_experience(&self, level: u32) -> f32 {
+        self.level_base + self.level_inc * level.saturating_sub(1) as f32
+    }
+
+    fn validate(&self) -> Result<(), BalanceError> {
+        validate_values(
+            "experience",
+            &[
+                ("experience_per_tier", self.experience_per_tier),
+                ("level_base", self.level_base),
+                ("level_inc", self.level_inc),
+            ],
+            &["level_base"],
+        )?;
+        if self.level_up_tiers == 0 {
+            return Err(BalanceError::Invalid(
+                "experience.level_up_tiers must be greater than zero".to_string(),
+            ));
+        }
+        if self.level_up_choices == 0 || self.level_up_choices > StatKind::ALL.len() {
+            return Err(BalanceError::Invalid(format!(
+                "experience.level_up_choices must be from 1 to {}, got {}",
+                StatKind::ALL.len(),
+                self.level_up_choices
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Combination of ingredients which summons a special kind of minion.
+#[derive(Deserialize, Clone, Debug)]
+pub struct RecipeBalance {
+    pub kind: RecipeKind,
+    /// Item types which all have to be among the ingredients, other ingredients may be added.
+    pub ingredients: Vec<SummoningItemType>,
+    /// Tiers added to stats of the minion.
+    pub stat_bonuses: Vec<(StatKind, u8)>,
+    /// Status effect with its tier applied on hit, unless an ingredient gives one.
+    pub on_hit: Option<(StatusEffectKind, u8)>,
+}
+
+impl RecipeBalance {
+    pub fn matches(&self, ingredients: &[SummoningItem]) -> bool {
+        self.ingredients.iter().all(|&item_type| {
+            ingredients
+                .iter()
+                .any(|ingredient| ingredient.item_type == item_type)
+        })
+    }
+
+    fn validate(&self) -> Result<(), BalanceError> {
+        let section = format!("recipes.{}", self.kind.name());
+
+        if self.ingredients.len() < 2 {
+            return Err(BalanceError::Invalid(format!(
+                "{section} must have at least two ingredients"
+            )));
+        }
+        if self
+            .ingredients
+            .iter()
+            .any(|item_type| item_type.is_consumable())
+        {
+            return Err(BalanceError::Invalid(format!(
+                "{section} can not have consumable ingredients"
+            )));
+        }
+        for (i, first) in self.ingredients.iter().enumerate() {
+            if self.ingredients[i + 1..]
+                .iter()
+                .any(|second| first.excludes(second))
+            {
+                return Err(BalanceError::Invalid(format!(
+                    "{section} has ingredients which can not be summoned together"
+                )));
+            }
+        }
+        if self
+            .on_hit
+            .is_some_and(|(kind, _)| kind == StatusEffectKind::Stun)
+        {
+            return Err(BalanceError::Invalid(format!(
+                "{section}.on_hit can not be a stun"
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+/// Boss at the end of an act, stats not listed here are rolled like for other enemies.
+#[derive(Deserialize, Clone, Debug)]
+pub struct BossBalance {
+    pub name: String,
+    /// Path of the sprite inside the assets folder.
+    pub sprite: String,
+    /// Rolled HP and damage are multiplied by these.
+    pub hp_multiplier: f32,
+    pub damage_multiplier: f32,
+    /// Ability and on hit effect with their tiers.
+    pub ability: Option<(AbilityKind, u8)>,
+    pub on_hit: Option<(StatusEffectKind, u8)>,
+    pub phases: Vec<BossPhase>,
+}
+
+impl BossBalance {
+    fn validate(&self) -> Result<(), BalanceError> {
+        let section = format!("bosses.{}", self.name);
+        validate_values(
+            &section,
+            &[
+                ("hp_multiplier", self.hp_multiplier),
+                ("damage_multiplier", self.damage_multiplier),
+            ],
+            &["hp_multiplier", "damage_multiplier"],
+        )?;
+
+        if self
+            .on_hit
+            .is_some_and(|(kind, _)| kind == StatusEffectKind::Stun)
+        {
+            return Err(BalanceError::Invalid(format!(
+                "{section}.on_hit can not be a stun"
+            )));
+        }
+
+        let mut previous_threshold = 1.;
+        for phase in self.phases.iter() {
+            if phase.hp_threshold <= 0. || phase.hp_threshold >= previous_threshold {
+                return Err(BalanceError::Invalid(format!(
+                    "{section} phase thresholds must be between 0 and 1 and descending, got {}",
+                    phase.hp_threshold
+                )));
+            }
+            previous_threshold = phase.hp_threshold;
+
+            validate_values(
+                &section,
+                &[
+                    ("damage_multiplier", phase.damage_multiplier),
+                    ("speed_multiplier", phase.speed_multiplier),
+                ],
+                &["damage_multiplier", "speed_multiplier"],
+            )?;
+
+            if let Some(special) = phase.special {
+                validate_values(
+                    &section,
+                    &[
+                        ("special.damage_multiplier", special.damage_multiplier),
+                        ("special.cooldown", special.cooldown),
+                    ],
+                    &["special.cooldown"],
+                )?;
+                if special.target_count == 0 {
+                    return Err(BalanceError::Invalid(format!(
+                        "{section} special attacks must hit at least one target"
+                    )));
+                }
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// Kind of normal and elite enemies with its own sprite, stats and passive.
+#[derive(Deserialize, Clone, Debug)]
+pub struct ArchetypeBalance {
+    pub name: String,
+    /// Path of the sprite inside the assets folder.
+    pub sprite: String,
+    /// Tiers added to rolled stats, negative ones take tiers away down to zero.
+    pub stat_bias: Vec<(StatKind, i8)>,
+    pub passive: EnemyPassive,
+}
+
+impl ArchetypeBalance {
+    /// Apply the bias to rolled stat tiers.
+    pub fn bias(&self, tiers: &mut [(StatKind, u8)]) {
+        for (stat, tier) in tiers.iter_mut() {
+            for &(bias_stat, bias) in self.stat_bias.iter() {
+                if bias_stat == *stat {
+                    *tier = tier.saturating_add_signed(bias).min(10);
+                }
+            }
+        }
+    }
+
+    pub fn apply_passive(&self, stats: &mut Stats) {
+        match self.passive {
+            EnemyPassive::Taunt => stats.taunt = true,
+            EnemyPassive::Frenzy(frenzy) => stats.frenzy = frenzy,
+            EnemyPassive::Lifesteal(lifesteal) => stats.lifesteal = lifesteal,
+        }
+    }
+
+    fn validate(&self) -> Result<(), BalanceError> {
+        let section = format!("archetypes.{}", self.name);
+        match self.passive {
+            EnemyPassive::Taunt => Ok(()),
+            EnemyPassive::Frenzy(frenzy) => validate_values(&section, &[("frenzy", frenzy)], &[]),
+            EnemyPassive::Lifesteal(lifesteal) => {
+                validate_values(&section, &[("lifesteal", lifesteal)], &[])
+            }
+        }
+    }
+}
+
+/// How elite modifiers change enemies and what they add to the rewards.
+#[derive(Deserialize, Clone, Debug)]
+pub struct EliteBalance {
+    /// Enraged elites enter a phase at this fraction of max HP, which multiplies their damage
+    /// and speed.
+    pub enraged_hp_threshold: f32,
+    pub enraged_damage_multiplier: f32,
+    pub enraged_speed_multiplier: f32,
+    /// Fraction of damage taken by elites with thorns which is dealt back to the attacker.
+    pub thorns: f32,
+    /// Fraction of max HP regenerating elites regenerate every second.
+    pub regeneration: f32,
+    /// Items added to the rewards of a group with a modifier.
+    pub modifier_reward_count: usize,
+}
+
+impl EliteBalance {
+    pub fn apply(&self, modifier: EliteModifier, stats: &mut Stats) {
+        match modifier {
+            EliteModifier::Enraged => stats.phases.push(BossPhase {
+                hp_threshold: self.enraged_hp_threshold,
+                damage_multiplier: self.enraged_damage_multiplier,
+                speed_multiplier: self.enraged_speed_multiplier,
+                special: None,
+            }),
+            EliteModifier::Thorns => stats.thorns = self.thorns,
+            EliteModifier::Regenerating => {
+                stats.hp_regeneration += stats.max_hp * self.regeneration
+            }
+        }
+    }
+
+    fn validate(&self) -> Result<(), BalanceError> {
+        validate_values(
+            "elites",
+            &[
+                ("enraged_hp_threshold", self.enraged_hp_threshold),
+                ("enraged_damage_multiplier", self.enraged_damage_multiplier),
+                ("enraged_speed_multiplier", self.enraged_speed_multiplier),
+                ("thorns", self.thorns),
+                ("regeneration", self.regeneration),
+            ],
+            &["enraged_damage_multiplier", "enraged_speed_multiplier"],
+        )?;
+        if self.enraged_hp_threshold <= 0. || self.enraged_hp_threshold >= 1. {
+            return Err(BalanceError::Invalid(format!(
+                "elites.enraged_hp_threshold must be between 0 and 1, got {}",
+                self.enraged_hp_threshold
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Asset, Resource, TypePath, Deserialize, Clone, Debug)]
+pub struct Balance {
+    pub minion: StatsBalance,
+    pub enemy: StatsBalance,
+    pub abilities: AbilityBalance,
+    pub status_effects: StatusEffectBalance,
+    pub battle_rules: BattleRulesBalance,
+    pub dungeon: DungeonBalance,
+    pub shop: ShopBalance,
+    pub crafting: CraftingBalance,
+    pub experience: ExperienceBalance,
+    pub recovery: RecoveryBalance,
+    pub gear: GearBalance,
+    pub sacrifice: SacrificeBalance,
+    pub formation: FormationBalance,
+    /// Recipes of special minions, the one with most ingredients is used when several match.
+    pub recipes: Vec<RecipeBalance>,
+    /// Bosses of acts, they take turns in this order.
+    pub bosses: Vec<BossBalance>,
+    /// Archetypes of normal and elite enemies, each enemy gets a random one.
+    pub archetypes: Vec<ArchetypeBalance>,
+    pub elites: EliteBalance,
+    /// Number of battles after which tier of dropped items increases.
+    pub battles_to_item_tier_inc: usize,
+    /// Number of battles after which tier of enemy stats increases.
+    pub battles_to_enemy_tier_inc: usize,
+    /// How many times stronger each additional enemy makes a group, HP and damage of the group
+    /// are split evenly among its enemies.
+    pub enemy_group_strength: f32,
+}
+
+/// Check that all values are non-negative numbers and that the listed ones are greater than zero.
+fn validate_values(
+    section: &str,
+    values: &[(&str, f32)],
+    positive: &[&str],
+) -> Result<(), BalanceError> {
+    for &(field, value) in values {
+        if !value.is_finite() || value < 0. {
+            return Err(BalanceError::Invalid(format!(
+                "{section}.{field} must be a non-negative number, got {value}"
+            )));
+        }
+        if value == 0. && positive.contains(&field) {
+            return Err(BalanceError::Invalid(format!(
+                "{section}.{field} must be greater than zero"
+            )));
+        }
+    }
+
+    Ok(())
+}
+
+impl StatsBalance {
+    pub fn base_and_inc(&self, stat: StatKind) -> (f32, f32) {
+        match stat {
+            StatKind::Damage => (self.damage_base, self.damage_inc),
+            StatKind::Speed => (self.speed_base, self.speed_inc),
+            StatKind::MaxHp => (self.hp_base, self.hp_inc),
+            StatKind::HpRegeneration => (self.hp_regeneration_base, self.hp_regeneration_inc),
+            StatKind::Armor => (self.armor_base, self.armor_inc),
+            StatKind::CritChance => (self.crit_chance_base, self.crit_chance_inc),
+            StatKind::CritMultiplier => (self.crit_multiplier_base, self.crit_multiplier_inc),
+            StatKind::Evasion => (self.evasion_base, self.evasion_inc),
+        }
+    }
+
+    pub fn value(&self, stat: StatKind, tier: u8) -> f32 {
+        let (base, inc) = self.base_and_inc(stat);
+
+        base + inc * tier as f32
+    }
+
+    pub fn tier(&self, stat: StatKind, stats: &Stats) -> u8 {
+        let (base, inc) = self.base_and_inc(stat);
+
+        ((stat.get(stats) - base) / inc).ceil() as u8
+    }
+
+    /// Stats with the given tiers, stats which are not listed stay at their base value.
+    pub fn stats(&self, tiers: &[(StatKind, u8)]) -> Stats {
+        let mut stats = Stats::default();
+        for stat in StatKind::ALL {
+            let tier = tiers
+                .iter()
+                .find(|(kind, _)| *kind == stat)
+                .map_or(0, |(_, tier)| *tier);
+            *stat.get_mut(&mut stats) = self.value(stat, tier);
+        }
+        stats.current_hp = stats.max_hp;
+
+        stats
+    }
+
+    fn validate(&self, section: &str) -> Result<(), BalanceError> {
+        // tiers are computed back from stats by dividing with increments
+        validate_values(
+            section,
+            &[
+                ("hp_base", self.hp_base),
+                ("hp_inc", self.hp_inc),
+                ("hp_regeneration_base", self.hp_regeneration_base),
+                ("hp_regeneration_inc", self.hp_regeneration_inc),
+                ("damage_base", self.damage_base),
+                ("damage_inc", self.damage_inc),
+                ("speed_base", self.speed_base),
+                ("speed_inc", self.speed_inc),
+                ("armor_base", self.armor_base),
+                ("armor_inc", self.armor_inc),
+                ("crit_chance_base", self.crit_chance_base),
+                ("crit_chance_inc", self.crit_chance_inc),
+                ("crit_multiplier_base", self.crit_multiplier_base),
+                ("crit_multiplier_inc", self.crit_multiplier_inc),
+                ("evasion_base", self.evasion_base),
+                ("evasion_inc", self.evasion_inc),
+            ],
+            &[
+                "hp_base",
+                "hp_inc",
+                "hp_regeneration_inc",
+                "damage_inc",
+                "speed_base",
+                "speed_inc",
+                "armor_inc",
+                "crit_chance_inc",
+                "crit_multiplier_base",
+                "crit_multiplier_inc",
+                "evasion_inc",
+            ],
+        )
+    }
+}
+
+impl AbilityBalance {
+    fn base_and_inc(&self, kind: AbilityKind) -> (f32, f32) {
+        match kind {
+            AbilityKind::AreaHit => (self.area_hit_base, self.area_hit_inc),
+            AbilityKind::Heal => (self.heal_base, self.heal_inc),
+            AbilityKind::Shield => (self.shield_base, self.shield_inc),
+            AbilityKind::Stun => (self.stun_base, self.stun_inc),
+        }
+    }
+
+    pub fn ability(&self, kind: AbilityKind, tier: u8) -> Ability {
+        let (base, inc) = self.base_and_inc(kind);
+
+        Ability {
+            kind,
+            power: base + inc * tier as f32,
+        }
+    }
+
+    pub fn tier(&self, ability: &Ability) -> u8 {
+        let (base, inc) = self.base_and_inc(ability.kind);
+
+        ((ability.power - base) / inc).ceil() as u8
+    }
+
+    fn validate(&self) -> Result<(), BalanceError> {
+        validate_values(
+            "abilities",
+            &[
+                ("mana", self.mana),
+                ("mana_regeneration", self.mana_regeneration),
+                ("area_hit_base", self.area_hit_base),
+                ("area_hit_inc", self.area_hit_inc),
+                ("heal_base", self.heal_base),
+                ("heal_inc", self.heal_inc),
+                ("shield_base", self.shield_base),
+                ("shield_inc", self.shield_inc),
+                ("stun_base", self.stun_base),
+                ("stun_inc", self.stun_inc),
+            ],
+            &[
+                "mana",
+                "mana_regeneration",
+                "area_hit_inc",
+                "heal_inc",
+                "shield_inc",
+                "stun_inc",
+            ],
+        )
+    }
+}
+
+impl StatusEffectBalance {
+    /// Stun is only applied by abilities, so it has no on hit balance.
+    fn effect_balance(&self, kind: StatusEffectKind) -> Option<&EffectBalance> {
+        match kind {
+            StatusEffectKind::Poison => Some(&self.poison),
+            StatusEffectKind::Burn => Some(&self.burn),
+            StatusEffectKind::Slow => Some(&self.slow),
+            StatusEffectKind::Bleed => Some(&self.bleed),
+            StatusEffectKind::Stun => None,
+        }
+    }
+
+    /// On hit effect of the tier, none for stuns.
+    pub fn effect(&self, kind: StatusEffectKind, tier: u8) -> Option<StatusEffect> {
+        let balance = self.effect_balance(kind)?;
+
+        Some(StatusEffect::new(
+            kind,
+            balance.power_base + balance.power_inc * tier as f32,
+            balance.duration,
+        ))
+    }
+
+    /// Tier of the on hit effect, none for stuns.
+    pub fn tier(&self, effect: &StatusEffect) -> Option<u8> {
+        let balance = self.effect_balance(effect.kind)?;
+
+        Some(((effect.power - balance.power_base) / balance.power_inc).ceil() as u8)
+    }
+
+    fn validate(&self) -> Result<(), BalanceError> {
+        for (section, balance) in [
+            ("status_effects.poison", &self.poison),
+            ("status_effects.burn", &self.burn),
+            ("status_effects.slow", &self.slow),
+            ("status_effects.bleed", &self.bleed),
+        ] {
+            validate_values(
+                section,
+                &[
+                    ("power_base", balance.power_base),
+                    ("power_inc", balance.power_inc),
+                    ("duration", balance.duration),
+                ],
+                &["power_inc", "duration"],
+            )?;
+        }
+
+        Ok(())
+    }
+}
+
+impl BattleRulesBalance {
+    pub fn rules(&self) -> BattleRules {
+        BattleRules {
+            max_evasion: self.max_evasion,
+            max_crit_chance: self.max_crit_chance,
+        }
+    }
+
+    fn validate(&self) -> Result<(), BalanceError> {
+        if !(0. ..1.).contains(&self.max_evasion) {
+            return Err(BalanceError::Invalid(format!(
+                "battle_rules.max_evasion must be at least zero and below one, got {}",
+                self.max_evasion
+            )));
+        }
+        if !(0. ..=1.).contains(&self.max_crit_chance) {
+            return Err(BalanceError::Invalid(format!(
+                "battle_rules.max_crit_chance must be between zero and one, got {}",
+                self.max_crit_chance
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+impl Balance {
+    /// Boss at the end of the act, acts are numbered from one.
+    pub fn boss(&self, act: usize) -> &BossBalance {
+        &self.bosses[act.saturating_sub(1) % self.bosses.len()]
+    }
+
+    /// Recipe with the most ingredients among those matching the ingredients, earlier recipes win
+    /// ties.
+    pub fn recipe(&self, ingredients: &[SummoningItem]) -> Option<&RecipeBalance> {
+        self.recipes
+            .iter()
+            .filter(|recipe| recipe.matches(ingredients))
+            .fold(None, |best: Option<&RecipeBalance>, recipe| match best {
+                Some(best) if best.ingredients.len() >= recipe.ingredients.len() => Some(best),
+                _ => Some(recipe),
+            })
+    }
+
+    /// Gold for a battle won after the given number of battles.
+    pub fn battle_gold(&self, battle_count: usize) -> usize {
+        self.shop.battle_gold * (battle_count / self.battles_to_item_tier_inc + 1)
+    }
+
+    /// Experience for defeating the enemy. HP and damage are left out, because groups split them
+    /// among their enemies.
+    pub fn enemy_experience(&self, stats: &Stats) -> f32 {
+        let tiers: Vec<_> = StatKind::ALL
+            .into_iter()
+            .filter(|&stat| stat != StatKind::MaxHp && stat != StatKind::Damage)
+            .map(|stat| self.enemy.tier(stat, stats) as f32)
+            .collect();
+        let tier = tiers.iter().sum::<f32>() / tiers.len() as f32;
+
+        self.experience.experience_per_tier * tier.max(1.)
+    }
+
+    pub fn validate(&self) -> Result<(), BalanceError> {
+        self.minion.validate("minion")?;
+        self.enemy.validate("enemy")?;
+        self.abilities.validate()?;
+        self.status_effects.validate()?;
+        self.battle_rules.validate()?;
+        self.dungeon.validate()?;
+        self.shop.validate()?;
+        self.crafting.validate()?;
+        self.experience.validate()?;
+        self.recovery.validate()?;
+        self.gear.validate()?;
+        self.sacrifice.validate()?;
+        self.formation.validate()?;
+        for (i, recipe) in self.recipes.iter().enumerate() {
+            recipe.validate()?;
+            if self.recipes[i + 1..]
+                .iter()
+                .any(|other| other.kind == recipe.kind)
+            {
+                return Err(BalanceError::Invalid(format!(
+                    "recipes.{} is defined more than once",
+                    recipe.kind.name()
+                )));
+            }
+        }
+        if self.bosses.is_empty() {
+            return Err(BalanceError::Invalid(
+                "at least one boss must be defined".to_string(),
+            ));
+        }
+        for boss in self.bosses.iter() {
+            boss.validate()?;
+        }
+        if self.archetypes.is_empty() {
+            return Err(BalanceError::Invalid(
+                "at least one enemy archetype must be defined".to_string(),
+            ));
+        }
+        for archetype in self.archetypes.iter() {
+            archetype.validate()?;
+        }
+        self.elites.validate()?;
+
+        if self.battles_to_item_tier_inc == 0 {
+            return Err(BalanceError::Invalid(
+                "battles_to_item_tier_inc must be greater than zero".to_string(),
+            ));
+        }
+        if self.battles_to_enemy_tier_inc == 0 {
+            return Err(BalanceError::Invalid(
+                "battles_to_enemy_tier_inc must be greater than zero".to_string(),
+            ));
+        }
+        if !self.enemy_group_strength.is_finite() || self.enemy_group_strength <= 0. {
+            return Err(BalanceError::Invalid(format!(
+                "enemy_group_strength must be greater than zero, got {}",
+                self.enemy_group_strength
+            )));
+        }
+
+        Ok(())
+    }
+}
+
+#[derive(Debug)]
+pub enum BalanceError {
+    Io(std::io::Error),
+    Parse(ron::error::SpannedError),
+    Invalid(String),
+}
+
+impl fmt::Display for BalanceError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            BalanceError::Io(error) => write!(f, "could not read balance file: {error}"),
+            BalanceError::Parse(error) => write!(f, "could not parse balance file: {error}"),
+            BalanceError::Invalid(error) => write!(f, "invalid balance: {error}"),
+        }
+    }
+}
+
+impl std::error::Error for BalanceError {}
+
+impl From<std::io::Error> for BalanceError {
+    fn from(error: std::io::Error) -> Self {
+        BalanceError::Io(error)
+    }
+}
+
+impl From<ron::error::SpannedError> for BalanceError {
+    fn from(error: ron::error::SpannedError) -> Self {
+        BalanceError::Parse(error)
+    }
+}
+
+#[derive(Default)]
+struct BalanceLoader;
+
+impl AssetLoader for BalanceLoader {
+    type Asset = Balance;
+    type Settings = ();
+    type Error = BalanceError;
+
+    fn load<'a>(
+        &'a self,
+        reader: &'a mut Reader,
+        _settings: &'a (),
+        _load_context: &'a mut LoadContext,
+    ) -> BoxedFuture<'a, Result<Self::Asset, Self::Error>> {
+        Box::pin(async move {
+            let mut bytes = Vec::new();
+            reader.read_to_end(&mut bytes).await?;
+
+            let balance: Balance = ron::de::from_bytes(&bytes)?;
+            balance.validate()?;
+
+            Ok(balance)
+        })
+    }
+
+    fn extensions(&self) -> &[&str] {
+        // asset server only takes the extension after the first dot of the file name
+        &["ron"]
+    }
+}
+
+fn insert_balance(
+    mut commands: Commands,
+    balance_assets: Res<BalanceAssets>,
+    balances: Res<Assets<Balance>>,
+) {
+    let balance = balances
+        .get(&balance_assets.balance)
+        .expect("Balance should be loaded before leaving the loading state.");
+
+    commands.insert_resource(balance.clone());
+}
+
+fn reload_balance(
+    mut commands: Commands,
+    mut asset_events: EventReader<AssetEvent<Balance>>,
+    balances: Res<Assets<Balance>>,
+) {
+    for event in asset_events.read() {
+        let AssetEvent::Modified { id } = event else {
+            continue;
+        };
+
+        if let Some(balance) = balances.get(*id) {
+            info!("balance reloaded");
+            commands.insert_resource(balance.clone());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use bevy::asset::AssetPath;
+
+    use super::*;
+
+    #[test]
+    fn shipped_balance_is_valid() {
+        let balance: Balance = ron::from_str(include_str!("../assets/balance.ron")).unwrap();
+
+        balance.validate().unwrap();
+    }
+
+    #[test]
+    fn loader_handles_the_shipped_balance() {
+        let extension = AssetPath::from("balance.ron").get_full_extension().unwrap();
+
+        assert!(BalanceLoader.extensions().contains(&extension.as_str()));
+    }
+
+    #[test]
+    fn bias_saturates_at_zero_and_ten() {
+        let archetype = ArchetypeBalance {
+            name: "Brute".to_string(),
+            sprite: String::new(),
+            stat_bias: vec![(StatKind::Damage, 3), (StatKind::Speed, -3)],
+            passive: EnemyPassive::Taunt,
+        };
+        let mut tiers = [
+            (StatKind::Damage, 9),
+            (StatKind::Speed, 2),
+            (StatKind::MaxHp, 5),
+        ];
+
+        archetype.bias(&mut tiers);
+
+        assert_eq!(
+            tiers,
+            [
+                (StatKind::Damage, 10),
+                (StatKind::Speed, 0),
+                (StatKind::MaxHp, 5),
+            ]
+        );
+    }
+
+    #[test]
+    fn elite_modifiers_change_stats() {
+        let elites = EliteBalance {
+            enraged_hp_threshold: 0.5,
+            enraged_damage_multiplier: 2.,
+            enraged_speed_multiplier: 1.5,
+            thorns: 0.3,
+            regeneration: 0.02,
+            modifier_reward_count: 1,
+        };
+        let base = Stats {
+            max_hp: 200.,
+            hp_regeneration: 1.,
+            ..Default::default()
+        };
+
+        let mut enraged = base.clone();
+        elites.apply(EliteModifier::Enraged, &mut enraged);
+        let mut thorns = base.clone();
+        elites.apply(EliteModifier::Thorns, &mut thorns);
+        let mut regenerating = base;
+        elites.apply(EliteModifier::Regenerating, &mut regenerating);
+
+        assert_eq!(
+            enraged.phases,
+            vec![BossPhase {
+                hp_threshold: 0.5,
+                damage_multiplier: 2.,
+                speed_multiplier: 1.5,
+                special: None,
+            }]
+        );
+        assert_eq!(thorns.thorns, 0.3);
+        assert_eq!(regenerating.hp_regeneration, 5.);
+    }
+
+    #[test]
+    fn stun_has_no_on_hit_tier() {
+        let effect = EffectBalance {
+            power_base: 2.,
+            power_inc: 1.,
+            duration: 3.,
+        };
+        let status_effects = StatusEffectBalance {
+            poison: effect.clone(),
+            burn: effect.clone(),
+            slow: effect.clone(),
+            bleed: effect,
+        };
+
+        let poison = status_effects.effect(StatusEffectKind::Poison, 3).unwrap();
+        assert_eq!(status_effects.tier(&poison), Some(3));
+        assert!(status_effects.effect(StatusEffectKind::Stun, 3).is_none());
+        let stun = StatusEffect::new(StatusEffectKind::Stun, 0., 2.);
+        assert_eq!(status_effects.tier(&stun), None);
+    }
+
+    #[test]
+    fn prices_grow_with_tier_and_sold_stacks_pay_a_fraction() {
+        let shop = ShopBalance {
+            stock_size: 4,
+            price_per_tier: 10,
+            sell_ratio: 0.35,
+            battle_gold: 5,
+        };
+        let item = SummoningItem {
+            item_type: SummoningItemType::Damage,
+            tier: 3,
+            quantity: 4,
+        };
+
+        assert_eq!(shop.price(&item), 30);
+        // every item of the stack sells for the rounded down fraction of its price
+        assert_eq!(shop.sell_price(&item), 10 * 4);
+    }
+
+    #[test]
+    fn battle_gold_rises_with_item_tiers() {
+        let balance: Balance = ron::from_str(include_str!("../assets/balance.ron")).unwrap();
+        let battles = balance.battles_to_item_tier_inc;
+
+        assert_eq!(balance.battle_gold(0), balance.shop.battle_gold);
+        assert_eq!(balance.battle_gold(battles - 1), balance.shop.battle_gold);
+        assert_eq!(balance.battle_gold(battles), balance.shop.battle_gold * 2);
+    }
+}
@@ -1,21 +1,32 @@
 use std::time::Duration;
 
 mod effects;
+pub mod report;
+pub mod simulation;
 
-use self::effects::EffectsPlugin;
+use self::{
+    effects::EffectsPlugin,
+    report::ReportPlugin,
+    simulation::{simulate_battle, ActionKind, BattleOutcome, BattleResult, Combatant},
+};
 use crate::{
     audio::Soundtrack,
+    balance::Balance,
     enemy::{DropRewards, Enemy},
-    health_bar::HealthBar,
+    experience::MinionLevel,
+    formation::{lineup, FormationSlot},
     loading::TextureAssets,
     minions::Minion,
-    stats::Stats,
-    summoning::{InventoryItems, MAX_ITEM_COUNT},
+    rewards::PendingRewards,
+    run_seed::{RngStream, RunSeed},
+    shop::Gold,
+    stats::{AbilityKind, Stats},
+    status_effects::StatusEffects,
     BattleCount, GameScreen, GameState,
 };
 use bevy::prelude::*;
 use bevy_kira_audio::{AudioInstance, AudioTween};
-use rand::{rngs::StdRng, Rng, SeedableRng};
+use rand::Rng;
 
 const VOLUME_TRANSITION: f32 = 0.5;
 
@@ -23,204 +34,421 @@ pub struct BattlePlugin;
 
 impl Plugin for BattlePlugin {
     fn build(&self, app: &mut App) {
-        app.add_plugins(EffectsPlugin)
+        app.add_plugins((EffectsPlugin, ReportPlugin))
             .add_event::<MinionAttackEvent>()
             .add_event::<EnemyAttackEvent>()
+            .add_event::<AbilityCastEvent>()
+            .add_event::<BossPhaseEvent>()
             .add_event::<MinionDiedEvent>()
             .add_event::<EnemyDiedEvent>()
             .add_systems(
                 OnEnter(GameScreen::Battle),
                 (prepare_battle, prepare_battle_screen),
             )
-            .add_systems(OnExit(GameScreen::Battle), clean_up_battle_screen)
+            .add_systems(
+                OnExit(GameScreen::Battle),
+                (clean_up_battle_screen, clean_up_battle_playback),
+            )
             .add_systems(
                 Update,
                 (update_battle, handle_enemy_dead, handle_minion_dead)
                     .chain()
-                    .run_if(in_state(GameState::Playing).and_then(in_state(GameScreen::Battle))),
+                    .run_if(
+                        in_state(GameState::Playing)
+                            .and_then(in_state(GameScreen::Battle))
+                            .and_then(resource_exists::<BattlePlayback>),
+                    ),
             )
-            .insert_resource(BattleRng(StdRng::from_entropy()))
             .insert_resource(MinionCount(0));
     }
 }
 
-#[derive(Component, Default)]
-pub struct BattleParticipant {
-    pub turn_accumulator: f32,
-}
-
 #[derive(Component)]
 pub struct BattleScreenEntity;
 
 #[derive(Resource)]
-pub struct BattleRng(StdRng);
+pub struct MinionCount(usize);
 
+/// Battle resolved by [`simulate_battle`], which is played back in real time.
 #[derive(Resource)]
-pub struct MinionCount(usize);
+pub struct BattlePlayback {
+    result: BattleResult,
+    minion_entities: Vec<Entity>,
+    enemy_entities: Vec<Entity>,
+    elapsed: f32,
+    next_action: usize,
+    /// Whether each minion is alive according to the last played back action.
+    minions_alive: Vec<bool>,
+    /// Whether each enemy is alive according to the last played back action.
+    enemies_alive: Vec<bool>,
+    /// Experience every surviving minion gets for the win.
+    experience: f32,
+}
 
+impl BattlePlayback {
+    fn is_finished(&self) -> bool {
+        self.next_action >= self.result.timeline.len() && self.elapsed >= self.result.duration
+    }
+
+    fn is_won(&self) -> bool {
+        self.is_finished() && self.result.outcome == BattleOutcome::Victory
+    }
+
+    fn is_lost(&self) -> bool {
+        self.is_finished() && self.result.outcome == BattleOutcome::Defeat
+    }
+
+    fn combatant_entity(&self, combatant: Combatant) -> Entity {
+        match combatant {
+            Combatant::Minion(index) => self.minion_entities[index],
+            Combatant::Enemy(index) => self.enemy_entities[index],
+        }
+    }
+}
+
+/// Hit of a minion attack, area hit or special attack.
 #[derive(Event)]
 pub struct MinionAttackEvent {
     attacker: Entity,
     target: Entity,
+    /// Damage after armor dealt to the target.
+    damage: f32,
+    /// Damage the attacker took back from thorns of the target.
+    recoil: f32,
 }
 
+/// Hit of an enemy attack, area hit or special attack.
 #[derive(Event)]
 pub struct EnemyAttackEvent {
     attacker: Entity,
     target: Entity,
+    /// Damage after armor dealt to the target.
+    damage: f32,
+    /// Damage the attacker took back from thorns of the target.
+    recoil: f32,
+}
+
+#[derive(Event)]
+pub struct AbilityCastEvent {
+    caster: Entity,
+    kind: AbilityKind,
+}
+
+#[derive(Event)]
+pub struct BossPhaseEvent {
+    boss: Entity,
 }
 
 #[derive(Event)]
 pub struct EnemyDiedEvent;
 
 #[derive(Event)]
-pub struct MinionDiedEvent;
+pub struct MinionDiedEvent {
+    minion: Entity,
+}
 
 fn prepare_battle(
     mut commands: Commands,
     mut minion_count: ResMut<MinionCount>,
-    minion_query: Query<Entity, With<Minion>>,
-    enemy_query: Query<Entity, With<Enemy>>,
+    run_seed: Res<RunSeed>,
+    battle_count: Res<BattleCount>,
+    balance: Res<Balance>,
+    mut minion_query: Query<(Entity, &mut Stats, &FormationSlot), (With<Minion>, Without<Enemy>)>,
+    mut enemy_query: Query<(Entity, &mut Stats), With<Enemy>>,
 ) {
-    for entity in minion_query.iter() {
-        commands.entity(entity).insert(BattleParticipant::default());
+    commands.insert_resource(PendingRewards {
+        title: "VICTORY",
+        ..Default::default()
+    });
+
+    // battles start with an empty mana pool and no status effects, minions line up by their slots
+    let (minions, rows) = lineup(
+        minion_query
+            .iter_mut()
+            .map(|(entity, mut stats, &slot)| {
+                stats.current_mana = 0.;
+                (slot, (entity, stats.clone()))
+            })
+            .collect(),
+    );
+    let (minion_entities, minion_stats): (Vec<_>, Vec<_>) = minions.into_iter().unzip();
+    let (enemy_entities, enemy_stats): (Vec<_>, Vec<_>) = enemy_query
+        .iter_mut()
+        .map(|(entity, mut stats)| {
+            stats.current_mana = 0.;
+            (entity, stats.clone())
+        })
+        .unzip();
+    for &entity in minion_entities.iter().chain(enemy_entities.iter()) {
+        commands.entity(entity).insert(StatusEffects::default());
     }
-
-    commands.entity(enemy_query.single()).insert((
-        HealthBar {
-            width: 256.,
-            offset: Vec2::new(0., 380.),
-            ..Default::default()
-        },
-        BattleParticipant::default(),
-    ));
-
-    minion_count.0 = minion_query.iter().count();
+    minion_count.0 = minion_entities.len();
+
+    let experience = enemy_stats
+        .iter()
+        .map(|stats| balance.enemy_experience(stats))
+        .sum();
+
+    let seed = run_seed.rng(RngStream::Battle, battle_count.0).gen();
+    let formation = balance.formation.formation(rows);
+    let result = simulate_battle(
+        &minion_stats,
+        &enemy_stats,
+        &formation,
+        &balance.battle_rules.rules(),
+        seed,
+    );
+    info!(
+        "battle resolved: {:?} after {:.1}s",
+        result.outcome, result.duration
+    );
+
+    commands.insert_resource(BattlePlayback {
+        result,
+        minions_alive: vec![true; minion_entities.len()],
+        enemies_alive: vec![true; enemy_entities.len()],
+        minion_entities,
+        enemy_entities,
+        elapsed: 0.,
+        next_action: 0,
+        experience,
+    });
 }
 
 pub fn update_battle(
     time: Res<Time>,
-    mut battle_rng: ResMut<BattleRng>,
-    minion_count: Res<MinionCount>,
+    mut playback: ResMut<BattlePlayback>,
     mut minion_attack_event: EventWriter<MinionAttackEvent>,
     mut enemy_attack_event: EventWriter<EnemyAttackEvent>,
-    mut minion_query: Query<
-        (Entity, &mut BattleParticipant, &mut Stats),
-        (With<Minion>, Without<Enemy>),
-    >,
-    mut enemy_query: Query<
-        (Entity, &mut BattleParticipant, &mut Stats),
-        (With<Enemy>, Without<Minion>),
-    >,
+    mut ability_cast_event: EventWriter<AbilityCastEvent>,
+    mut boss_phase_event: EventWriter<BossPhaseEvent>,
+    mut stats_query: Query<(&mut Stats, &mut StatusEffects)>,
 ) {
-    if let Ok((enemy_entity, mut enemy_battle_participant, mut enemy_stats)) =
-        enemy_query.get_single_mut()
-    {
-        for (entity, mut battle_participant, stats) in minion_query.iter_mut() {
-            battle_participant.turn_accumulator += time.delta_seconds();
+    playback.elapsed += time.delta_seconds();
+
+    // regenerate between actions, so bars fill up smoothly, actions then set the exact values,
+    // this is only for display, so damage over time never kills anyone before the timeline does
+    let entities = playback
+        .minion_entities
+        .iter()
+        .zip(playback.minions_alive.iter())
+        .chain(
+            playback
+                .enemy_entities
+                .iter()
+                .zip(playback.enemies_alive.iter()),
+        );
+    for (&entity, &alive) in entities {
+        if !alive {
+            continue;
+        }
+        if let Ok((mut stats, status_effects)) = stats_query.get_mut(entity) {
+            let mut modified = status_effects.modify(&stats);
+            modified.regenerate_hp_and_mana(time.delta_seconds());
+            stats.current_hp = modified.current_hp.max(f32::EPSILON);
+            stats.current_mana = modified.current_mana;
+        }
+    }
 
-            if battle_participant.turn_accumulator >= 1. / stats.speed {
-                battle_participant.turn_accumulator -= 1. / stats.speed;
+    while let Some(action) = playback.result.timeline.get(playback.next_action) {
+        if action.time > playback.elapsed {
+            break;
+        }
 
-                enemy_stats.current_hp -= stats.damage;
-                info!(
-                    "minion attacking for {}, enemy has {} hp",
-                    stats.damage, enemy_stats.current_hp
-                );
-                minion_attack_event.send(MinionAttackEvent {
-                    attacker: entity,
-                    target: enemy_entity,
-                });
+        let snapshots = playback
+            .minion_entities
+            .iter()
+            .zip(action.minions.iter())
+            .chain(playback.enemy_entities.iter().zip(action.enemies.iter()));
+        for (&entity, vitals) in snapshots {
+            if let Ok((mut stats, mut status_effects)) = stats_query.get_mut(entity) {
+                stats.current_hp = vitals.hp;
+                stats.current_mana = vitals.mana;
+                status_effects.set_if_neq(vitals.effects.clone());
             }
         }
-
-        enemy_battle_participant.turn_accumulator += time.delta_seconds();
-
-        if enemy_battle_participant.turn_accumulator >= 1. / enemy_stats.speed {
-            enemy_battle_participant.turn_accumulator -= 1. / enemy_stats.speed;
-
-            let target = minion_query
-                .iter_mut()
-                .nth(battle_rng.0.gen_range(0..minion_count.0));
-            if let Some((entity, _, mut stats)) = target {
-                stats.current_hp -= enemy_stats.damage;
+        let minions_alive = action.minions.iter().map(|vitals| vitals.hp > 0.).collect();
+        let enemies_alive = action.enemies.iter().map(|vitals| vitals.hp > 0.).collect();
+
+        let attacker = playback.combatant_entity(action.actor);
+        let mut send_hit = |target, damage, recoil| {
+            let target = playback.combatant_entity(target);
+            match action.actor {
+                Combatant::Minion(_) => {
+                    minion_attack_event.send(MinionAttackEvent {
+                        attacker,
+                        target,
+                        damage,
+                        recoil,
+                    });
+                }
+                Combatant::Enemy(_) => {
+                    enemy_attack_event.send(EnemyAttackEvent {
+                        attacker,
+                        target,
+                        damage,
+                        recoil,
+                    });
+                }
+            }
+        };
+        match &action.kind {
+            ActionKind::Attack {
+                target,
+                damage,
+                hit,
+                recoil,
+            } => {
                 info!(
-                    "enemy attacking for {}, minion has {} hp",
-                    enemy_stats.damage, stats.current_hp
+                    "{:?} attacking {:?} for {} ({:?})",
+                    action.actor, target, damage, hit
                 );
-                enemy_attack_event.send(EnemyAttackEvent {
-                    attacker: enemy_entity,
-                    target: entity,
+                send_hit(*target, *damage, *recoil);
+            }
+            ActionKind::Ability {
+                ability,
+                targets,
+                damage,
+                recoil,
+            } => {
+                info!("{:?} casting {:?}", action.actor, ability);
+                ability_cast_event.send(AbilityCastEvent {
+                    caster: attacker,
+                    kind: ability.kind,
                 });
+
+                // area hits hurt their targets like attacks do
+                if ability.kind == AbilityKind::AreaHit {
+                    for ((&target, &damage), &recoil) in targets.iter().zip(damage).zip(recoil) {
+                        send_hit(target, damage, recoil);
+                    }
+                }
+            }
+            ActionKind::Special {
+                targets,
+                damage,
+                recoil,
+            } => {
+                info!("{:?} using special attack on {:?}", action.actor, targets);
+                for ((&target, &damage), &recoil) in targets.iter().zip(damage).zip(recoil) {
+                    send_hit(target, damage, recoil);
+                }
             }
+            ActionKind::Phase { phase } => {
+                info!("{:?} entering phase {}", action.actor, phase);
+                boss_phase_event.send(BossPhaseEvent { boss: attacker });
+            }
+            ActionKind::StatusEffects { .. } => {}
         }
+
+        playback.minions_alive = minions_alive;
+        playback.enemies_alive = enemies_alive;
+        playback.next_action += 1;
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_enemy_dead(
     mut commands: Commands,
     mut enemy_died_event: EventWriter<EnemyDiedEvent>,
     mut next_screen: ResMut<NextState<GameScreen>>,
-    mut inventory_items: ResMut<InventoryItems>,
+    mut pending_rewards: ResMut<PendingRewards>,
     mut battle_count: ResMut<BattleCount>,
-    enemy_query: Query<(Entity, &Stats, &DropRewards), With<Enemy>>,
+    mut gold: ResMut<Gold>,
+    balance: Res<Balance>,
+    playback: Res<BattlePlayback>,
+    enemy_query: Query<&DropRewards, With<Enemy>>,
+    mut minion_query: Query<(&mut Stats, &mut MinionLevel), (With<Minion>, Without<Enemy>)>,
 ) {
-    let (entity, stats, drop_rewards) = enemy_query.single();
+    // deaths are taken from the timeline, so that the playback always matches the simulation
+    let dead_enemies = playback
+        .enemy_entities
+        .iter()
+        .zip(playback.enemies_alive.iter())
+        .filter(|(_, &alive)| !alive);
+    for (&entity, _) in dead_enemies {
+        let Ok(drop_rewards) = enemy_query.get(entity) else {
+            continue;
+        };
 
-    if stats.current_hp > 0. {
-        return;
-    }
+        // drops are picked on the rewards screen, so that nothing is lost to a full inventory
+        pending_rewards
+            .items
+            .extend(drop_rewards.items.iter().cloned());
+        pending_rewards.gear.extend(drop_rewards.gear.clone());
 
-    for reward_item in drop_rewards.0.iter() {
-        if let Some(item) = inventory_items
-            .0
-            .iter_mut()
-            .find(|item| item.item_type == reward_item.item_type && item.tier == reward_item.tier)
-        {
-            item.quantity += reward_item.quantity;
-        } else if inventory_items.0.len() < MAX_ITEM_COUNT {
-            inventory_items.0.push(reward_item.clone());
-        }
+        enemy_died_event.send(EnemyDiedEvent);
+        commands.entity(entity).despawn_recursive();
     }
 
-    enemy_died_event.send(EnemyDiedEvent);
+    if !playback.is_won() {
+        return;
+    }
 
     // battle win
+    let minions = playback
+        .minion_entities
+        .iter()
+        .zip(playback.minions_alive.iter());
+    for (&entity, &alive) in minions {
+        if !alive {
+            continue;
+        }
+        if let Ok((mut stats, mut level)) = minion_query.get_mut(entity) {
+            level.add_experience(playback.experience, &balance.experience);
+            stats.heal(balance.recovery.battle_heal);
+        }
+    }
+    pending_rewards.gold = balance.battle_gold(battle_count.0);
+    gold.0 += pending_rewards.gold;
     battle_count.0 += 1;
-    commands.entity(entity).despawn_recursive();
-    next_screen.set(GameScreen::Summoning);
+    next_screen.set(GameScreen::Results);
 }
 
 fn handle_minion_dead(
     mut commands: Commands,
     mut minion_died_event: EventWriter<MinionDiedEvent>,
     mut next_screen: ResMut<NextState<GameScreen>>,
-    mut next_state: ResMut<NextState<GameState>>,
     mut minion_count: ResMut<MinionCount>,
-    minion_query: Query<(Entity, &Stats), With<Minion>>,
+    playback: Res<BattlePlayback>,
+    minion_query: Query<(), With<Minion>>,
     enemy_query: Query<Entity, With<Enemy>>,
 ) {
-    for (entity, stats) in minion_query.iter() {
-        if stats.current_hp > 0. {
+    // battle which was not decided in time is lost as well
+    let lost = playback.is_lost();
+
+    let minions = playback
+        .minion_entities
+        .iter()
+        .zip(playback.minions_alive.iter());
+    for (&entity, &alive) in minions {
+        if (alive && !lost) || !minion_query.contains(entity) {
             continue;
         }
 
         commands.entity(entity).despawn_recursive();
         minion_count.0 -= 1;
 
-        minion_died_event.send(MinionDiedEvent);
+        minion_died_event.send(MinionDiedEvent { minion: entity });
 
-        // game over
+        // game over follows the results of the battle
         if minion_count.0 == 0 {
-            if enemy_query.iter().next().is_some() {
-                commands.entity(enemy_query.single()).despawn_recursive();
+            for enemy_entity in enemy_query.iter() {
+                commands.entity(enemy_entity).despawn_recursive();
             }
-            next_screen.set(GameScreen::Other);
-            next_state.set(GameState::GameOver);
+            next_screen.set(GameScreen::Results);
         }
     }
 }
 
+fn clean_up_battle_playback(mut commands: Commands, mut query: Query<&mut StatusEffects>) {
+    commands.remove_resource::<BattlePlayback>();
+
+    for mut status_effects in query.iter_mut() {
+        status_effects.set_if_neq(StatusEffects::default());
+    }
+}
+
 fn prepare_battle_screen(
     mut commands: Commands,
     mut audio_instances: ResMut<Assets<AudioInstance>>,
@@ -7,9 +7,12 @@ use bevy_tweening::{
     Animator, EaseFunction, RepeatCount, RepeatStrategy, Tween,
 };
 
-use crate::{loading::AudioAssets, GameScreen, GameState};
+use crate::{loading::AudioAssets, stats::AbilityKind, GameScreen, GameState};
 
-use super::{EnemyAttackEvent, EnemyDiedEvent, MinionAttackEvent, MinionDiedEvent};
+use super::{
+    AbilityCastEvent, BossPhaseEvent, EnemyAttackEvent, EnemyDiedEvent, MinionAttackEvent,
+    MinionDiedEvent,
+};
 
 const ATTACK_DURATION: f32 = 0.2;
 const MINION_ATTACK_OFFSET: Vec2 = Vec2::new(30., 0.);
@@ -18,6 +21,11 @@ const ENEMY_ATTACK_OFFSET: Vec2 = Vec2::new(-60., 0.);
 const HURT_DURATION: f32 = 0.2;
 const HURT_COLOR: Color = Color::rgb(2., 0., 0.);
 
+const CAST_DURATION: f32 = 0.4;
+
+const BOSS_PHASE_DURATION: f32 = 1.;
+const BOSS_PHASE_COLOR: Color = Color::rgb(2., 0., 2.);
+
 pub struct EffectsPlugin;
 
 impl Plugin for EffectsPlugin {
@@ -29,6 +37,8 @@ impl Plugin for EffectsPlugin {
                 handle_enemy_attack_effect,
                 handle_enemy_hurt_effect,
                 handle_minion_hurt_effect,
+                handle_ability_cast_effect,
+                handle_boss_phase_effect,
             )
                 .run_if(in_state(GameScreen::Battle).and_then(in_state(GameState::Playing))),
         )
@@ -208,3 +218,62 @@ fn handle_enemy_hurt_effect(
         commands.entity(event.target).insert(Animator::new(tween));
     }
 }
+
+fn handle_ability_cast_effect(
+    mut commands: Commands,
+    mut ability_cast_event: EventReader<AbilityCastEvent>,
+    query: Query<()>,
+) {
+    for event in ability_cast_event.read() {
+        if query.get(event.caster).is_err() {
+            continue;
+        }
+
+        let color = match event.kind {
+            AbilityKind::AreaHit => Color::rgb(2., 1., 0.),
+            AbilityKind::Heal => Color::rgb(0., 2., 0.),
+            AbilityKind::Shield => Color::rgb(0., 1., 2.),
+            AbilityKind::Stun => Color::rgb(2., 2., 0.),
+        };
+
+        // start tween
+        let tween = Tween::new(
+            EaseFunction::QuadraticOut,
+            Duration::from_secs_f32(CAST_DURATION / 2.),
+            SpriteColorLens {
+                start: Color::WHITE,
+                end: color,
+            },
+        )
+        .with_repeat_count(RepeatCount::Finite(2))
+        .with_repeat_strategy(RepeatStrategy::MirroredRepeat);
+
+        commands.entity(event.caster).insert(Animator::new(tween));
+    }
+}
+
+fn handle_boss_phase_effect(
+    mut commands: Commands,
+    mut boss_phase_event: EventReader<BossPhaseEvent>,
+    query: Query<()>,
+) {
+    for event in boss_phase_event.read() {
+        if query.get(event.boss).is_err() {
+            continue;
+        }
+
+        // start tween
+        let tween = Tween::new(
+            EaseFunction::QuadraticInOut,
+            Duration::from_secs_f32(BOSS_PHASE_DURATION / 4.),
+            SpriteColorLens {
+                start: Color::WHITE,
+                end: BOSS_PHASE_COLOR,
+            },
+        )
+        .with_repeat_count(RepeatCount::Finite(4))
+        .with_repeat_strategy(RepeatStrategy::MirroredRepeat);
+
+        commands.entity(event.boss).insert(Animator::new(tween));
+    }
+}
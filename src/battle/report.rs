@@ -0,0 +1,152 @@
+use bevy::prelude::*;
+
+use crate::{formation::FormationSlot, GameScreen, GameState};
+
+use super::{
+    prepare_battle,
+    simulation::{ActionKind, BattleOutcome, Combatant},
+    BattlePlayback, EnemyAttackEvent, EnemyDiedEvent, MinionAttackEvent, MinionDiedEvent,
+};
+
+pub struct ReportPlugin;
+
+/// This plugin collects the combat breakdown of the battle from its events as it is played back,
+/// so that the results screen can show why the battle went the way it did.
+impl Plugin for ReportPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<BattleReport>()
+            .add_systems(
+                OnEnter(GameScreen::Battle),
+                start_battle_report.after(prepare_battle),
+            )
+            .add_systems(
+                PostUpdate,
+                record_battle_events.run_if(
+                    in_state(GameScreen::Battle)
+                        .and_then(in_state(GameState::Playing))
+                        .and_then(resource_exists::<BattlePlayback>),
+                ),
+            );
+    }
+}
+
+/// Combat breakdown of a single minion.
+pub struct MinionReport {
+    pub entity: Entity,
+    pub slot: FormationSlot,
+    /// Damage from hits and thorns hurting the attacking enemies.
+    pub damage_dealt: f32,
+    /// Damage from hits, thorns of the hit enemies and status effects.
+    pub damage_taken: f32,
+    pub regenerated: f32,
+    pub lost: bool,
+}
+
+/// Combat breakdown of the last battle, minions are in lineup order.
+#[derive(Resource, Default)]
+pub struct BattleReport {
+    pub outcome: BattleOutcome,
+    pub minions: Vec<MinionReport>,
+    pub enemy_count: usize,
+    /// Seconds since the start of the battle at which the enemies died.
+    pub kill_times: Vec<f32>,
+    /// Seconds the battle was played back for.
+    pub duration: f32,
+    /// Index of the first timeline action whose status effect damage was not added yet.
+    next_action: usize,
+}
+
+impl BattleReport {
+    pub fn is_victory(&self) -> bool {
+        self.outcome == BattleOutcome::Victory
+    }
+
+    pub fn lost_count(&self) -> usize {
+        self.minions.iter().filter(|minion| minion.lost).count()
+    }
+
+    fn minion_mut(&mut self, entity: Entity) -> Option<&mut MinionReport> {
+        self.minions
+            .iter_mut()
+            .find(|minion| minion.entity == entity)
+    }
+}
+
+fn start_battle_report(
+    mut commands: Commands,
+    playback: Res<BattlePlayback>,
+    slot_query: Query<&FormationSlot>,
+) {
+    let minions = playback
+        .minion_entities
+        .iter()
+        .zip(playback.result.minions_regenerated.iter())
+        .filter_map(|(&entity, &regenerated)| {
+            let &slot = slot_query.get(entity).ok()?;
+            Some(MinionReport {
+                entity,
+                slot,
+                damage_dealt: 0.,
+                damage_taken: 0.,
+                regenerated,
+                lost: false,
+            })
+        })
+        .collect();
+
+    commands.insert_resource(BattleReport {
+        outcome: playback.result.outcome,
+        minions,
+        enemy_count: playback.enemy_entities.len(),
+        kill_times: Vec::new(),
+        duration: 0.,
+        next_action: 0,
+    });
+}
+
+/// Add damage of the hits and thorns played back since the last frame to the breakdown. Status
+/// effects have no events, so their damage is taken from the timeline.
+fn record_battle_events(
+    mut report: ResMut<BattleReport>,
+    mut minion_attack_event: EventReader<MinionAttackEvent>,
+    mut enemy_attack_event: EventReader<EnemyAttackEvent>,
+    mut minion_died_event: EventReader<MinionDiedEvent>,
+    mut enemy_died_event: EventReader<EnemyDiedEvent>,
+    playback: Res<BattlePlayback>,
+) {
+    report.duration = playback.elapsed.min(playback.result.duration);
+
+    for event in minion_attack_event.read() {
+        if let Some(minion) = report.minion_mut(event.attacker) {
+            minion.damage_dealt += event.damage;
+            minion.damage_taken += event.recoil;
+        }
+    }
+    for event in enemy_attack_event.read() {
+        if let Some(minion) = report.minion_mut(event.target) {
+            minion.damage_taken += event.damage;
+            minion.damage_dealt += event.recoil;
+        }
+    }
+
+    for action in &playback.result.timeline[report.next_action..playback.next_action] {
+        if let (Combatant::Minion(index), ActionKind::StatusEffects { damage }) =
+            (action.actor, &action.kind)
+        {
+            if let Some(minion) = report.minion_mut(playback.minion_entities[index]) {
+                minion.damage_taken += damage;
+            }
+        }
+    }
+    report.next_action = playback.next_action;
+
+    for event in minion_died_event.read() {
+        if let Some(minion) = report.minion_mut(event.minion) {
+            minion.lost = true;
+        }
+    }
+    for _ in enemy_died_event.read() {
+        let duration = report.duration;
+        report.kill_times.push(duration);
+    }
+}
@@ -0,0 +1,1404 @@
+use rand::{rngs::StdRng, Rng, SeedableRng};
+
+use crate::{
+    stats::{Ability, AbilityKind, Row, SpecialAttack, Stats, TargetingPolicy},
+    status_effects::{StatusEffect, StatusEffectKind, StatusEffects},
+};
+
+/// Length of one simulation step in seconds.
+pub const SIMULATION_STEP: f32 = 1. / 60.;
+/// Battles which are not decided after this many seconds are lost.
+pub const MAX_BATTLE_DURATION: f32 = 300.;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default)]
+pub enum BattleOutcome {
+    Victory,
+    #[default]
+    Defeat,
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum Combatant {
+    /// Index into the minion stats passed to [`simulate_battle`].
+    Minion(usize),
+    /// Index into the enemy stats passed to [`simulate_battle`].
+    Enemy(usize),
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum HitKind {
+    Normal,
+    Critical,
+    /// The target evaded the hit and took no damage.
+    Evaded,
+}
+
+/// Rules which apply to every participant of a battle.
+#[derive(Clone, Debug)]
+pub struct BattleRules {
+    /// Evasion of every participant is capped to this.
+    pub max_evasion: f32,
+    /// Critical strike chance of every participant is capped to this.
+    pub max_crit_chance: f32,
+}
+
+impl Default for BattleRules {
+    fn default() -> Self {
+        Self {
+            max_evasion: 1.,
+            max_crit_chance: 1.,
+        }
+    }
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub enum ActionKind {
+    Attack {
+        target: Combatant,
+        /// Damage after armor of the target.
+        damage: f32,
+        hit: HitKind,
+        /// Damage the actor took back from thorns of the target.
+        recoil: f32,
+    },
+    Ability {
+        ability: Ability,
+        targets: Vec<Combatant>,
+        /// Damage after armor dealt to every target, zero for abilities which do not hit.
+        damage: Vec<f32>,
+        /// Damage the actor took back from thorns of every target.
+        recoil: Vec<f32>,
+    },
+    /// Special attack of a boss phase.
+    Special {
+        targets: Vec<Combatant>,
+        /// Damage after armor dealt to every target.
+        damage: Vec<f32>,
+        /// Damage the actor took back from thorns of every target.
+        recoil: Vec<f32>,
+    },
+    /// Boss started its next phase, phases are numbered from one, the battle starts in phase zero.
+    Phase { phase: usize },
+    /// Status effects of the actor expired or killed it, also recorded at the end of the battle
+    /// for damage over time not reported yet.
+    StatusEffects {
+        /// Damage status effects dealt to the actor since its last status effects action.
+        damage: f32,
+    },
+}
+
+/// Rows of minions and how the back row changes damage. Back row only changes damage while a
+/// front row ally is alive to cover it.
+#[derive(Clone, Debug)]
+pub struct Formation {
+    /// Row of every minion passed to [`simulate_battle`], missing ones fight in the front row.
+    pub rows: Vec<Row>,
+    /// Damage of attacks of covered back row minions is multiplied by this.
+    pub back_row_damage: f32,
+    /// Damage taken by covered back row minions is multiplied by this.
+    pub back_row_damage_taken: f32,
+}
+
+impl Default for Formation {
+    fn default() -> Self {
+        Self {
+            rows: Vec::new(),
+            back_row_damage: 1.,
+            back_row_damage_taken: 1.,
+        }
+    }
+}
+
+/// HP, mana and status effects of a participant.
+#[derive(Clone, PartialEq, Debug)]
+pub struct Vitals {
+    pub hp: f32,
+    pub mana: f32,
+    pub effects: StatusEffects,
+}
+
+/// Single attack, ability cast or status effect change which happened during the battle.
+#[derive(Clone, PartialEq, Debug)]
+pub struct BattleAction {
+    /// Time of the action in seconds since the start of the battle.
+    pub time: f32,
+    pub actor: Combatant,
+    pub kind: ActionKind,
+    /// Vitals of every minion right after the action.
+    pub minions: Vec<Vitals>,
+    /// Vitals of every enemy right after the action.
+    pub enemies: Vec<Vitals>,
+}
+
+#[derive(Clone, PartialEq, Debug)]
+pub struct BattleResult {
+    pub outcome: BattleOutcome,
+    /// Length of the battle in seconds.
+    pub duration: f32,
+    pub timeline: Vec<BattleAction>,
+    /// HP of every minion at the end of the battle, dead minions have HP at or below zero.
+    pub minions_hp: Vec<f32>,
+    /// HP of every enemy at the end of the battle.
+    pub enemies_hp: Vec<f32>,
+    /// HP every minion regenerated during the battle.
+    pub minions_regenerated: Vec<f32>,
+}
+
+struct Participant {
+    stats: Stats,
+    row: Row,
+    turn_accumulator: f32,
+    shield: f32,
+    effects: StatusEffects,
+    /// Number of boss phases started so far.
+    phase: usize,
+    special_timer: f32,
+    /// HP regenerated so far.
+    regenerated: f32,
+    /// Damage over time not recorded in the timeline yet.
+    effect_damage: f32,
+}
+
+impl Participant {
+    fn new(stats: &Stats, row: Row) -> Self {
+        let mut stats = stats.clone();
+        // battles start with an empty mana pool
+        stats.current_mana = 0.;
+
+        Self {
+            stats,
+            row,
+            turn_accumulator: 0.,
+            shield: 0.,
+            effects: StatusEffects::default(),
+            phase: 0,
+            special_timer: 0.,
+            regenerated: 0.,
+            effect_damage: 0.,
+        }
+    }
+
+    fn is_alive(&self) -> bool {
+        self.stats.current_hp > 0.
+    }
+
+    fn vitals(&self) -> Vitals {
+        Vitals {
+            hp: self.stats.current_hp,
+            mana: self.stats.current_mana,
+            effects: self.effects.clone(),
+        }
+    }
+
+    /// Regenerate HP and mana with stats changed by status effects and count the effects down,
+    /// returns true if any effect expired. Damage over time lowers regeneration, so HP lost in
+    /// the step counts as damage of the status effects.
+    fn regenerate(&mut self, delta: f32) -> bool {
+        let mut stats = self.effects.modify(&self.stats);
+        stats.regenerate_hp_and_mana(delta);
+        let change = stats.current_hp - self.stats.current_hp;
+        self.regenerated += change.max(0.);
+        self.effect_damage += (-change).max(0.);
+        self.stats.current_hp = stats.current_hp;
+        self.stats.current_mana = stats.current_mana;
+
+        self.effects.tick(delta)
+    }
+
+    /// Advance turn timer of the participant, returns true if participant should attack.
+    fn tick(&mut self, delta: f32) -> bool {
+        if self.effects.is_stunned() {
+            return false;
+        }
+
+        let speed = self.effects.modify(&self.stats).speed;
+        self.turn_accumulator += delta;
+
+        if self.turn_accumulator >= 1. / speed {
+            self.turn_accumulator -= 1. / speed;
+            return true;
+        }
+
+        false
+    }
+
+    /// Returns ability of the participant if its mana pool is full and the participant can cast.
+    fn ready_ability(&self) -> Option<Ability> {
+        let mana_full = self.stats.max_mana > 0. && self.stats.current_mana >= self.stats.max_mana;
+        if !self.is_alive() || self.effects.is_stunned() || !mana_full {
+            return None;
+        }
+
+        self.stats.ability
+    }
+
+    /// Advance timer of the special attack of the current phase, returns the attack if it should be
+    /// used.
+    fn tick_special(&mut self, delta: f32) -> Option<SpecialAttack> {
+        let special = self
+            .phase
+            .checked_sub(1)
+            .and_then(|phase| self.stats.phases[phase].special)?;
+        if self.effects.is_stunned() {
+            return None;
+        }
+
+        self.special_timer += delta;
+        if self.special_timer < special.cooldown {
+            return None;
+        }
+        self.special_timer -= special.cooldown;
+
+        Some(special)
+    }
+
+    /// Start the next phase if HP dropped to its threshold, returns true if it started.
+    fn advance_phase(&mut self) -> bool {
+        let Some(phase) = self.stats.phases.get(self.phase).copied() else {
+            return false;
+        };
+        if !self.is_alive() || self.stats.current_hp > self.stats.max_hp * phase.hp_threshold {
+            return false;
+        }
+
+        self.phase += 1;
+        self.stats.damage *= phase.damage_multiplier;
+        self.stats.speed *= phase.speed_multiplier;
+        self.special_timer = 0.;
+
+        true
+    }
+
+    /// Damage is absorbed by the shield first.
+    fn take_damage(&mut self, damage: f32) {
+        let absorbed = damage.min(self.shield);
+        self.shield -= absorbed;
+        self.stats.current_hp -= damage - absorbed;
+    }
+
+    fn heal(&mut self, amount: f32) {
+        self.stats.current_hp = (self.stats.current_hp + amount).min(self.stats.max_hp);
+    }
+}
+
+struct Battle {
+    rng: StdRng,
+    rules: BattleRules,
+    formation: Formation,
+    minions: Vec<Participant>,
+    enemies: Vec<Participant>,
+    timeline: Vec<BattleAction>,
+    /// Estimates only need the outcome, so they skip recording the timeline.
+    record_timeline: bool,
+    time: f32,
+}
+
+impl Battle {
+    fn participant(&self, combatant: Combatant) -> &Participant {
+        match combatant {
+            Combatant::Minion(index) => &self.minions[index],
+            Combatant::Enemy(index) => &self.enemies[index],
+        }
+    }
+
+    fn participant_mut(&mut self, combatant: Combatant) -> &mut Participant {
+        match combatant {
+            Combatant::Minion(index) => &mut self.minions[index],
+            Combatant::Enemy(index) => &mut self.enemies[index],
+        }
+    }
+
+    fn alive_minions(&self) -> Vec<Combatant> {
+        (0..self.minions.len())
+            .filter(|&index| self.minions[index].is_alive())
+            .map(Combatant::Minion)
+            .collect()
+    }
+
+    fn alive_enemies(&self) -> Vec<Combatant> {
+        (0..self.enemies.len())
+            .filter(|&index| self.enemies[index].is_alive())
+            .map(Combatant::Enemy)
+            .collect()
+    }
+
+    fn allies(&self, actor: Combatant) -> Vec<Combatant> {
+        match actor {
+            Combatant::Minion(_) => self.alive_minions(),
+            Combatant::Enemy(_) => self.alive_enemies(),
+        }
+    }
+
+    fn opponents(&self, actor: Combatant) -> Vec<Combatant> {
+        match actor {
+            Combatant::Minion(_) => self.alive_enemies(),
+            Combatant::Enemy(_) => self.alive_minions(),
+        }
+    }
+
+    /// Participant fights from the back row and an alive front row ally covers it. Once the front
+    /// row falls, the back row becomes the front.
+    fn is_covered(&self, combatant: Combatant) -> bool {
+        self.participant(combatant).row == Row::Back
+            && self
+                .allies(combatant)
+                .into_iter()
+                .any(|ally| self.participant(ally).row == Row::Front)
+    }
+
+    /// Pick an alive opponent of the actor according to its targeting policy. Taunting opponents
+    /// are picked first, then the front row.
+    fn choose_target(&mut self, actor: Combatant) -> Option<Combatant> {
+        let opponents = self.opponents(actor);
+        let taunting: Vec<_> = opponents
+            .iter()
+            .copied()
+            .filter(|&opponent| self.participant(opponent).stats.taunt)
+            .collect();
+        let front_row: Vec<_> = opponents
+            .iter()
+            .copied()
+            .filter(|&opponent| self.participant(opponent).row == Row::Front)
+            .collect();
+        let candidates = if !taunting.is_empty() {
+            taunting
+        } else if !front_row.is_empty() {
+            front_row
+        } else {
+            opponents
+        };
+        if candidates.is_empty() {
+            return None;
+        }
+
+        match self.participant(actor).stats.targeting {
+            TargetingPolicy::FrontMost => candidates.first().copied(),
+            TargetingPolicy::LowestHp => candidates.into_iter().min_by(|&a, &b| {
+                let a = self.participant(a).stats.current_hp;
+                let b = self.participant(b).stats.current_hp;
+                a.total_cmp(&b)
+            }),
+            TargetingPolicy::HighestDamage => candidates.into_iter().max_by(|&a, &b| {
+                let a = self.participant(a).stats.damage;
+                let b = self.participant(b).stats.damage;
+                a.total_cmp(&b)
+            }),
+            TargetingPolicy::Random => Some(candidates[self.rng.gen_range(0..candidates.len())]),
+        }
+    }
+
+    fn record(&mut self, actor: Combatant, kind: ActionKind) {
+        if !self.record_timeline {
+            return;
+        }
+        self.timeline.push(BattleAction {
+            time: self.time,
+            actor,
+            kind,
+            minions: self.minions.iter().map(Participant::vitals).collect(),
+            enemies: self.enemies.iter().map(Participant::vitals).collect(),
+        });
+    }
+
+    /// Evasion is rolled first, then critical strike, both chances are capped by the rules.
+    /// Armor of the target reduces the result.
+    /// The attacker then steals life from the damage and thorns of the target hurt it back.
+    /// Returns damage dealt before the shield and damage the attacker took from thorns.
+    fn hit(&mut self, actor: Combatant, target: Combatant, damage: f32) -> (f32, HitKind, f32) {
+        let attacker = &self.participant(actor).stats;
+        let (crit_chance, crit_multiplier, on_hit, lifesteal) = (
+            attacker.crit_chance.min(self.rules.max_crit_chance),
+            attacker.crit_multiplier,
+            attacker.on_hit,
+            attacker.lifesteal,
+        );
+        let (evasion, thorns) = {
+            let defender = &self.participant(target).stats;
+            (
+                defender.evasion.min(self.rules.max_evasion),
+                defender.thorns,
+            )
+        };
+
+        if self.rng.gen::<f32>() < evasion {
+            return (0., HitKind::Evaded, 0.);
+        }
+
+        let (damage, hit) = if self.rng.gen::<f32>() < crit_chance {
+            (damage * crit_multiplier, HitKind::Critical)
+        } else {
+            (damage, HitKind::Normal)
+        };
+
+        let damage_taken = if self.is_covered(target) {
+            self.formation.back_row_damage_taken
+        } else {
+            1.
+        };
+        let target = self.participant_mut(target);
+        let damage = (damage * damage_taken - target.stats.armor).max(0.);
+        target.take_damage(damage);
+        if let Some(effect) = on_hit {
+            target.effects.apply(effect);
+        }
+
+        let attacker = self.participant_mut(actor);
+        attacker.heal(damage * lifesteal);
+        let recoil = damage * thorns;
+        attacker.take_damage(recoil);
+
+        (damage, hit, recoil)
+    }
+
+    /// Covered back row attacks from range with reduced damage, frenzy raises damage for every
+    /// fallen ally.
+    fn attack(&mut self, actor: Combatant, target: Combatant) {
+        let fallen_allies = match actor {
+            Combatant::Minion(_) => self.minions.len() - self.alive_minions().len(),
+            Combatant::Enemy(_) => self.enemies.len() - self.alive_enemies().len(),
+        };
+        let row_damage = if self.is_covered(actor) {
+            self.formation.back_row_damage
+        } else {
+            1.
+        };
+        let attacker = self.participant(actor);
+        let damage = attacker.stats.damage
+            * row_damage
+            * (1. + attacker.stats.frenzy * fallen_allies as f32);
+        let (damage, hit, recoil) = self.hit(actor, target, damage);
+        self.record(
+            actor,
+            ActionKind::Attack {
+                target,
+                damage,
+                hit,
+                recoil,
+            },
+        );
+    }
+
+    fn cast(&mut self, actor: Combatant, ability: Ability) {
+        self.participant_mut(actor).stats.current_mana = 0.;
+
+        let targets = match ability.kind {
+            AbilityKind::AreaHit => self.opponents(actor),
+            AbilityKind::Heal => {
+                let allies = self.allies(actor);
+                let lowest = allies.into_iter().min_by(|&a, &b| {
+                    let a = self.participant(a).stats.current_hp;
+                    let b = self.participant(b).stats.current_hp;
+                    a.total_cmp(&b)
+                });
+                lowest.into_iter().collect()
+            }
+            AbilityKind::Shield => self.allies(actor),
+            AbilityKind::Stun => self.choose_target(actor).into_iter().collect(),
+        };
+
+        let mut damage = Vec::new();
+        let mut recoil = Vec::new();
+        for &target in targets.iter() {
+            let (dealt, thorns) = match ability.kind {
+                AbilityKind::AreaHit => {
+                    let (dealt, _, thorns) = self.hit(actor, target, ability.power);
+                    (dealt, thorns)
+                }
+                AbilityKind::Heal => {
+                    self.participant_mut(target).heal(ability.power);
+                    (0., 0.)
+                }
+                AbilityKind::Shield => {
+                    self.participant_mut(target).shield += ability.power;
+                    (0., 0.)
+                }
+                AbilityKind::Stun => {
+                    self.participant_mut(target)
+                        .effects
+                        .apply(StatusEffect::new(StatusEffectKind::Stun, 0., ability.power));
+                    (0., 0.)
+                }
+            };
+            damage.push(dealt);
+            recoil.push(thorns);
+        }
+
+        self.record(
+            actor,
+            ActionKind::Ability {
+                ability,
+                targets,
+                damage,
+                recoil,
+            },
+        );
+    }
+
+    /// Hit several opponents, taunting ones first and the rest in lineup order, front row first.
+    fn special_attack(&mut self, actor: Combatant, special: SpecialAttack) {
+        let (mut targets, mut others): (Vec<_>, Vec<_>) = self
+            .opponents(actor)
+            .into_iter()
+            .partition(|&opponent| self.participant(opponent).stats.taunt);
+        others.sort_by_key(|&opponent| self.participant(opponent).row);
+        targets.extend(others);
+        targets.truncate(special.target_count);
+
+        let damage = self.participant(actor).stats.damage * special.damage_multiplier;
+        let (damage, recoil) = targets
+            .iter()
+            .map(|&target| {
+                let (dealt, _, thorns) = self.hit(actor, target, damage);
+                (dealt, thorns)
+            })
+            .unzip();
+
+        self.record(
+            actor,
+            ActionKind::Special {
+                targets,
+                damage,
+                recoil,
+            },
+        );
+    }
+
+    /// Let the participant use its special attack, cast its ability or attack if it is ready to
+    /// do so.
+    fn act(&mut self, actor: Combatant) {
+        if !self.participant(actor).is_alive() || self.opponents(actor).is_empty() {
+            return;
+        }
+
+        if let Some(special) = self.participant_mut(actor).tick_special(SIMULATION_STEP) {
+            self.special_attack(actor, special);
+            return;
+        }
+
+        if let Some(ability) = self.participant(actor).ready_ability() {
+            self.cast(actor, ability);
+            return;
+        }
+
+        if !self.participant_mut(actor).tick(SIMULATION_STEP) {
+            return;
+        }
+
+        if let Some(target) = self.choose_target(actor) {
+            self.attack(actor, target);
+        }
+    }
+}
+
+/// Resolve battle between minions in the formation and a group of enemies without any rendering.
+/// The same stats, formation, rules and seed always produce the same result.
+pub fn simulate_battle(
+    minions: &[Stats],
+    enemies: &[Stats],
+    formation: &Formation,
+    rules: &BattleRules,
+    seed: u64,
+) -> BattleResult {
+    run_battle(minions, enemies, formation, rules, seed, true)
+}
+
+/// Resolve battle like [`simulate_battle`] does but leave the timeline empty, which is enough
+/// for estimates and a lot cheaper.
+pub fn estimate_battle(
+    minions: &[Stats],
+    enemies: &[Stats],
+    formation: &Formation,
+    rules: &BattleRules,
+    seed: u64,
+) -> BattleResult {
+    run_battle(minions, enemies, formation, rules, seed, false)
+}
+
+fn run_battle(
+    minions: &[Stats],
+    enemies: &[Stats],
+    formation: &Formation,
+    rules: &BattleRules,
+    seed: u64,
+    record_timeline: bool,
+) -> BattleResult {
+    let mut battle = Battle {
+        rng: StdRng::seed_from_u64(seed),
+        rules: rules.clone(),
+        minions: minions
+            .iter()
+            .enumerate()
+            .map(|(index, stats)| {
+                let row = formation.rows.get(index).copied().unwrap_or_default();
+                Participant::new(stats, row)
+            })
+            .collect(),
+        enemies: enemies
+            .iter()
+            .map(|stats| Participant::new(stats, Row::Front))
+            .collect(),
+        formation: formation.clone(),
+        timeline: Vec::new(),
+        record_timeline,
+        time: 0.,
+    };
+
+    // minions falling together with the last enemy lose, as the run can't go on without them
+    let outcome = loop {
+        if !battle.minions.iter().any(Participant::is_alive) || battle.time >= MAX_BATTLE_DURATION {
+            break BattleOutcome::Defeat;
+        }
+        if !battle.enemies.iter().any(Participant::is_alive) {
+            break BattleOutcome::Victory;
+        }
+
+        battle.time += SIMULATION_STEP;
+
+        let combatants = (0..battle.minions.len())
+            .map(Combatant::Minion)
+            .chain((0..battle.enemies.len()).map(Combatant::Enemy));
+        for combatant in combatants {
+            let participant = battle.participant_mut(combatant);
+            if !participant.is_alive() {
+                continue;
+            }
+
+            // playback only learns about expired effects and deaths from the timeline
+            let expired = participant.regenerate(SIMULATION_STEP);
+            if expired || !participant.is_alive() {
+                let damage = std::mem::take(&mut participant.effect_damage);
+                battle.record(combatant, ActionKind::StatusEffects { damage });
+            }
+        }
+
+        for index in 0..battle.minions.len() {
+            battle.act(Combatant::Minion(index));
+        }
+        for index in 0..battle.enemies.len() {
+            battle.act(Combatant::Enemy(index));
+        }
+
+        let combatants = (0..battle.minions.len())
+            .map(Combatant::Minion)
+            .chain((0..battle.enemies.len()).map(Combatant::Enemy));
+        for combatant in combatants {
+            while battle.participant_mut(combatant).advance_phase() {
+                let phase = battle.participant(combatant).phase;
+                battle.record(combatant, ActionKind::Phase { phase });
+            }
+        }
+    };
+
+    // damage over time of participants killed by hits or still alive was not recorded yet
+    let combatants = (0..battle.minions.len())
+        .map(Combatant::Minion)
+        .chain((0..battle.enemies.len()).map(Combatant::Enemy));
+    for combatant in combatants {
+        let damage = std::mem::take(&mut battle.participant_mut(combatant).effect_damage);
+        if damage > 0. {
+            battle.record(combatant, ActionKind::StatusEffects { damage });
+        }
+    }
+
+    BattleResult {
+        outcome,
+        duration: battle.time,
+        timeline: battle.timeline,
+        minions_hp: battle
+            .minions
+            .iter()
+            .map(|minion| minion.stats.current_hp)
+            .collect(),
+        enemies_hp: battle
+            .enemies
+            .iter()
+            .map(|enemy| enemy.stats.current_hp)
+            .collect(),
+        minions_regenerated: battle
+            .minions
+            .iter()
+            .map(|minion| minion.regenerated)
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::stats::{Ability, AbilityKind, BossPhase};
+
+    fn minion() -> Stats {
+        Stats {
+            current_hp: 100.,
+            max_hp: 100.,
+            damage: 20.,
+            ..Default::default()
+        }
+    }
+
+    fn enemy() -> Stats {
+        Stats {
+            current_hp: 150.,
+            max_hp: 150.,
+            damage: 15.,
+            speed: 0.8,
+            ..Default::default()
+        }
+    }
+
+    fn formation(rows: Vec<Row>) -> Formation {
+        Formation {
+            rows,
+            back_row_damage: 0.5,
+            back_row_damage_taken: 0.5,
+        }
+    }
+
+    /// HP the minion lost on the action, minions start at full HP.
+    fn hp_lost(result: &BattleResult, action: usize, minion: usize) -> f32 {
+        let before = match action.checked_sub(1) {
+            Some(previous) => result.timeline[previous].minions[minion].hp,
+            None => self::minion().max_hp,
+        };
+        before - result.timeline[action].minions[minion].hp
+    }
+
+    #[test]
+    fn same_seed_gives_same_result() {
+        let minions = [minion(), minion()];
+        let enemies = [enemy(), enemy()];
+
+        let first = simulate_battle(
+            &minions,
+            &enemies,
+            &Formation::default(),
+            &BattleRules::default(),
+            42,
+        );
+        let second = simulate_battle(
+            &minions,
+            &enemies,
+            &Formation::default(),
+            &BattleRules::default(),
+            42,
+        );
+
+        assert_eq!(first, second);
+        assert!(!first.timeline.is_empty());
+    }
+
+    #[test]
+    fn estimate_matches_simulation_without_timeline() {
+        let minions = [minion(), minion()];
+        let enemies = [enemy(), enemy()];
+
+        let simulated = simulate_battle(
+            &minions,
+            &enemies,
+            &Formation::default(),
+            &BattleRules::default(),
+            42,
+        );
+        let estimated = estimate_battle(
+            &minions,
+            &enemies,
+            &Formation::default(),
+            &BattleRules::default(),
+            42,
+        );
+
+        assert!(estimated.timeline.is_empty());
+        assert_eq!(
+            BattleResult {
+                timeline: Vec::new(),
+                ..simulated
+            },
+            estimated
+        );
+    }
+
+    #[test]
+    fn overwhelming_lineup_wins() {
+        let strong = Stats {
+            current_hp: 10000.,
+            max_hp: 10000.,
+            damage: 500.,
+            speed: 2.,
+            ..Default::default()
+        };
+        let result = simulate_battle(
+            &[strong.clone(), strong],
+            &[enemy(), enemy()],
+            &Formation::default(),
+            &BattleRules::default(),
+            7,
+        );
+
+        assert_eq!(result.outcome, BattleOutcome::Victory);
+        assert!(result.minions_hp.iter().all(|&hp| hp > 0.));
+        assert!(result.enemies_hp.iter().all(|&hp| hp <= 0.));
+    }
+
+    #[test]
+    fn enemies_stop_acting_once_minions_fall() {
+        let minion = Stats {
+            current_hp: 1.,
+            max_hp: 1.,
+            ..Default::default()
+        };
+        let killer = Stats {
+            damage: 100.,
+            speed: 120.,
+            ..enemy()
+        };
+        let healer = Stats {
+            max_mana: 10.,
+            mana_regeneration: 6000.,
+            ability: Some(Ability {
+                kind: AbilityKind::Heal,
+                power: 10.,
+            }),
+            ..enemy()
+        };
+        let result = simulate_battle(
+            &[minion],
+            &[killer, healer],
+            &Formation::default(),
+            &BattleRules::default(),
+            7,
+        );
+
+        assert_eq!(result.outcome, BattleOutcome::Defeat);
+        assert!(result
+            .timeline
+            .iter()
+            .all(|action| action.actor == Combatant::Enemy(0)));
+    }
+
+    #[test]
+    fn evasion_is_capped() {
+        let untouchable = Stats {
+            evasion: 2.,
+            ..minion()
+        };
+        let rules = BattleRules {
+            max_evasion: 0.5,
+            ..Default::default()
+        };
+        let result = simulate_battle(&[untouchable], &[enemy()], &Formation::default(), &rules, 7);
+
+        assert!(result.timeline.iter().any(|action| matches!(
+            action.kind,
+            ActionKind::Attack {
+                target: Combatant::Minion(0),
+                hit: HitKind::Normal,
+                ..
+            }
+        )));
+    }
+
+    #[test]
+    fn thorns_and_damage_over_time_are_recorded() {
+        let poisoner = Stats {
+            on_hit: Some(StatusEffect::new(StatusEffectKind::Poison, 5., 3.)),
+            ..minion()
+        };
+        let spiky = Stats {
+            thorns: 0.5,
+            ..enemy()
+        };
+        let result = simulate_battle(
+            &[poisoner],
+            &[spiky],
+            &Formation::default(),
+            &BattleRules::default(),
+            7,
+        );
+
+        let recoil: f32 = result
+            .timeline
+            .iter()
+            .map(|action| match action.kind {
+                ActionKind::Attack { recoil, .. } if action.actor == Combatant::Minion(0) => recoil,
+                _ => 0.,
+            })
+            .sum();
+        let effect_damage: f32 = result
+            .timeline
+            .iter()
+            .map(|action| match action.kind {
+                ActionKind::StatusEffects { damage } if action.actor == Combatant::Enemy(0) => {
+                    damage
+                }
+                _ => 0.,
+            })
+            .sum();
+        assert!(recoil > 0.);
+        assert!(effect_damage > 0.);
+    }
+
+    #[test]
+    fn empty_lineup_loses() {
+        let result = simulate_battle(
+            &[],
+            &[enemy()],
+            &Formation::default(),
+            &BattleRules::default(),
+            7,
+        );
+
+        assert_eq!(result.outcome, BattleOutcome::Defeat);
+        assert!(result.timeline.is_empty());
+    }
+
+    fn phase(hp_threshold: f32, special: Option<SpecialAttack>) -> BossPhase {
+        BossPhase {
+            hp_threshold,
+            damage_multiplier: 2.,
+            speed_multiplier: 1.5,
+            special,
+        }
+    }
+
+    #[test]
+    fn phase_starts_at_its_threshold_once() {
+        let boss = Stats {
+            current_hp: 60.,
+            max_hp: 100.,
+            damage: 10.,
+            phases: vec![phase(0.5, None), phase(0.25, None)],
+            ..Default::default()
+        };
+        let mut boss = Participant::new(&boss, Row::Front);
+
+        assert!(!boss.advance_phase());
+
+        boss.stats.current_hp = 50.;
+        assert!(boss.advance_phase());
+        assert!(!boss.advance_phase());
+        assert_eq!(boss.phase, 1);
+        assert_eq!(boss.stats.damage, 20.);
+        assert_eq!(boss.stats.speed, 1.5);
+    }
+
+    #[test]
+    fn special_attack_hits_taunting_targets_first() {
+        let boss = Stats {
+            phases: vec![phase(
+                1.,
+                Some(SpecialAttack {
+                    damage_multiplier: 1.,
+                    target_count: 2,
+                    cooldown: 0.5,
+                }),
+            )],
+            ..enemy()
+        };
+        let taunter = Stats {
+            taunt: true,
+            ..minion()
+        };
+        let result = simulate_battle(
+            &[minion(), minion(), taunter, minion()],
+            &[boss],
+            &Formation::default(),
+            &BattleRules::default(),
+            7,
+        );
+
+        let phase_index = result
+            .timeline
+            .iter()
+            .position(|action| matches!(action.kind, ActionKind::Phase { phase: 1 }))
+            .unwrap();
+        let special_index = result
+            .timeline
+            .iter()
+            .position(|action| matches!(action.kind, ActionKind::Special { .. }))
+            .unwrap();
+        assert!(phase_index < special_index);
+
+        let ActionKind::Special { targets, .. } = &result.timeline[special_index].kind else {
+            unreachable!();
+        };
+        assert_eq!(targets, &vec![Combatant::Minion(2), Combatant::Minion(0)]);
+    }
+
+    #[test]
+    fn back_row_without_front_row_is_not_scaled() {
+        let result = simulate_battle(
+            &[minion(), minion()],
+            &[enemy()],
+            &formation(vec![Row::Back, Row::Back]),
+            &BattleRules::default(),
+            7,
+        );
+
+        let damage = |actor| {
+            result.timeline.iter().find_map(|action| match action.kind {
+                ActionKind::Attack { damage, .. } if action.actor == actor => Some(damage),
+                _ => None,
+            })
+        };
+        assert_eq!(damage(Combatant::Minion(0)), Some(20.));
+        assert_eq!(damage(Combatant::Enemy(0)), Some(15.));
+    }
+
+    #[test]
+    fn front_row_is_targeted_first() {
+        let weak = Stats {
+            current_hp: 50.,
+            ..minion()
+        };
+        let hunter = Stats {
+            targeting: TargetingPolicy::LowestHp,
+            ..enemy()
+        };
+        let result = simulate_battle(
+            &[weak, minion()],
+            &[hunter],
+            &formation(vec![Row::Back, Row::Front]),
+            &BattleRules::default(),
+            7,
+        );
+
+        let first_target = result.timeline.iter().find_map(|action| match action.kind {
+            ActionKind::Attack { target, .. } if action.actor == Combatant::Enemy(0) => {
+                Some(target)
+            }
+            _ => None,
+        });
+        assert_eq!(first_target, Some(Combatant::Minion(1)));
+    }
+
+    #[test]
+    fn covered_back_row_deals_and_takes_scaled_damage() {
+        let caster = Stats {
+            max_mana: 10.,
+            mana_regeneration: 20.,
+            ability: Some(Ability {
+                kind: AbilityKind::AreaHit,
+                power: 10.,
+            }),
+            ..enemy()
+        };
+        let result = simulate_battle(
+            &[minion(), minion()],
+            &[caster],
+            &formation(vec![Row::Front, Row::Back]),
+            &BattleRules::default(),
+            7,
+        );
+
+        let back_row_attack = result.timeline.iter().find_map(|action| match action.kind {
+            ActionKind::Attack { damage, .. } if action.actor == Combatant::Minion(1) => {
+                Some(damage)
+            }
+            _ => None,
+        });
+        let area_hit = result
+            .timeline
+            .iter()
+            .position(|action| matches!(action.kind, ActionKind::Ability { .. }))
+            .unwrap();
+        assert_eq!(back_row_attack, Some(10.));
+        assert_eq!(hp_lost(&result, area_hit, 0), 10.);
+        assert_eq!(hp_lost(&result, area_hit, 1), 5.);
+    }
+
+    #[test]
+    fn special_attack_hits_front_row_first() {
+        let boss = Stats {
+            phases: vec![BossPhase {
+                hp_threshold: 1.,
+                damage_multiplier: 1.,
+                speed_multiplier: 1.,
+                special: Some(SpecialAttack {
+                    damage_multiplier: 1.,
+                    target_count: 2,
+                    cooldown: 0.5,
+                }),
+            }],
+            ..enemy()
+        };
+        let result = simulate_battle(
+            &[minion(), minion(), minion()],
+            &[boss],
+            &formation(vec![Row::Back, Row::Front, Row::Back]),
+            &BattleRules::default(),
+            7,
+        );
+
+        let special = result
+            .timeline
+            .iter()
+            .position(|action| matches!(action.kind, ActionKind::Special { .. }))
+            .unwrap();
+        let ActionKind::Special { targets, .. } = &result.timeline[special].kind else {
+            unreachable!();
+        };
+        assert_eq!(targets, &vec![Combatant::Minion(1), Combatant::Minion(0)]);
+        assert_eq!(hp_lost(&result, special, 1), 15.);
+        assert_eq!(hp_lost(&result, special, 0), 7.5);
+    }
+
+    /// Enemy which does not attack during the first minion attacks.
+    fn idle_enemy() -> Stats {
+        Stats {
+            speed: 0.01,
+            ..enemy()
+        }
+    }
+
+    /// Minion HP and the action kind of every attack of the actor.
+    fn attacks(result: &BattleResult, actor: Combatant) -> Vec<(f32, ActionKind)> {
+        result
+            .timeline
+            .iter()
+            .filter(|action| {
+                action.actor == actor && matches!(action.kind, ActionKind::Attack { .. })
+            })
+            .map(|action| (action.minions[0].hp, action.kind.clone()))
+            .collect()
+    }
+
+    #[test]
+    fn lifesteal_heals_the_attacker() {
+        let vampire = Stats {
+            current_hp: 50.,
+            lifesteal: 0.5,
+            ..minion()
+        };
+        let result = simulate_battle(
+            &[vampire],
+            &[idle_enemy()],
+            &Formation::default(),
+            &BattleRules::default(),
+            7,
+        );
+
+        let (hp, _) = attacks(&result, Combatant::Minion(0))[0].clone();
+        assert_eq!(hp, 60.);
+    }
+
+    #[test]
+    fn thorns_hurt_the_attacker() {
+        let spiky = Stats {
+            thorns: 0.5,
+            ..idle_enemy()
+        };
+        let result = simulate_battle(
+            &[minion()],
+            &[spiky],
+            &Formation::default(),
+            &BattleRules::default(),
+            7,
+        );
+
+        let (hp, kind) = attacks(&result, Combatant::Minion(0))[0].clone();
+        assert!(matches!(kind, ActionKind::Attack { recoil, .. } if recoil == 10.));
+        assert_eq!(hp, 90.);
+    }
+
+    #[test]
+    fn thorns_recoil_can_kill_the_attacker() {
+        let frail = Stats {
+            current_hp: 5.,
+            ..minion()
+        };
+        let spiky = Stats {
+            thorns: 1.,
+            ..idle_enemy()
+        };
+        let result = simulate_battle(
+            &[frail],
+            &[spiky],
+            &Formation::default(),
+            &BattleRules::default(),
+            7,
+        );
+
+        assert_eq!(attacks(&result, Combatant::Minion(0)).len(), 1);
+        assert!(attacks(&result, Combatant::Enemy(0)).is_empty());
+        assert!(result.minions_hp[0] <= 0.);
+        assert_eq!(result.outcome, BattleOutcome::Defeat);
+    }
+
+    #[test]
+    fn frenzy_rises_with_fallen_allies() {
+        let fodder = Stats {
+            current_hp: 1.,
+            max_hp: 1.,
+            ..idle_enemy()
+        };
+        let berserker = Stats {
+            damage: 10.,
+            speed: 2.,
+            frenzy: 0.5,
+            ..enemy()
+        };
+        let result = simulate_battle(
+            &[minion()],
+            &[fodder, berserker],
+            &Formation::default(),
+            &BattleRules::default(),
+            7,
+        );
+
+        let damage: Vec<_> = result
+            .timeline
+            .iter()
+            .filter_map(|action| match action.kind {
+                ActionKind::Attack { damage, .. } if action.actor == Combatant::Enemy(1) => {
+                    Some((action.enemies[0].hp > 0., damage))
+                }
+                _ => None,
+            })
+            .collect();
+        assert!(damage.contains(&(true, 10.)));
+        assert!(damage.contains(&(false, 15.)));
+        assert!(damage
+            .iter()
+            .all(|&(fodder_alive, damage)| damage == if fodder_alive { 10. } else { 15. }));
+    }
+
+    #[test]
+    fn falling_with_last_enemy_loses() {
+        let minion = Stats {
+            current_hp: 10.,
+            max_hp: 10.,
+            damage: 100.,
+            speed: 2.,
+            ..Default::default()
+        };
+        let enemy = Stats {
+            current_hp: 50.,
+            max_hp: 50.,
+            speed: 0.1,
+            thorns: 1.,
+            ..Default::default()
+        };
+        let result = simulate_battle(
+            &[minion],
+            &[enemy],
+            &Formation::default(),
+            &BattleRules::default(),
+            7,
+        );
+
+        let last = result.timeline.last().unwrap();
+        assert!(last.minions[0].hp <= 0.);
+        assert!(last.enemies[0].hp <= 0.);
+        assert_eq!(result.outcome, BattleOutcome::Defeat);
+    }
+
+    #[test]
+    fn ability_is_cast_once_mana_is_full() {
+        let healer = Stats {
+            max_mana: 10.,
+            mana_regeneration: 5.,
+            ability: Some(Ability {
+                kind: AbilityKind::Heal,
+                power: 30.,
+            }),
+            ..minion()
+        };
+        let wounded = Stats {
+            current_hp: 40.,
+            ..minion()
+        };
+        let sturdy = Stats {
+            current_hp: 1000.,
+            max_hp: 1000.,
+            ..idle_enemy()
+        };
+        let result = simulate_battle(
+            &[healer, wounded],
+            &[sturdy],
+            &Formation::default(),
+            &BattleRules::default(),
+            7,
+        );
+
+        let casts: Vec<_> = result
+            .timeline
+            .iter()
+            .filter(|action| matches!(action.kind, ActionKind::Ability { .. }))
+            .collect();
+        assert!(casts.iter().all(|cast| cast.actor == Combatant::Minion(0)));
+
+        let first = casts[0];
+        assert!((first.time - 2.).abs() <= SIMULATION_STEP * 1.5);
+        assert_eq!(first.minions[0].mana, 0.);
+        assert_eq!(first.minions[1].hp, 70.);
+        let ActionKind::Ability { targets, .. } = &first.kind else {
+            unreachable!();
+        };
+        assert_eq!(targets, &vec![Combatant::Minion(1)]);
+    }
+
+    /// First opponent the minion attacks with the targeting policy.
+    fn first_target(targeting: TargetingPolicy, enemies: &[Stats]) -> Option<Combatant> {
+        let minion = Stats {
+            targeting,
+            ..minion()
+        };
+        let result = simulate_battle(
+            &[minion],
+            enemies,
+            &Formation::default(),
+            &BattleRules::default(),
+            7,
+        );
+
+        result.timeline.iter().find_map(|action| match action.kind {
+            ActionKind::Attack { target, .. } if action.actor == Combatant::Minion(0) => {
+                Some(target)
+            }
+            _ => None,
+        })
+    }
+
+    #[test]
+    fn targeting_policies_pick_their_targets() {
+        let weak = Stats {
+            current_hp: 50.,
+            ..idle_enemy()
+        };
+        let strong = Stats {
+            damage: 40.,
+            ..idle_enemy()
+        };
+        let enemies = [idle_enemy(), weak, strong];
+
+        assert_eq!(
+            first_target(TargetingPolicy::FrontMost, &enemies),
+            Some(Combatant::Enemy(0))
+        );
+        assert_eq!(
+            first_target(TargetingPolicy::LowestHp, &enemies),
+            Some(Combatant::Enemy(1))
+        );
+        assert_eq!(
+            first_target(TargetingPolicy::HighestDamage, &enemies),
+            Some(Combatant::Enemy(2))
+        );
+    }
+
+    #[test]
+    fn taunting_opponents_are_attacked_first() {
+        let weak = Stats {
+            current_hp: 50.,
+            ..idle_enemy()
+        };
+        let taunter = Stats {
+            taunt: true,
+            ..idle_enemy()
+        };
+        let enemies = [idle_enemy(), weak, taunter];
+
+        for targeting in [TargetingPolicy::FrontMost, TargetingPolicy::LowestHp] {
+            assert_eq!(first_target(targeting, &enemies), Some(Combatant::Enemy(2)));
+        }
+    }
+}
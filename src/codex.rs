@@ -0,0 +1,289 @@
+use bevy::prelude::*;
+
+use crate::{
+    balance::{Balance, RecipeBalance},
+    experience::LevelUpChoice,
+    loading::{FontAssets, TextureAssets},
+    mouse_control::Clickable,
+    sacrifice::SacrificeConfirmation,
+    status_effects::status_effect_icon,
+    summoning::stat_icon,
+    utils::num_to_roman,
+    GameScreen, GameState,
+};
+
+const CODEX_BUTTON_POS: Vec3 = Vec3::new(300., 1080. / 2. - 36., 0.);
+const CODEX_BUTTON_SIZE: Vec2 = Vec2::new(240., 52.);
+const CODEX_Z: f32 = 300.;
+const CODEX_TEXT_SIZE: f32 = 48.;
+const RECIPE_AREA_HEIGHT: f32 = 800.;
+const MAX_RECIPE_ROW_HEIGHT: f32 = 96.;
+const RECIPE_NAME_X: f32 = -560.;
+const RECIPE_INGREDIENTS_X: f32 = -500.;
+const RECIPE_BONUSES_X: f32 = -40.;
+const RECIPE_ICON_SPACING: f32 = 112.;
+
+pub struct CodexPlugin;
+
+/// This plugin shows the codex of summoning recipes, which is opened from the summoning screen.
+impl Plugin for CodexPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_state::<CodexState>()
+            .add_systems(OnEnter(GameScreen::Summoning), spawn_codex_button)
+            .add_systems(OnExit(GameScreen::Summoning), despawn_codex_button)
+            .add_systems(OnEnter(CodexState::Open), spawn_codex)
+            .add_systems(OnExit(CodexState::Open), despawn_codex)
+            .add_systems(
+                Update,
+                handle_codex_control.run_if(
+                    in_state(GameState::Playing)
+                        .and_then(in_state(GameScreen::Summoning))
+                        .and_then(not(resource_exists::<LevelUpChoice>))
+                        .and_then(not(resource_exists::<SacrificeConfirmation>)),
+                ),
+            );
+    }
+}
+
+/// Summoning screen ignores clicks while the codex is open.
+#[derive(States, PartialEq, Eq, Debug, Hash, Clone, Copy, Default)]
+pub enum CodexState {
+    #[default]
+    Closed,
+    Open,
+}
+
+#[derive(Component)]
+struct CodexButton;
+
+#[derive(Component)]
+struct CodexEntity;
+
+fn spawn_codex_button(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    fonts: Res<FontAssets>,
+) {
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: textures.square.clone(),
+                sprite: Sprite {
+                    custom_size: Some(CODEX_BUTTON_SIZE),
+                    color: Color::DARK_GRAY,
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(CODEX_BUTTON_POS),
+                ..Default::default()
+            },
+            Clickable::default(),
+            CodexButton,
+        ))
+        .with_children(|parent| {
+            parent.spawn(Text2dBundle {
+                text: Text::from_section(
+                    "RECIPES",
+                    TextStyle {
+                        font: fonts.texts.clone(),
+                        font_size: CODEX_TEXT_SIZE,
+                        color: Color::WHITE,
+                    },
+                ),
+                transform: Transform::from_xyz(0., 0., 1.),
+                ..Default::default()
+            });
+        });
+}
+
+fn despawn_codex_button(
+    mut commands: Commands,
+    mut next_codex_state: ResMut<NextState<CodexState>>,
+    query: Query<Entity, With<CodexButton>>,
+) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+    next_codex_state.set(CodexState::Closed);
+}
+
+/// The button opens the codex and any click closes it.
+fn handle_codex_control(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    codex_state: Res<State<CodexState>>,
+    mut next_codex_state: ResMut<NextState<CodexState>>,
+    button_query: Query<&Clickable, With<CodexButton>>,
+) {
+    match codex_state.get() {
+        CodexState::Closed => {
+            if button_query
+                .iter()
+                .any(|clickable| clickable.just_left_clicked)
+            {
+                next_codex_state.set(CodexState::Open);
+            }
+        }
+        CodexState::Open => {
+            if mouse_input.any_just_pressed([MouseButton::Left, MouseButton::Right]) {
+                next_codex_state.set(CodexState::Closed);
+            }
+        }
+    }
+}
+
+/// Ingredients of the recipe followed by the stat tiers and on hit effect it adds.
+fn spawn_recipe_row(
+    parent: &mut ChildBuilder,
+    textures: &TextureAssets,
+    fonts: &FontAssets,
+    recipe: &RecipeBalance,
+    y: f32,
+    row_height: f32,
+) {
+    let icon_size = row_height * 0.6;
+    let text_style = |color: Color| TextStyle {
+        font: fonts.texts.clone(),
+        font_size: row_height * 0.5,
+        color,
+    };
+
+    parent.spawn(Text2dBundle {
+        text: Text::from_section(recipe.kind.name(), text_style(Color::GOLD)),
+        text_anchor: bevy::sprite::Anchor::CenterRight,
+        transform: Transform::from_xyz(RECIPE_NAME_X, y, 1.),
+        ..Default::default()
+    });
+
+    for (index, item_type) in recipe.ingredients.iter().enumerate() {
+        let x = RECIPE_INGREDIENTS_X + RECIPE_ICON_SPACING * index as f32;
+        if index > 0 {
+            parent.spawn(Text2dBundle {
+                text: Text::from_section("+", text_style(Color::WHITE)),
+                transform: Transform::from_xyz(
+                    x - RECIPE_ICON_SPACING / 2. + icon_size / 2.,
+                    y,
+                    1.,
+                ),
+                ..Default::default()
+            });
+        }
+        parent.spawn(SpriteBundle {
+            texture: item_type.icon(textures),
+            sprite: Sprite {
+                color: Color::CYAN,
+                custom_size: Some(Vec2::splat(icon_size)),
+                anchor: bevy::sprite::Anchor::CenterLeft,
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(x, y, 1.),
+            ..Default::default()
+        });
+    }
+
+    parent.spawn(Text2dBundle {
+        text: Text::from_section("=", text_style(Color::WHITE)),
+        transform: Transform::from_xyz(RECIPE_BONUSES_X - RECIPE_ICON_SPACING / 2., y, 1.),
+        ..Default::default()
+    });
+
+    let mut bonuses: Vec<_> = recipe
+        .stat_bonuses
+        .iter()
+        .map(|&(stat, tier)| (stat_icon(textures, stat), tier))
+        .collect();
+    if let Some((kind, tier)) = recipe.on_hit {
+        bonuses.push((status_effect_icon(textures, kind), tier));
+    }
+
+    for (index, (texture, tier)) in bonuses.into_iter().enumerate() {
+        let x = RECIPE_BONUSES_X + RECIPE_ICON_SPACING * 1.5 * index as f32;
+        parent.spawn(Text2dBundle {
+            text: Text::from_section(
+                format!("+{}", num_to_roman(tier)),
+                TextStyle {
+                    font: fonts.tier_numbers.clone(),
+                    font_size: row_height * 0.6,
+                    color: Color::WHITE,
+                },
+            ),
+            text_anchor: bevy::sprite::Anchor::CenterLeft,
+            transform: Transform::from_xyz(x, y, 1.),
+            ..Default::default()
+        });
+        parent.spawn(SpriteBundle {
+            texture,
+            sprite: Sprite {
+                color: Color::CYAN,
+                custom_size: Some(Vec2::splat(icon_size)),
+                anchor: bevy::sprite::Anchor::CenterRight,
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(x + RECIPE_ICON_SPACING * 1.5 - 16., y, 1.),
+            ..Default::default()
+        });
+    }
+}
+
+fn spawn_codex(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    fonts: Res<FontAssets>,
+    balance: Res<Balance>,
+) {
+    let row_height =
+        (RECIPE_AREA_HEIGHT / balance.recipes.len().max(1) as f32).min(MAX_RECIPE_ROW_HEIGHT);
+
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: textures.square.clone(),
+                sprite: Sprite {
+                    custom_size: Some(Vec2::new(1920., 1080.)),
+                    color: Color::BLACK.with_a(0.9),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0., 0., CODEX_Z),
+                ..Default::default()
+            },
+            CodexEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn(Text2dBundle {
+                text: Text::from_section(
+                    "RECIPES",
+                    TextStyle {
+                        font: fonts.texts.clone(),
+                        font_size: 96.,
+                        color: Color::WHITE,
+                    },
+                ),
+                transform: Transform::from_xyz(0., 1080. / 2. - 80., 1.),
+                ..Default::default()
+            });
+
+            let top = RECIPE_AREA_HEIGHT / 2. - 40.;
+            for (index, recipe) in balance.recipes.iter().enumerate() {
+                let y = top - row_height * (index as f32 + 0.5);
+                spawn_recipe_row(parent, &textures, &fonts, recipe, y, row_height);
+            }
+
+            parent.spawn(Text2dBundle {
+                text: Text::from_section(
+                    "MINIONS SUMMONED WITH ALL INGREDIENTS OF A RECIPE GET ITS BONUSES\n(CLICK ANYWHERE TO CLOSE)",
+                    TextStyle {
+                        font: fonts.texts.clone(),
+                        font_size: CODEX_TEXT_SIZE * 0.7,
+                        color: Color::WHITE,
+                    },
+                ),
+                text_anchor: bevy::sprite::Anchor::BottomCenter,
+                transform: Transform::from_xyz(0., -1080. / 2. + 24., 1.),
+                ..Default::default()
+            });
+        });
+}
+
+fn despawn_codex(mut commands: Commands, query: Query<Entity, With<CodexEntity>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
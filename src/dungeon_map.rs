@@ -0,0 +1,259 @@
+use bevy::prelude::*;
+use rand::{rngs::StdRng, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::balance::DungeonBalance;
+
+/// Most nodes a single floor of the map can have.
+pub const MAX_FLOOR_NODES: usize = 4;
+
+/// Chance of a node to get an additional path to a neighbouring node of the next floor.
+const BRANCH_CHANCE: f64 = 0.3;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum NodeKind {
+    Fight,
+    /// Fight with stronger enemies which drop better items.
+    Elite,
+    /// Heals minions.
+    Rest,
+    /// Gives items without a fight.
+    Treasure,
+    Shop,
+    /// Last fight of the act.
+    Boss,
+}
+
+#[derive(Clone, Debug, Serialize, Deserialize)]
+pub struct MapNode {
+    pub kind: NodeKind,
+    /// Indices of nodes on the next floor which can be visited after this one.
+    pub next: Vec<usize>,
+}
+
+/// Map of the current act. Floors are visited one after another, a single node on each, and the
+/// last floor holds the boss. A new act starts once the boss is beaten.
+#[derive(Resource, Clone, Default, Debug, Serialize, Deserialize)]
+pub struct DungeonMap {
+    /// Number of the current act, starting at one. Zero before the first act is generated.
+    pub act: usize,
+    pub floors: Vec<Vec<MapNode>>,
+    /// Index of the node visited on every floor so far.
+    pub route: Vec<usize>,
+    /// Nodes visited during the whole run.
+    pub steps: usize,
+}
+
+impl DungeonMap {
+    /// Generate map of the next act, the route starts over.
+    pub fn start_next_act(&mut self, rng: &mut StdRng, balance: &DungeonBalance) {
+        self.act += 1;
+        self.route.clear();
+        self.floors = (0..balance.floor_count)
+            .map(|floor| {
+                let node_count = rng.gen_range(balance.min_floor_nodes..=balance.max_floor_nodes);
+                (0..node_count)
+                    .map(|_| {
+                        let kind = if floor == 0 {
+                            NodeKind::Fight
+                        } else if floor == balance.floor_count - 1 {
+                            NodeKind::Rest
+                        } else {
+                            random_node_kind(rng, balance)
+                        };
+                        MapNode {
+                            kind,
+                            next: Vec::new(),
+                        }
+                    })
+                    .collect()
+            })
+            .collect();
+        self.floors.push(vec![MapNode {
+            kind: NodeKind::Boss,
+            next: Vec::new(),
+        }]);
+
+        for floor in 0..self.floors.len() - 1 {
+            self.connect_floor(rng, floor);
+        }
+    }
+
+    /// Connect nodes of the floor to the nearest nodes of the next floor, so that every node leads
+    /// somewhere and can be reached, and add a few branches. Paths never cross each other.
+    fn connect_floor(&mut self, rng: &mut StdRng, floor: usize) {
+        let count = self.floors[floor].len();
+        let next_count = self.floors[floor + 1].len();
+        let nearest = |index: usize, count: usize, other_count: usize| {
+            (((index as f32 + 0.5) / count as f32 * other_count as f32) as usize)
+                .min(other_count - 1)
+        };
+
+        let mut paths = Vec::new();
+        for index in 0..count {
+            paths.push((index, nearest(index, count, next_count)));
+        }
+        for next_index in 0..next_count {
+            let path = (nearest(next_index, next_count, count), next_index);
+            if !paths.contains(&path) {
+                paths.push(path);
+            }
+        }
+
+        for index in 0..count {
+            if !rng.gen_bool(BRANCH_CHANCE) {
+                continue;
+            }
+
+            let target = nearest(index, count, next_count);
+            let neighbour = if rng.gen_bool(0.5) {
+                target.checked_sub(1)
+            } else {
+                Some(target + 1).filter(|&neighbour| neighbour < next_count)
+            };
+            let Some(neighbour) = neighbour else {
+                continue;
+            };
+
+            let crosses = paths.iter().any(|&(from, to)| {
+                (from < index && to > neighbour) || (from > index && to < neighbour)
+            });
+            if !crosses && !paths.contains(&(index, neighbour)) {
+                paths.push((index, neighbour));
+            }
+        }
+
+        for (from, to) in paths {
+            self.floors[floor][from].next.push(to);
+        }
+        for node in self.floors[floor].iter_mut() {
+            node.next.sort_unstable();
+        }
+    }
+
+    /// True once the boss of the act is beaten, or before the first act.
+    pub fn is_finished(&self) -> bool {
+        self.route.len() >= self.floors.len()
+    }
+
+    /// Floor of the nodes which can be visited next.
+    pub fn current_floor(&self) -> usize {
+        self.route.len()
+    }
+
+    /// Indices of nodes on the current floor which can be visited next.
+    pub fn reachable(&self) -> Vec<usize> {
+        match self.route.last() {
+            Some(&last) => self.floors[self.route.len() - 1][last].next.clone(),
+            None => (0..self.floors.first().map_or(0, Vec::len)).collect(),
+        }
+    }
+
+    pub fn visit(&mut self, index: usize) {
+        self.route.push(index);
+        self.steps += 1;
+    }
+}
+
+fn random_node_kind(rng: &mut StdRng, balance: &DungeonBalance) -> NodeKind {
+    let weights = [
+        (NodeKind::Fight, balance.fight_weight),
+        (NodeKind::Elite, balance.elite_weight),
+        (NodeKind::Rest, balance.rest_weight),
+        (NodeKind::Treasure, balance.treasure_weight),
+        (NodeKind::Shop, balance.shop_weight),
+    ];
+    let total: u32 = weights.iter().map(|(_, weight)| weight).sum();
+
+    let mut roll = rng.gen_range(0..total);
+    for (kind, weight) in weights {
+        if roll < weight {
+            return kind;
+        }
+        roll -= weight;
+    }
+
+    unreachable!("roll is always lower than the total weight")
+}
+
+#[cfg(test)]
+mod tests {
+    use rand::SeedableRng;
+
+    use super::*;
+    use crate::balance::Balance;
+
+    fn dungeon_balance() -> DungeonBalance {
+        let balance: Balance = ron::from_str(include_str!("../assets/balance.ron")).unwrap();
+        DungeonBalance {
+            min_floor_nodes: 1,
+            max_floor_nodes: MAX_FLOOR_NODES,
+            ..balance.dungeon
+        }
+    }
+
+    fn generate_maps() -> impl Iterator<Item = DungeonMap> {
+        let balance = dungeon_balance();
+        (0..200).map(move |seed| {
+            let mut map = DungeonMap::default();
+            map.start_next_act(&mut StdRng::seed_from_u64(seed), &balance);
+            map
+        })
+    }
+
+    #[test]
+    fn every_node_is_reachable() {
+        for map in generate_maps() {
+            for floor in 1..map.floors.len() {
+                for index in 0..map.floors[floor].len() {
+                    assert!(
+                        map.floors[floor - 1]
+                            .iter()
+                            .any(|node| node.next.contains(&index)),
+                        "node {index} on floor {floor} can not be reached"
+                    );
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn every_node_leads_to_the_boss() {
+        for map in generate_maps() {
+            let boss_floor = map.floors.last().unwrap();
+            assert_eq!(boss_floor.len(), 1);
+            assert_eq!(boss_floor[0].kind, NodeKind::Boss);
+
+            // nodes of the last floor before the boss lead to it, so it is enough to check that
+            // every other node leads to a node of the next floor
+            for floor in 0..map.floors.len() - 1 {
+                let next_count = map.floors[floor + 1].len();
+                for node in map.floors[floor].iter() {
+                    assert!(!node.next.is_empty(), "node on floor {floor} is a dead end");
+                    assert!(node.next.iter().all(|&next| next < next_count));
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn paths_do_not_cross() {
+        for map in generate_maps() {
+            for (floor, nodes) in map.floors.iter().enumerate() {
+                let paths: Vec<_> = nodes
+                    .iter()
+                    .enumerate()
+                    .flat_map(|(from, node)| node.next.iter().map(move |&to| (from, to)))
+                    .collect();
+                for &(from, to) in paths.iter() {
+                    assert!(
+                        !paths
+                            .iter()
+                            .any(|&(other_from, other_to)| other_from > from && other_to < to),
+                        "paths from floor {floor} cross"
+                    );
+                }
+            }
+        }
+    }
+}
@@ -1,33 +1,115 @@
+use crate::gear::Gear;
+use crate::health_bar::HealthBar;
 use crate::summoning::SummoningItem;
 use crate::GameScreen;
 use bevy::prelude::*;
+use rand::rngs::StdRng;
+use serde::Deserialize;
+
+pub const MAX_ENEMY_COUNT: usize = 3;
+const ENEMY_SIZE: Vec2 = Vec2::new(640., 768.);
+/// Height available for the whole group of enemies.
+const ENEMY_COLUMN_HEIGHT: f32 = 960.;
+const ENEMY_HEALTH_BAR_WIDTH: f32 = 256.;
+const NDC_ENEMY_X: f32 = 0.5;
+const NDC_ENEMY_Y: f32 = 0.1;
 
 pub struct EnemyPlugin;
 
 impl Plugin for EnemyPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameScreen::Battle), reposition_enemy);
+        app.add_systems(OnEnter(GameScreen::Battle), reposition_enemies);
     }
 }
 
 #[derive(Component)]
 pub struct Enemy;
 
-#[derive(Component, Clone)]
-pub struct DropRewards(pub Vec<SummoningItem>);
+#[derive(Component, Clone, Default)]
+pub struct DropRewards {
+    pub items: Vec<SummoningItem>,
+    pub gear: Option<Gear>,
+}
+
+#[derive(Resource)]
+pub struct RewardRng(pub StdRng);
+
+/// Behaviour an enemy archetype brings into battles, fractions are fractions of damage.
+#[derive(Clone, Copy, PartialEq, Debug, Deserialize)]
+pub enum EnemyPassive {
+    /// Draws attacks of all minions.
+    Taunt,
+    /// Damage of attacks rises by the fraction for every fallen ally.
+    Frenzy(f32),
+    /// Heals for the fraction of damage dealt by hits.
+    Lifesteal(f32),
+}
 
-fn reposition_enemy(
+impl EnemyPassive {
+    pub fn name(&self) -> &'static str {
+        match self {
+            EnemyPassive::Taunt => "TAUNT",
+            EnemyPassive::Frenzy(_) => "FRENZY",
+            EnemyPassive::Lifesteal(_) => "LIFESTEAL",
+        }
+    }
+}
+
+/// Modifier which makes every enemy of an elite group stronger.
+#[derive(Clone, Copy, PartialEq, Eq, Debug)]
+pub enum EliteModifier {
+    /// Deals more damage and attacks faster once HP drops low.
+    Enraged,
+    /// Hurts attackers back.
+    Thorns,
+    /// Regenerates a part of max HP every second.
+    Regenerating,
+}
+
+impl EliteModifier {
+    pub const ALL: [EliteModifier; 3] = [
+        EliteModifier::Enraged,
+        EliteModifier::Thorns,
+        EliteModifier::Regenerating,
+    ];
+
+    pub fn name(&self) -> &'static str {
+        match self {
+            EliteModifier::Enraged => "ENRAGED",
+            EliteModifier::Thorns => "THORNS",
+            EliteModifier::Regenerating => "REGENERATING",
+        }
+    }
+}
+
+/// Enemies of a group are stacked vertically, the more enemies there are, the smaller they get.
+fn reposition_enemies(
+    mut commands: Commands,
     camera: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
-    mut query: Query<(&mut Transform, &mut Visibility), With<Enemy>>,
+    mut query: Query<(Entity, &mut Transform, &mut Sprite, &mut Visibility), With<Enemy>>,
 ) {
     let (camera, camera_transform) = camera.single();
-    let spawn_pos = camera
-        .ndc_to_world(camera_transform, Vec3::new(0.5, 0.1, 0.))
+    let enemy_count = query.iter().count();
+    let scale = (ENEMY_COLUMN_HEIGHT / (enemy_count as f32 * ENEMY_SIZE.y)).min(1.);
+    let size = ENEMY_SIZE * scale;
+
+    let center = camera
+        .ndc_to_world(camera_transform, Vec3::new(NDC_ENEMY_X, NDC_ENEMY_Y, 0.))
         .unwrap();
 
-    let (mut transform, mut visibility) = query.single_mut();
+    for (index, (entity, mut transform, mut sprite, mut visibility)) in query.iter_mut().enumerate()
+    {
+        let offset = (index as f32 - (enemy_count as f32 - 1.) / 2.) * size.y;
 
-    transform.translation = spawn_pos;
-    transform.translation.z = 0.;
-    *visibility = Visibility::Visible;
+        transform.translation = center - Vec3::new(0., offset, 0.);
+        transform.translation.z = 0.;
+        sprite.custom_size = Some(size);
+        *visibility = Visibility::Visible;
+
+        commands.entity(entity).insert(HealthBar {
+            width: ENEMY_HEALTH_BAR_WIDTH * scale,
+            offset: Vec2::new(0., size.y / 2. - 4.),
+            ..Default::default()
+        });
+    }
 }
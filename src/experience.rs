@@ -0,0 +1,373 @@
+use bevy::prelude::*;
+use rand::{seq::SliceRandom, Rng};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    balance::{Balance, ExperienceBalance},
+    codex::CodexState,
+    formation::MinionClickedEvent,
+    gear::no_gear_selected,
+    loading::{FontAssets, TextureAssets},
+    minions::Minion,
+    mouse_control::Clickable,
+    run_seed::{RngStream, RunSeed},
+    sacrifice::SacrificeConfirmation,
+    stats::{StatKind, Stats},
+    summoning::{add_stat_tiers, format_stat, stat_icon},
+    utils::num_to_roman,
+    GameScreen, GameState,
+};
+
+const LEVEL_UP_Z: f32 = 300.;
+/// Marker sits above the health bar and HP of the minion.
+const LEVEL_UP_MARKER_Y: f32 = 140.;
+const LEVEL_UP_MARKER_SIZE: f32 = 32.;
+const OPTION_CARD_SIZE: Vec2 = Vec2::new(320., 240.);
+const OPTION_CARD_SPACING: f32 = 400.;
+const OPTION_ICON_SIZE: f32 = 96.;
+const LEVEL_UP_TEXT_SIZE: f32 = 48.;
+
+pub struct ExperiencePlugin;
+
+/// This plugin lets minions spend their level ups on the summoning screen, experience itself is
+/// handed out by the battle.
+impl Plugin for ExperiencePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnExit(GameScreen::Summoning),
+            (despawn_level_up_markers, close_level_up_choice),
+        )
+        .add_systems(
+            Update,
+            (
+                update_level_up_markers,
+                handle_open_level_up_choice.run_if(
+                    in_state(CodexState::Closed)
+                        .and_then(not(resource_exists::<LevelUpChoice>))
+                        .and_then(not(resource_exists::<SacrificeConfirmation>))
+                        .and_then(no_gear_selected),
+                ),
+                spawn_level_up_choice.run_if(resource_added::<LevelUpChoice>),
+                handle_level_up_choice.run_if(resource_exists::<LevelUpChoice>),
+            )
+                .run_if(in_state(GameState::Playing).and_then(in_state(GameScreen::Summoning))),
+        );
+    }
+}
+
+/// Level of a minion, it is kept for the whole run.
+#[derive(Component, Serialize, Deserialize, Clone, Debug)]
+pub struct MinionLevel {
+    pub level: u32,
+    /// Experience gathered towards the next level.
+    pub experience: f32,
+    /// Level ups whose stat was not chosen yet.
+    pub unspent_level_ups: u32,
+    /// Number of minions summoned earlier in the run, so that every minion gets its own level up
+    /// options.
+    pub summon_index: usize,
+}
+
+impl Default for MinionLevel {
+    fn default() -> Self {
+        Self {
+            level: 1,
+            experience: 0.,
+            unspent_level_ups: 0,
+            summon_index: 0,
+        }
+    }
+}
+
+impl MinionLevel {
+    pub fn add_experience(&mut self, experience: f32, balance: &ExperienceBalance) {
+        self.experience += experience;
+        while self.experience >= balance.level_experience(self.level) {
+            self.experience -= balance.level_experience(self.level);
+            self.level += 1;
+            self.unspent_level_ups += 1;
+        }
+    }
+}
+
+/// Stats offered to the minion for its oldest unspent level up, summoning screen ignores clicks
+/// while the choice is shown.
+#[derive(Resource)]
+pub struct LevelUpChoice {
+    minion: Entity,
+    options: Vec<StatKind>,
+}
+
+#[derive(Component)]
+struct LevelUpMarker;
+
+#[derive(Component)]
+struct LevelUpOption(StatKind);
+
+#[derive(Component)]
+struct LevelUpEntity;
+
+/// Minions with unspent level ups are marked above their heads.
+fn update_level_up_markers(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    minion_query: Query<(Entity, &MinionLevel, Option<&Children>), With<Minion>>,
+    marker_query: Query<(), With<LevelUpMarker>>,
+) {
+    for (entity, level, children) in minion_query.iter() {
+        let marker = children
+            .into_iter()
+            .flatten()
+            .find(|&&child| marker_query.contains(child));
+
+        match (marker, level.unspent_level_ups > 0) {
+            (None, true) => {
+                commands.entity(entity).with_children(|parent| {
+                    parent.spawn((
+                        Text2dBundle {
+                            text: Text::from_section(
+                                "LEVEL UP",
+                                TextStyle {
+                                    font: fonts.texts.clone(),
+                                    font_size: LEVEL_UP_MARKER_SIZE,
+                                    color: Color::GOLD,
+                                },
+                            ),
+                            text_anchor: bevy::sprite::Anchor::BottomCenter,
+                            transform: Transform::from_xyz(0., LEVEL_UP_MARKER_Y, 1.),
+                            ..Default::default()
+                        },
+                        LevelUpMarker,
+                    ));
+                });
+            }
+            (Some(&marker), false) => {
+                commands.entity(marker).despawn_recursive();
+            }
+            _ => {}
+        }
+    }
+}
+
+fn despawn_level_up_markers(mut commands: Commands, query: Query<Entity, With<LevelUpMarker>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Clicked minion with an unspent level up gets its stats to choose from. Options depend only on
+/// the minion and its level, so they stay the same when the run is resumed.
+pub fn handle_open_level_up_choice(
+    mut commands: Commands,
+    mut minion_clicked_event: EventReader<MinionClickedEvent>,
+    run_seed: Res<RunSeed>,
+    balance: Res<Balance>,
+    minion_query: Query<&MinionLevel, With<Minion>>,
+) {
+    for &MinionClickedEvent { minion: entity } in minion_clicked_event.read() {
+        let Ok(level) = minion_query.get(entity) else {
+            continue;
+        };
+        if level.unspent_level_ups == 0 {
+            continue;
+        }
+
+        let reached_level = level.level + 1 - level.unspent_level_ups;
+        let minion_seed = RunSeed(run_seed.rng(RngStream::LevelUps, level.summon_index).gen());
+        let mut rng = minion_seed.rng(RngStream::LevelUps, reached_level as usize);
+        let options = StatKind::ALL
+            .choose_multiple(&mut rng, balance.experience.level_up_choices)
+            .copied()
+            .collect();
+
+        commands.insert_resource(LevelUpChoice {
+            minion: entity,
+            options,
+        });
+        return;
+    }
+}
+
+fn spawn_level_up_choice(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    fonts: Res<FontAssets>,
+    balance: Res<Balance>,
+    choice: Res<LevelUpChoice>,
+) {
+    let tiers = balance.experience.level_up_tiers;
+    let text_style = |font_size: f32, color: Color| TextStyle {
+        font: fonts.texts.clone(),
+        font_size,
+        color,
+    };
+
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: textures.square.clone(),
+                sprite: Sprite {
+                    custom_size: Some(Vec2::new(1920., 1080.)),
+                    color: Color::BLACK.with_a(0.9),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0., 0., LEVEL_UP_Z),
+                ..Default::default()
+            },
+            LevelUpEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn(Text2dBundle {
+                text: Text::from_section("LEVEL UP", text_style(96., Color::GOLD)),
+                transform: Transform::from_xyz(0., 1080. / 2. - 160., 1.),
+                ..Default::default()
+            });
+
+            let first_x = -OPTION_CARD_SPACING * (choice.options.len() as f32 - 1.) / 2.;
+            for (index, &stat) in choice.options.iter().enumerate() {
+                let (_, inc) = balance.minion.base_and_inc(stat);
+                parent
+                    .spawn((
+                        SpriteBundle {
+                            texture: textures.square.clone(),
+                            sprite: Sprite {
+                                custom_size: Some(OPTION_CARD_SIZE),
+                                color: Color::DARK_GRAY,
+                                ..Default::default()
+                            },
+                            transform: Transform::from_xyz(
+                                first_x + OPTION_CARD_SPACING * index as f32,
+                                0.,
+                                1.,
+                            ),
+                            ..Default::default()
+                        },
+                        Clickable::default(),
+                        LevelUpOption(stat),
+                    ))
+                    .with_children(|card| {
+                        card.spawn(Text2dBundle {
+                            text: Text::from_section(
+                                format!("+{}", num_to_roman(tiers)),
+                                TextStyle {
+                                    font: fonts.tier_numbers.clone(),
+                                    font_size: OPTION_ICON_SIZE,
+                                    color: Color::WHITE,
+                                },
+                            ),
+                            text_anchor: bevy::sprite::Anchor::CenterRight,
+                            transform: Transform::from_xyz(-8., 24., 1.),
+                            ..Default::default()
+                        });
+                        card.spawn(SpriteBundle {
+                            texture: stat_icon(&textures, stat),
+                            sprite: Sprite {
+                                color: Color::CYAN,
+                                custom_size: Some(Vec2::splat(OPTION_ICON_SIZE)),
+                                anchor: bevy::sprite::Anchor::CenterLeft,
+                                ..Default::default()
+                            },
+                            transform: Transform::from_xyz(8., 24., 1.),
+                            ..Default::default()
+                        });
+                        card.spawn(Text2dBundle {
+                            text: Text::from_section(
+                                format!("+{}", format_stat(stat, inc * tiers as f32)),
+                                text_style(LEVEL_UP_TEXT_SIZE, Color::LIME_GREEN),
+                            ),
+                            transform: Transform::from_xyz(0., -72., 1.),
+                            ..Default::default()
+                        });
+                    });
+            }
+
+            parent.spawn(Text2dBundle {
+                text: Text::from_section(
+                    "CHOOSE A STAT TO IMPROVE\n(CLICK ELSEWHERE TO DECIDE LATER)",
+                    text_style(LEVEL_UP_TEXT_SIZE * 0.7, Color::WHITE),
+                ),
+                text_anchor: bevy::sprite::Anchor::BottomCenter,
+                transform: Transform::from_xyz(0., -1080. / 2. + 24., 1.),
+                ..Default::default()
+            });
+        });
+}
+
+/// Chosen stat is raised and the level up spent, any other click keeps it for later.
+fn handle_level_up_choice(
+    mut commands: Commands,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    balance: Res<Balance>,
+    choice: Res<LevelUpChoice>,
+    option_query: Query<(&Clickable, &LevelUpOption)>,
+    mut minion_query: Query<(&mut Stats, &mut MinionLevel), With<Minion>>,
+    entity_query: Query<Entity, With<LevelUpEntity>>,
+) {
+    if !mouse_input.any_just_pressed([MouseButton::Left, MouseButton::Right]) {
+        return;
+    }
+
+    let chosen = option_query
+        .iter()
+        .find(|(clickable, _)| clickable.just_left_clicked)
+        .map(|(_, &LevelUpOption(stat))| stat);
+    if let (Some(stat), Ok((mut stats, mut level))) = (chosen, minion_query.get_mut(choice.minion))
+    {
+        add_stat_tiers(
+            &mut stats,
+            &balance.minion,
+            stat,
+            balance.experience.level_up_tiers,
+        );
+        level.unspent_level_ups = level.unspent_level_ups.saturating_sub(1);
+    }
+
+    commands.remove_resource::<LevelUpChoice>();
+    for entity in entity_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn close_level_up_choice(mut commands: Commands, query: Query<Entity, With<LevelUpEntity>>) {
+    commands.remove_resource::<LevelUpChoice>();
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance() -> ExperienceBalance {
+        ExperienceBalance {
+            experience_per_tier: 4.,
+            level_base: 20.,
+            level_inc: 10.,
+            level_up_tiers: 1,
+            level_up_choices: 3,
+        }
+    }
+
+    #[test]
+    fn level_up_at_threshold() {
+        let mut level = MinionLevel::default();
+
+        level.add_experience(19., &balance());
+        assert_eq!((level.level, level.unspent_level_ups), (1, 0));
+
+        level.add_experience(1., &balance());
+        assert_eq!((level.level, level.unspent_level_ups), (2, 1));
+        assert_eq!(level.experience, 0.);
+    }
+
+    #[test]
+    fn one_battle_gives_several_level_ups() {
+        let mut level = MinionLevel::default();
+
+        level.add_experience(65., &balance());
+
+        assert_eq!((level.level, level.unspent_level_ups), (3, 2));
+        assert_eq!(level.experience, 15.);
+    }
+}
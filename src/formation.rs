@@ -0,0 +1,223 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    codex::CodexState,
+    experience::LevelUpChoice,
+    gear::{handle_gear_control, no_gear_selected},
+    loading::{FontAssets, TextureAssets},
+    minions::{Minion, MAX_MINION_COUNT, MINION_SIZE},
+    mouse_control::{Clickable, MouseInfo},
+    sacrifice::SacrificeConfirmation,
+    stats::Row,
+    GameScreen, GameState,
+};
+
+/// Every row fits all minions, so any formation can be chosen.
+pub const ROW_SLOT_COUNT: usize = MAX_MINION_COUNT;
+
+/// Back row is on the left and front row on the right, facing the enemies like in the battle.
+/// Slots with the lowest index are next to the center.
+const FORMATION_Y: f32 = -460.;
+const ROW_CENTER_GAP: f32 = 160.;
+const SLOT_SPACING: f32 = 224.;
+const SLOT_LABEL_SIZE: f32 = 32.;
+/// Dragged minion is drawn above the others.
+const DRAGGED_MINION_Z: f32 = 50.;
+/// Mouse has to move this far with the button held before a press turns into a drag.
+const DRAG_THRESHOLD: f32 = 16.;
+
+pub struct FormationPlugin;
+
+/// This plugin lets the player drag minions between the front and back row slots on the summoning
+/// screen. Presses on minions which do not turn into drags are sent as clicks.
+impl Plugin for FormationPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<MinionClickedEvent>()
+            .add_systems(OnEnter(GameScreen::Summoning), spawn_formation_slots)
+            .add_systems(OnExit(GameScreen::Summoning), clean_formation)
+            .add_systems(
+                Update,
+                handle_minion_drag.before(handle_gear_control).run_if(
+                    in_state(GameState::Playing)
+                        .and_then(in_state(GameScreen::Summoning))
+                        .and_then(in_state(CodexState::Closed))
+                        .and_then(not(resource_exists::<LevelUpChoice>))
+                        .and_then(not(resource_exists::<SacrificeConfirmation>))
+                        .and_then(no_gear_selected),
+                ),
+            );
+    }
+}
+
+/// Place of a minion in the formation, minions keep it for the whole run.
+#[derive(Component, Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub struct FormationSlot {
+    pub row: Row,
+    pub index: usize,
+}
+
+impl FormationSlot {
+    /// Slots in lineup order, front row first.
+    pub fn all() -> impl Iterator<Item = FormationSlot> {
+        [Row::Front, Row::Back]
+            .into_iter()
+            .flat_map(|row| (0..ROW_SLOT_COUNT).map(move |index| FormationSlot { row, index }))
+    }
+
+    /// First slot in lineup order which is not taken.
+    pub fn first_free(taken: &[FormationSlot]) -> Option<FormationSlot> {
+        FormationSlot::all().find(|slot| !taken.contains(slot))
+    }
+
+    /// Position of the slot on the summoning screen.
+    pub fn position(&self) -> Vec3 {
+        let offset = ROW_CENTER_GAP / 2. + MINION_SIZE / 2. + SLOT_SPACING * self.index as f32;
+        let x = match self.row {
+            Row::Front => offset,
+            Row::Back => -offset,
+        };
+
+        Vec3::new(x, FORMATION_Y, 0.)
+    }
+}
+
+/// Minions in lineup order, front row first, together with their rows.
+pub fn lineup<T>(mut minions: Vec<(FormationSlot, T)>) -> (Vec<T>, Vec<Row>) {
+    minions.sort_by_key(|&(slot, _)| (slot.row, slot.index));
+    minions
+        .into_iter()
+        .map(|(slot, minion)| (minion, slot.row))
+        .unzip()
+}
+
+/// Minion pressed without the mouse moving far enough to be dragged.
+#[derive(Event)]
+pub struct MinionClickedEvent {
+    pub minion: Entity,
+}
+
+#[derive(Resource)]
+struct DraggedMinion {
+    minion: Entity,
+    start: Vec2,
+    is_dragging: bool,
+}
+
+#[derive(Component)]
+struct FormationEntity;
+
+/// Empty slots are outlined and labeled with their row.
+fn spawn_formation_slots(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    fonts: Res<FontAssets>,
+) {
+    for slot in FormationSlot::all() {
+        let label = match slot.row {
+            Row::Front => "FRONT",
+            Row::Back => "BACK",
+        };
+
+        commands
+            .spawn((
+                SpriteBundle {
+                    texture: textures.square.clone(),
+                    sprite: Sprite {
+                        custom_size: Some(Vec2::splat(MINION_SIZE)),
+                        color: Color::DARK_GRAY.with_a(0.4),
+                        ..Default::default()
+                    },
+                    transform: Transform::from_translation(slot.position() - Vec3::Z * 0.5),
+                    ..Default::default()
+                },
+                FormationEntity,
+            ))
+            .with_children(|parent| {
+                parent.spawn(Text2dBundle {
+                    text: Text::from_section(
+                        label,
+                        TextStyle {
+                            font: fonts.texts.clone(),
+                            font_size: SLOT_LABEL_SIZE,
+                            color: Color::GRAY,
+                        },
+                    ),
+                    transform: Transform::from_xyz(0., 0., 0.1),
+                    ..Default::default()
+                });
+            });
+    }
+}
+
+/// Dragged minion follows the mouse and is dropped into the nearest slot, a minion already there
+/// swaps places with it.
+fn handle_minion_drag(
+    mut commands: Commands,
+    mouse_info: Res<MouseInfo>,
+    dragged_minion: Option<ResMut<DraggedMinion>>,
+    mut minion_clicked_event: EventWriter<MinionClickedEvent>,
+    clickable_query: Query<(Entity, &Clickable), With<Minion>>,
+    mut minion_query: Query<(Entity, &mut Transform, &mut FormationSlot), With<Minion>>,
+) {
+    let Some(mut dragged) = dragged_minion else {
+        if let Some((minion, _)) = clickable_query
+            .iter()
+            .find(|(_, clickable)| clickable.just_left_clicked)
+        {
+            commands.insert_resource(DraggedMinion {
+                minion,
+                start: mouse_info.position,
+                is_dragging: false,
+            });
+        }
+        return;
+    };
+
+    if mouse_info.pressed {
+        if mouse_info.position.distance(dragged.start) > DRAG_THRESHOLD {
+            dragged.is_dragging = true;
+        }
+        if dragged.is_dragging {
+            if let Ok((_, mut transform, _)) = minion_query.get_mut(dragged.minion) {
+                transform.translation = mouse_info.position.extend(DRAGGED_MINION_Z);
+            }
+        }
+        return;
+    }
+
+    commands.remove_resource::<DraggedMinion>();
+    if !dragged.is_dragging {
+        minion_clicked_event.send(MinionClickedEvent {
+            minion: dragged.minion,
+        });
+        return;
+    }
+
+    let Ok((_, _, &old_slot)) = minion_query.get(dragged.minion) else {
+        return;
+    };
+    let new_slot = FormationSlot::all()
+        .min_by(|a, b| {
+            let a = a.position().xy().distance(mouse_info.position);
+            let b = b.position().xy().distance(mouse_info.position);
+            a.total_cmp(&b)
+        })
+        .unwrap();
+
+    for (entity, mut transform, mut slot) in minion_query.iter_mut() {
+        if entity == dragged.minion {
+            *slot = new_slot;
+        } else if *slot == new_slot {
+            *slot = old_slot;
+        }
+        transform.translation = slot.position();
+    }
+}
+
+fn clean_formation(mut commands: Commands, query: Query<Entity, With<FormationEntity>>) {
+    commands.remove_resource::<DraggedMinion>();
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -5,8 +5,13 @@ use bevy_kira_audio::{AudioInstance, AudioTween};
 
 use crate::{
     audio::Soundtrack,
+    dungeon_map::DungeonMap,
+    gear::GearInventory,
     loading::{FontAssets, TextureAssets},
     mouse_control::Clickable,
+    planning_screen::RerollsUsed,
+    run_seed::RunSeed,
+    shop::Gold,
     statistics::Statistics,
     summoning::InventoryItems,
     BattleCount, GameState,
@@ -16,9 +21,10 @@ const TITLE_SIZE: f32 = 128.;
 const TITLE_Y: f32 = 400.;
 
 const LABELS_SIZE: f32 = 96.;
-const BATTLE_COUNT_Y: f32 = 150.;
-const TIME_Y: f32 = 0.;
-const MINIONS_Y: f32 = -150.;
+const BATTLE_COUNT_Y: f32 = 210.;
+const TIME_Y: f32 = 90.;
+const MINIONS_Y: f32 = -30.;
+const SEED_Y: f32 = -150.;
 
 const MENU_BUTTON_SIZE: Vec2 = Vec2::new(256., 96.);
 const MENU_BUTTON_Y: f32 = -300.;
@@ -48,6 +54,7 @@ struct GameOverEntity;
 #[derive(Component)]
 struct MenuButton;
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_entities(
     mut commands: Commands,
     mut audio_instances: ResMut<Assets<AudioInstance>>,
@@ -55,6 +62,7 @@ fn spawn_entities(
     fonts: Res<FontAssets>,
     statistics: Res<Statistics>,
     battle_count: Res<BattleCount>,
+    run_seed: Res<RunSeed>,
     soundtrack: Res<Soundtrack>,
 ) {
     // audio
@@ -188,6 +196,26 @@ fn spawn_entities(
         GameOverEntity,
     ));
 
+    // seed label
+    commands.spawn((
+        Text2dBundle {
+            text: Text {
+                sections: vec![TextSection::new(
+                    format!("SEED: {}", run_seed.0),
+                    TextStyle {
+                        font: fonts.texts.clone(),
+                        color: Color::WHITE,
+                        font_size: LABELS_SIZE,
+                    },
+                )],
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(UI_X, SEED_Y, 0.),
+            ..Default::default()
+        },
+        GameOverEntity,
+    ));
+
     // menu button
     commands
         .spawn((
@@ -251,11 +279,16 @@ fn despawn_entities(
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_menu_button(
     mut next_state: ResMut<NextState<GameState>>,
     mut battle_count: ResMut<BattleCount>,
+    mut dungeon_map: ResMut<DungeonMap>,
     mut statistics: ResMut<Statistics>,
     mut inventory_items: ResMut<InventoryItems>,
+    mut gear_inventory: ResMut<GearInventory>,
+    mut gold: ResMut<Gold>,
+    mut rerolls_used: ResMut<RerollsUsed>,
     query: Query<&Clickable, With<MenuButton>>,
 ) {
     let clickable = query.single();
@@ -265,9 +298,13 @@ fn handle_menu_button(
     }
 
     battle_count.0 = 1;
+    *dungeon_map = DungeonMap::default();
     statistics.elapsed_seconds = 0.;
     statistics.summoned_minions = 0;
     inventory_items.0.clear();
+    gear_inventory.0.clear();
+    gold.0 = 0;
+    rerolls_used.0 = 0;
 
     next_state.set(GameState::Menu);
 }
@@ -0,0 +1,479 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    balance::{Balance, StatsBalance},
+    codex::CodexState,
+    experience::{handle_open_level_up_choice, LevelUpChoice},
+    loading::{FontAssets, TextureAssets},
+    minions::Minion,
+    mouse_control::{update_clickables, Clickable},
+    sacrifice::SacrificeConfirmation,
+    stats::{StatKind, Stats},
+    summoning::add_stat_tiers,
+    utils::num_to_roman,
+    GameScreen, GameState,
+};
+
+/// Gear which is not equipped waits in the rack, extra gear is lost.
+pub const MAX_GEAR_COUNT: usize = 6;
+pub const GEAR_SLOT_COUNT: usize = 2;
+pub const GEAR_COLOR: Color = Color::ORANGE;
+
+const GEAR_RACK_POS: Vec3 = Vec3::new(0., -110., 0.);
+const GEAR_CARD_SIZE: f32 = 80.;
+const GEAR_CARD_SPACING: f32 = 88.;
+/// Equipped gear is shown right of the minion, slots are stacked vertically.
+const EQUIPPED_GEAR_X: f32 = 96.;
+const EQUIPPED_GEAR_SPACING: f32 = 56.;
+const EQUIPPED_GEAR_SIZE: f32 = 48.;
+
+pub struct GearPlugin;
+
+/// This plugin handles gear, which is equipped on minions on the summoning screen and stays with
+/// them until it is moved to another minion or unequipped.
+impl Plugin for GearPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<GearInventory>()
+            .init_resource::<SelectedGear>()
+            .add_systems(OnEnter(GameScreen::Summoning), spawn_gear_rack)
+            .add_systems(OnExit(GameScreen::Summoning), clean_gear)
+            .add_systems(
+                Update,
+                handle_gear_control
+                    .after(handle_open_level_up_choice)
+                    .run_if(
+                        in_state(GameState::Playing)
+                            .and_then(in_state(GameScreen::Summoning))
+                            .and_then(in_state(CodexState::Closed))
+                            .and_then(not(resource_exists::<LevelUpChoice>))
+                            .and_then(not(resource_exists::<SacrificeConfirmation>)),
+                    ),
+            )
+            .add_systems(
+                PreUpdate,
+                spawn_gear_cards
+                    .after(update_clickables)
+                    .run_if(in_state(GameState::Playing).and_then(in_state(GameScreen::Summoning))),
+            );
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Copy, Debug, Serialize, Deserialize)]
+pub enum GearKind {
+    Axe,
+    Chestplate,
+    Cloak,
+    Amulet,
+}
+
+impl GearKind {
+    pub const ALL: [GearKind; 4] = [
+        GearKind::Axe,
+        GearKind::Chestplate,
+        GearKind::Cloak,
+        GearKind::Amulet,
+    ];
+
+    /// Main stat of the gear followed by the secondary one.
+    pub fn stats(&self) -> [StatKind; 2] {
+        match self {
+            GearKind::Axe => [StatKind::Damage, StatKind::CritMultiplier],
+            GearKind::Chestplate => [StatKind::Armor, StatKind::MaxHp],
+            GearKind::Cloak => [StatKind::Evasion, StatKind::Speed],
+            GearKind::Amulet => [StatKind::HpRegeneration, StatKind::CritChance],
+        }
+    }
+
+    pub fn icon(&self, textures: &TextureAssets) -> Handle<Image> {
+        match self {
+            GearKind::Axe => textures.axe_icon.clone(),
+            GearKind::Chestplate => textures.chestplate_icon.clone(),
+            GearKind::Cloak => textures.cloak_icon.clone(),
+            GearKind::Amulet => textures.amulet_icon.clone(),
+        }
+    }
+}
+
+#[derive(PartialEq, Eq, Clone, Debug, Serialize, Deserialize)]
+pub struct Gear {
+    pub kind: GearKind,
+    pub tier: u8,
+}
+
+impl Gear {
+    /// Tiers the gear adds to each of its stats.
+    pub fn stat_tiers(&self, balance: &Balance) -> [(StatKind, u8); 2] {
+        let [main, secondary] = self.kind.stats();
+        let secondary_tier = (self.tier as f32 * balance.gear.secondary_stat_ratio).ceil() as u8;
+
+        [(main, self.tier), (secondary, secondary_tier)]
+    }
+
+    fn equip(&self, stats: &mut Stats, balance: &Balance) {
+        for (stat, tier) in self.stat_tiers(balance) {
+            add_stat_tiers(stats, &balance.minion, stat, tier);
+        }
+    }
+
+    /// Take the bonuses of the gear away again, the minion keeps at least one HP.
+    fn unequip(&self, stats: &mut Stats, balance: &Balance) {
+        for (stat, tier) in self.stat_tiers(balance) {
+            remove_stat_tiers(stats, &balance.minion, stat, tier);
+        }
+    }
+}
+
+fn remove_stat_tiers(stats: &mut Stats, balance: &StatsBalance, stat: StatKind, tier: u8) {
+    let (_, inc) = balance.base_and_inc(stat);
+    *stat.get_mut(stats) -= inc * tier as f32;
+    if stat == StatKind::MaxHp {
+        stats.current_hp = (stats.current_hp - inc * tier as f32).max(1.);
+    }
+}
+
+/// Gear equipped on a minion, its bonuses are already included in the stats of the minion.
+#[derive(Component, Serialize, Deserialize, Clone, Default)]
+pub struct Equipment(pub Vec<Gear>);
+
+#[derive(Resource, Default)]
+pub struct GearInventory(pub Vec<Gear>);
+
+impl GearInventory {
+    /// Put the gear into the rack. Returns false if the rack is full and the gear was lost.
+    pub fn add(&mut self, gear: &Gear) -> bool {
+        if self.0.len() >= MAX_GEAR_COUNT {
+            return false;
+        }
+
+        self.0.push(gear.clone());
+        true
+    }
+}
+
+/// Where the gear picked by the player currently is.
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum GearSource {
+    Rack(usize),
+    Minion(Entity, usize),
+}
+
+/// Gear picked to be equipped on the next clicked minion.
+#[derive(Resource, Default)]
+pub struct SelectedGear(Option<GearSource>);
+
+#[derive(Component)]
+struct GearRack;
+
+#[derive(Component)]
+pub struct GearCard(GearSource);
+
+#[derive(Component)]
+struct GearScreenEntity;
+
+fn spawn_gear_rack(mut commands: Commands, mut selected_gear: ResMut<SelectedGear>) {
+    selected_gear.0 = None;
+
+    commands.spawn((
+        SpatialBundle::from_transform(Transform::from_translation(GEAR_RACK_POS)),
+        GearRack,
+        GearScreenEntity,
+    ));
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_gear_card(
+    parent: &mut ChildBuilder,
+    textures: &TextureAssets,
+    fonts: &FontAssets,
+    gear: Option<&Gear>,
+    source: GearSource,
+    size: f32,
+    position: Vec3,
+    is_selected: bool,
+) {
+    let color = if is_selected {
+        Color::GOLD.with_a(0.4)
+    } else {
+        Color::DARK_GRAY.with_a(0.7)
+    };
+
+    let mut card = parent.spawn(SpriteBundle {
+        texture: textures.square.clone(),
+        sprite: Sprite {
+            color,
+            custom_size: Some(Vec2::splat(size)),
+            ..Default::default()
+        },
+        transform: Transform::from_translation(position),
+        ..Default::default()
+    });
+
+    // empty slots only show how much gear fits
+    let Some(gear) = gear else {
+        return;
+    };
+
+    card.insert((Clickable::default(), GearCard(source)))
+        .with_children(|card| {
+            card.spawn(SpriteBundle {
+                texture: gear.kind.icon(textures),
+                sprite: Sprite {
+                    color: GEAR_COLOR,
+                    custom_size: Some(Vec2::splat(size * 0.8)),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0., 0., 1.),
+                ..Default::default()
+            });
+            card.spawn(Text2dBundle {
+                text: Text::from_section(
+                    num_to_roman(gear.tier),
+                    TextStyle {
+                        color: Color::WHITE,
+                        font: fonts.tier_numbers.clone(),
+                        font_size: size * 0.5,
+                    },
+                ),
+                text_anchor: bevy::sprite::Anchor::BottomRight,
+                transform: Transform::from_xyz(size / 2. - 2., -size / 2. + 2., 2.),
+                ..Default::default()
+            });
+        });
+}
+
+/// Recreate the rack and gear slots of minions whenever the gear or the selection changes.
+#[allow(clippy::too_many_arguments)]
+fn spawn_gear_cards(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    fonts: Res<FontAssets>,
+    gear_inventory: Res<GearInventory>,
+    selected_gear: Res<SelectedGear>,
+    rack_query: Query<(Entity, Option<&Children>, Ref<GearRack>)>,
+    minion_query: Query<(Entity, Ref<Equipment>), With<Minion>>,
+    card_query: Query<(Entity, &Parent), With<GearScreenEntity>>,
+) {
+    let Ok((rack_entity, rack_children, rack)) = rack_query.get_single() else {
+        return;
+    };
+    let equipment_changed = minion_query
+        .iter()
+        .any(|(_, equipment)| equipment.is_changed());
+    if !rack.is_added()
+        && !gear_inventory.is_changed()
+        && !selected_gear.is_changed()
+        && !equipment_changed
+    {
+        return;
+    }
+
+    for &child in rack_children.into_iter().flatten() {
+        commands.entity(child).despawn_recursive();
+    }
+    commands.entity(rack_entity).clear_children();
+
+    commands.entity(rack_entity).with_children(|parent| {
+        let first_x = -GEAR_CARD_SPACING * (MAX_GEAR_COUNT as f32 - 1.) / 2.;
+        for index in 0..MAX_GEAR_COUNT {
+            let source = GearSource::Rack(index);
+            spawn_gear_card(
+                parent,
+                &textures,
+                &fonts,
+                gear_inventory.0.get(index),
+                source,
+                GEAR_CARD_SIZE,
+                Vec3::new(first_x + GEAR_CARD_SPACING * index as f32, 0., 1.),
+                selected_gear.0 == Some(source),
+            );
+        }
+    });
+
+    // slots of minions are their children, so they follow them around
+    for (entity, parent) in card_query.iter() {
+        if minion_query.contains(parent.get()) {
+            commands.entity(entity).despawn_recursive();
+        }
+    }
+    for (minion_entity, equipment) in minion_query.iter() {
+        commands.entity(minion_entity).with_children(|parent| {
+            parent
+                .spawn((SpatialBundle::default(), GearScreenEntity))
+                .with_children(|slots| {
+                    for slot in 0..GEAR_SLOT_COUNT {
+                        let source = GearSource::Minion(minion_entity, slot);
+                        let y = EQUIPPED_GEAR_SPACING * (0.5 - slot as f32);
+                        spawn_gear_card(
+                            slots,
+                            &textures,
+                            &fonts,
+                            equipment.0.get(slot),
+                            source,
+                            EQUIPPED_GEAR_SIZE,
+                            Vec3::new(EQUIPPED_GEAR_X, y, 1.),
+                            selected_gear.0 == Some(source),
+                        );
+                    }
+                });
+        });
+    }
+}
+
+fn take_gear(
+    source: GearSource,
+    balance: &Balance,
+    gear_inventory: &mut GearInventory,
+    minion_query: &mut Query<(&mut Stats, &mut Equipment), With<Minion>>,
+) -> Option<Gear> {
+    match source {
+        GearSource::Rack(index) => {
+            (index < gear_inventory.0.len()).then(|| gear_inventory.0.remove(index))
+        }
+        GearSource::Minion(entity, slot) => {
+            let (mut stats, mut equipment) = minion_query.get_mut(entity).ok()?;
+            if slot >= equipment.0.len() {
+                return None;
+            }
+            let gear = equipment.0.remove(slot);
+            gear.unequip(&mut stats, balance);
+            Some(gear)
+        }
+    }
+}
+
+/// Clicked gear is selected and equipped on the next clicked minion with a free slot, right click
+/// puts equipped gear back into the rack. Any other click drops the selection.
+pub fn handle_gear_control(
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    balance: Res<Balance>,
+    mut gear_inventory: ResMut<GearInventory>,
+    mut selected_gear: ResMut<SelectedGear>,
+    card_query: Query<(&Clickable, &GearCard)>,
+    clickable_minion_query: Query<(Entity, &Clickable), With<Minion>>,
+    mut minion_query: Query<(&mut Stats, &mut Equipment), With<Minion>>,
+) {
+    let left_clicked = mouse_input.just_pressed(MouseButton::Left);
+    let right_clicked = mouse_input.just_pressed(MouseButton::Right);
+    if !left_clicked && !right_clicked {
+        return;
+    }
+
+    let clicked_card = card_query
+        .iter()
+        .find_map(|(clickable, &GearCard(source))| {
+            (clickable.just_left_clicked || clickable.just_right_clicked).then_some(source)
+        });
+    let clicked_minion = clickable_minion_query
+        .iter()
+        .find_map(|(entity, clickable)| clickable.just_left_clicked.then_some(entity));
+
+    if right_clicked {
+        if let Some(source @ GearSource::Minion(..)) = clicked_card {
+            if gear_inventory.0.len() < MAX_GEAR_COUNT {
+                let gear = take_gear(source, &balance, &mut gear_inventory, &mut minion_query);
+                if let Some(gear) = gear {
+                    gear_inventory.add(&gear);
+                }
+            }
+        }
+        selected_gear.0 = None;
+        return;
+    }
+
+    match (selected_gear.0, clicked_card, clicked_minion) {
+        (_, Some(source), _) => {
+            selected_gear.0 = (selected_gear.0 != Some(source)).then_some(source);
+        }
+        (Some(source), None, Some(target)) => {
+            let has_free_slot = minion_query
+                .get(target)
+                .is_ok_and(|(_, equipment)| equipment.0.len() < GEAR_SLOT_COUNT);
+            let is_same_minion =
+                matches!(source, GearSource::Minion(entity, _) if entity == target);
+            if has_free_slot && !is_same_minion {
+                if let Some(gear) =
+                    take_gear(source, &balance, &mut gear_inventory, &mut minion_query)
+                {
+                    let (mut stats, mut equipment) = minion_query.get_mut(target).unwrap();
+                    gear.equip(&mut stats, &balance);
+                    equipment.0.push(gear);
+                }
+            }
+            selected_gear.0 = None;
+        }
+        _ => selected_gear.0 = None,
+    }
+}
+
+fn clean_gear(
+    mut commands: Commands,
+    mut selected_gear: ResMut<SelectedGear>,
+    query: Query<Entity, With<GearScreenEntity>>,
+) {
+    selected_gear.0 = None;
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Level up choice is not opened by clicks which equip gear.
+pub fn no_gear_selected(selected_gear: Res<SelectedGear>) -> bool {
+    selected_gear.0.is_none()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn balance() -> StatsBalance {
+        StatsBalance {
+            hp_base: 50.,
+            hp_inc: 10.,
+            hp_regeneration_base: 0.,
+            hp_regeneration_inc: 0.5,
+            damage_base: 5.,
+            damage_inc: 2.,
+            speed_base: 1.,
+            speed_inc: 0.1,
+            armor_base: 0.,
+            armor_inc: 1.,
+            crit_chance_base: 0.,
+            crit_chance_inc: 0.05,
+            crit_multiplier_base: 1.5,
+            crit_multiplier_inc: 0.1,
+            evasion_base: 0.,
+            evasion_inc: 0.05,
+        }
+    }
+
+    #[test]
+    fn full_rack_rejects_gear() {
+        let gear = Gear {
+            kind: GearKind::Cloak,
+            tier: 1,
+        };
+        let mut rack = GearInventory::default();
+
+        for _ in 0..MAX_GEAR_COUNT {
+            assert!(rack.add(&gear));
+        }
+        assert!(!rack.add(&gear));
+        assert_eq!(rack.0.len(), MAX_GEAR_COUNT);
+    }
+
+    #[test]
+    fn removing_max_hp_keeps_one_hp() {
+        let mut stats = Stats {
+            current_hp: 25.,
+            max_hp: 80.,
+            ..Default::default()
+        };
+
+        remove_stat_tiers(&mut stats, &balance(), StatKind::MaxHp, 2);
+        assert_eq!((stats.current_hp, stats.max_hp), (5., 60.));
+
+        remove_stat_tiers(&mut stats, &balance(), StatKind::MaxHp, 1);
+        assert_eq!((stats.current_hp, stats.max_hp), (1., 50.));
+    }
+}
@@ -6,6 +6,7 @@ use bevy::{
 
 const HEALTH_BAR_BACKGROUND_Z: f32 = 40.;
 const HEALTH_BAR_FOREGROUND_Z: f32 = 41.;
+const MANA_BAR_COLOR: Color = Color::rgb(0.2, 0.4, 1.);
 
 pub struct HealthBarPlugin;
 
@@ -23,6 +24,8 @@ impl Plugin for HealthBarPlugin {
 pub struct HealthBar {
     pub health_bar_background_entity: Option<Entity>,
     pub health_bar_foreground_entity: Option<Entity>,
+    /// Mana bar is only spawned for participants with a mana pool.
+    pub mana_bar_foreground_entity: Option<Entity>,
     pub width: f32,
     pub height: f32,
     pub offset: Vec2,
@@ -33,6 +36,7 @@ impl Default for HealthBar {
         Self {
             health_bar_background_entity: None,
             health_bar_foreground_entity: None,
+            mana_bar_foreground_entity: None,
             width: 96.,
             height: 16.,
             offset: Vec2::new(0., 96.),
@@ -44,9 +48,9 @@ fn spawn_health_bars(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<ColorMaterial>>,
-    mut query: Query<(Entity, &mut HealthBar), With<Stats>>,
+    mut query: Query<(Entity, &mut HealthBar, &Stats)>,
 ) {
-    for (entity, mut health_bar) in query.iter_mut() {
+    for (entity, mut health_bar, stats) in query.iter_mut() {
         if health_bar.health_bar_background_entity.is_some() {
             continue;
         }
@@ -82,6 +86,39 @@ fn spawn_health_bars(
 
         health_bar.health_bar_background_entity = Some(health_bar_background_entity);
         health_bar.health_bar_foreground_entity = Some(health_bar_foreground_entity);
+
+        if stats.max_mana <= 0. {
+            continue;
+        }
+
+        // mana bar is half as tall and sits right below the health bar
+        let mana_bar_height = health_bar.height / 2.;
+        let mana_bar_background_entity = commands
+            .spawn(MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(meshes.add(Rectangle::new(health_bar.width, mana_bar_height))),
+                material: materials.add(Color::BLACK),
+                transform: Transform::from_xyz(0., -(health_bar.height + mana_bar_height) / 2., 0.),
+                ..default()
+            })
+            .id();
+
+        let mana_bar_foreground_entity = commands
+            .spawn(MaterialMesh2dBundle {
+                mesh: Mesh2dHandle(meshes.add(Rectangle::new(health_bar.width, mana_bar_height))),
+                material: materials.add(MANA_BAR_COLOR),
+                transform: Transform::from_xyz(0., 0., 1.),
+                ..default()
+            })
+            .id();
+
+        commands
+            .entity(health_bar_background_entity)
+            .push_children(&[mana_bar_background_entity]);
+        commands
+            .entity(mana_bar_background_entity)
+            .push_children(&[mana_bar_foreground_entity]);
+
+        health_bar.mana_bar_foreground_entity = Some(mana_bar_foreground_entity);
     }
 }
 
@@ -89,17 +126,26 @@ fn update_health_bars(
     query: Query<(&HealthBar, &Stats)>,
     mut transform_query: Query<&mut Transform>,
 ) {
-    for (health_bar, hp) in query.iter() {
+    for (health_bar, stats) in query.iter() {
         if health_bar.health_bar_background_entity.is_none() {
             continue;
         }
 
-        let value = hp.current_hp / hp.max_hp;
+        let value = stats.current_hp / stats.max_hp;
         if let Ok(mut transform) =
             transform_query.get_mut(health_bar.health_bar_foreground_entity.unwrap())
         {
             transform.scale.x = value;
             transform.translation.x = -(1. - value) * (health_bar.width / 2.);
         }
+
+        let Some(mana_bar_foreground_entity) = health_bar.mana_bar_foreground_entity else {
+            continue;
+        };
+        let value = stats.current_mana / stats.max_mana;
+        if let Ok(mut transform) = transform_query.get_mut(mana_bar_foreground_entity) {
+            transform.scale.x = value;
+            transform.translation.x = -(1. - value) * (health_bar.width / 2.);
+        }
     }
 }
@@ -1,34 +1,61 @@
 #![allow(clippy::type_complexity)]
 
 mod audio;
+mod balance;
 mod battle;
+mod codex;
+mod dungeon_map;
 mod enemy;
+mod experience;
+mod formation;
 mod game_over;
+mod gear;
 mod health_bar;
 mod loading;
 mod menu;
 mod minions;
 mod mouse_control;
 mod planning_screen;
+mod recovery;
+mod results;
+mod rewards;
+mod run_seed;
+mod sacrifice;
+mod save;
+mod shop;
 mod statistics;
 mod stats;
+mod status_effects;
 mod summoning;
 mod tutorial;
 mod utils;
 
 use crate::audio::InternalAudioPlugin;
+use crate::balance::BalancePlugin;
 use crate::loading::LoadingPlugin;
 use crate::menu::MenuPlugin;
 
 use crate::battle::BattlePlugin;
+use crate::codex::CodexPlugin;
+use crate::dungeon_map::DungeonMap;
 use crate::enemy::EnemyPlugin;
+use crate::experience::ExperiencePlugin;
+use crate::formation::FormationPlugin;
 use crate::game_over::GameOverPlugin;
+use crate::gear::GearPlugin;
 use crate::health_bar::HealthBarPlugin;
 use crate::minions::MinionsPlugin;
 use crate::mouse_control::MouseControlPlugin;
 use crate::planning_screen::PlanningScreenPlugin;
+use crate::recovery::RecoveryPlugin;
+use crate::results::ResultsPlugin;
+use crate::rewards::RewardsPlugin;
+use crate::run_seed::RunSeed;
+use crate::sacrifice::SacrificePlugin;
+use crate::save::SavePlugin;
+use crate::shop::ShopPlugin;
 use crate::statistics::StatisticsPlugin;
-use crate::stats::StatsPlugin;
+use crate::status_effects::StatusEffectsPlugin;
 use crate::summoning::SummoningPlugin;
 use crate::tutorial::TutorialPlugin;
 
@@ -37,6 +64,13 @@ use bevy::diagnostic::{FrameTimeDiagnosticsPlugin, LogDiagnosticsPlugin};
 use bevy::prelude::*;
 use bevy::{app::App, window::close_on_esc};
 
+pub use crate::battle::simulation::{
+    estimate_battle, simulate_battle, ActionKind, BattleAction, BattleOutcome, BattleResult,
+    BattleRules, Combatant, Formation, HitKind, Vitals,
+};
+pub use crate::stats::{Ability, AbilityKind, Row, StatKind, Stats, TargetingPolicy};
+pub use crate::status_effects::{StatusEffect, StatusEffectKind, StatusEffects};
+
 // This example game uses States to separate logic
 // See https://bevy-cheatbook.github.io/programming/states.html
 // Or https://github.com/bevyengine/bevy/blob/main/examples/ecs/state.rs
@@ -59,6 +93,9 @@ enum GameScreen {
     Battle,
     Summoning,
     Planning,
+    Shop,
+    Results,
+    Rewards,
 }
 
 #[derive(Resource, Default)]
@@ -71,23 +108,38 @@ impl Plugin for GamePlugin {
         app.init_state::<GameState>()
             .init_state::<GameScreen>()
             .insert_resource(BattleCount(1))
+            .init_resource::<RunSeed>()
+            .init_resource::<DungeonMap>()
             .add_systems(OnExit(GameState::Loading), spawn_camera)
             .add_systems(Update, close_on_esc)
             .add_plugins((
                 LoadingPlugin,
+                BalancePlugin,
                 MenuPlugin,
                 InternalAudioPlugin,
                 EnemyPlugin,
-                HealthBarPlugin,
-                StatsPlugin,
-                MinionsPlugin,
-                BattlePlugin,
-                SummoningPlugin,
+                (HealthBarPlugin, StatusEffectsPlugin),
+                (
+                    MinionsPlugin,
+                    ExperiencePlugin,
+                    RecoveryPlugin,
+                    SacrificePlugin,
+                    FormationPlugin,
+                ),
+                (BattlePlugin, ResultsPlugin),
+                (
+                    SummoningPlugin,
+                    ShopPlugin,
+                    RewardsPlugin,
+                    CodexPlugin,
+                    GearPlugin,
+                ),
                 MouseControlPlugin,
                 PlanningScreenPlugin,
                 StatisticsPlugin,
                 GameOverPlugin,
                 TutorialPlugin,
+                SavePlugin,
             ));
 
         #[cfg(debug_assertions)]
@@ -1,3 +1,4 @@
+use crate::balance::Balance;
 use crate::GameState;
 use bevy::prelude::*;
 use bevy_asset_loader::prelude::*;
@@ -15,7 +16,8 @@ impl Plugin for LoadingPlugin {
                 .continue_to_state(GameState::Menu)
                 .load_collection::<FontAssets>()
                 .load_collection::<AudioAssets>()
-                .load_collection::<TextureAssets>(),
+                .load_collection::<TextureAssets>()
+                .load_collection::<BalanceAssets>(),
         );
     }
 }
@@ -59,6 +61,12 @@ pub struct AudioAssets {
     pub game_over_soundtrack: Handle<AudioSource>,
 }
 
+#[derive(AssetCollection, Resource)]
+pub struct BalanceAssets {
+    #[asset(path = "balance.ron")]
+    pub balance: Handle<Balance>,
+}
+
 #[derive(AssetCollection, Resource)]
 pub struct TextureAssets {
     #[asset(path = "textures/bevy.png")]
@@ -84,8 +92,6 @@ pub struct TextureAssets {
     pub summoning_circle: Handle<Image>,
     #[asset(path = "textures/minion.png")]
     pub minion: Handle<Image>,
-    #[asset(path = "textures/enemy1.png")]
-    pub enemy1: Handle<Image>,
 
     #[asset(path = "textures/shapes/square.png")]
     pub square: Handle<Image>,
@@ -100,4 +106,60 @@ pub struct TextureAssets {
     pub hearth_icon: Handle<Image>,
     #[asset(path = "textures/icons/hp_regeneration_icon.png")]
     pub hp_regeneration_icon: Handle<Image>,
+    #[asset(path = "textures/icons/armor_icon.png")]
+    pub armor_icon: Handle<Image>,
+    #[asset(path = "textures/icons/crit_chance_icon.png")]
+    pub crit_chance_icon: Handle<Image>,
+    #[asset(path = "textures/icons/crit_multiplier_icon.png")]
+    pub crit_multiplier_icon: Handle<Image>,
+    #[asset(path = "textures/icons/evasion_icon.png")]
+    pub evasion_icon: Handle<Image>,
+    #[asset(path = "textures/icons/area_hit_icon.png")]
+    pub area_hit_icon: Handle<Image>,
+    #[asset(path = "textures/icons/heal_icon.png")]
+    pub heal_icon: Handle<Image>,
+    #[asset(path = "textures/icons/shield_icon.png")]
+    pub shield_icon: Handle<Image>,
+    #[asset(path = "textures/icons/stun_icon.png")]
+    pub stun_icon: Handle<Image>,
+    #[asset(path = "textures/icons/target_front_icon.png")]
+    pub target_front_icon: Handle<Image>,
+    #[asset(path = "textures/icons/target_weakest_icon.png")]
+    pub target_weakest_icon: Handle<Image>,
+    #[asset(path = "textures/icons/target_strongest_icon.png")]
+    pub target_strongest_icon: Handle<Image>,
+    #[asset(path = "textures/icons/target_random_icon.png")]
+    pub target_random_icon: Handle<Image>,
+    #[asset(path = "textures/icons/taunt_icon.png")]
+    pub taunt_icon: Handle<Image>,
+    #[asset(path = "textures/icons/poison_icon.png")]
+    pub poison_icon: Handle<Image>,
+    #[asset(path = "textures/icons/burn_icon.png")]
+    pub burn_icon: Handle<Image>,
+    #[asset(path = "textures/icons/slow_icon.png")]
+    pub slow_icon: Handle<Image>,
+    #[asset(path = "textures/icons/bleed_icon.png")]
+    pub bleed_icon: Handle<Image>,
+    #[asset(path = "textures/icons/herb_icon.png")]
+    pub herb_icon: Handle<Image>,
+    #[asset(path = "textures/icons/axe_icon.png")]
+    pub axe_icon: Handle<Image>,
+    #[asset(path = "textures/icons/chestplate_icon.png")]
+    pub chestplate_icon: Handle<Image>,
+    #[asset(path = "textures/icons/cloak_icon.png")]
+    pub cloak_icon: Handle<Image>,
+    #[asset(path = "textures/icons/amulet_icon.png")]
+    pub amulet_icon: Handle<Image>,
+    #[asset(path = "textures/icons/elite_icon.png")]
+    pub elite_icon: Handle<Image>,
+    #[asset(path = "textures/icons/rest_icon.png")]
+    pub rest_icon: Handle<Image>,
+    #[asset(path = "textures/icons/treasure_icon.png")]
+    pub treasure_icon: Handle<Image>,
+    #[asset(path = "textures/icons/shop_icon.png")]
+    pub shop_icon: Handle<Image>,
+    #[asset(path = "textures/icons/boss_icon.png")]
+    pub boss_icon: Handle<Image>,
+    #[asset(path = "textures/icons/gold_icon.png")]
+    pub gold_icon: Handle<Image>,
 }
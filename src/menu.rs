@@ -1,18 +1,29 @@
 use crate::loading::{FontAssets, TextureAssets};
+use crate::run_seed::RunSeed;
+use crate::save::{read_save, ContinueRunEvent, SaveError};
 use crate::{GameScreen, GameState};
+use bevy::input::keyboard::KeyboardInput;
+use bevy::input::ButtonState;
 use bevy::prelude::*;
 
 const TITLE_SIZE: f32 = 128.;
 const TITLE_Y: f32 = 400.;
 
+const MAX_SEED_LENGTH: usize = 18;
+
 pub struct MenuPlugin;
 
 /// This plugin is responsible for the game menu (containing only one button...)
 /// The menu is only drawn during the State `GameState::Menu` and is removed when that state is exited
 impl Plugin for MenuPlugin {
     fn build(&self, app: &mut App) {
-        app.add_systems(OnEnter(GameState::Menu), setup_menu)
-            .add_systems(Update, click_play_button.run_if(in_state(GameState::Menu)))
+        app.init_resource::<SeedInput>()
+            .add_systems(OnEnter(GameState::Menu), setup_menu)
+            .add_systems(
+                Update,
+                (click_play_button, handle_seed_input, update_seed_text)
+                    .run_if(in_state(GameState::Menu)),
+            )
             .add_systems(OnExit(GameState::Menu), cleanup_menu);
     }
 }
@@ -35,9 +46,27 @@ impl Default for ButtonColors {
 #[derive(Component)]
 struct Menu;
 
-fn setup_menu(mut commands: Commands, textures: Res<TextureAssets>, fonts: Res<FontAssets>) {
+#[derive(Component)]
+struct SeedText;
+
+/// Seed typed in by the player, random seed is used until the player starts typing.
+#[derive(Resource, Default)]
+struct SeedInput {
+    text: String,
+    edited: bool,
+}
+
+fn setup_menu(
+    mut commands: Commands,
+    mut seed_input: ResMut<SeedInput>,
+    textures: Res<TextureAssets>,
+    fonts: Res<FontAssets>,
+) {
     info!("menu");
 
+    seed_input.text = RunSeed::random().0.to_string();
+    seed_input.edited = false;
+
     // title
     commands.spawn((
         Text2dBundle {
@@ -123,6 +152,101 @@ fn setup_menu(mut commands: Commands, textures: Res<TextureAssets>, fonts: Res<F
                         },
                     ));
                 });
+
+            // saved run
+            match read_save() {
+                Ok(_) => {
+                    children
+                        .spawn((
+                            ButtonBundle {
+                                style: Style {
+                                    width: Val::Px(256.0),
+                                    height: Val::Px(96.0),
+                                    margin: UiRect::top(Val::Px(16.)),
+                                    justify_content: JustifyContent::Center,
+                                    align_items: AlignItems::Center,
+                                    ..Default::default()
+                                },
+                                background_color: ButtonColors::default().normal.into(),
+                                ..Default::default()
+                            },
+                            ButtonColors::default(),
+                            ContinueRun,
+                        ))
+                        .with_children(|parent| {
+                            parent.spawn(TextBundle::from_section(
+                                "Continue",
+                                TextStyle {
+                                    font_size: 64.0,
+                                    color: Color::rgb(0.9, 0.9, 0.9),
+                                    font: fonts.texts.clone(),
+                                },
+                            ));
+                        });
+                }
+                Err(SaveError::NotFound) => {}
+                Err(error) => {
+                    warn!("Saved run could not be loaded: {error}");
+                    children.spawn(
+                        TextBundle::from_section(
+                            format!("Saved run could not be loaded: {error}"),
+                            TextStyle {
+                                font_size: 32.0,
+                                color: Color::rgb(0.9, 0.4, 0.4),
+                                font: fonts.texts.clone(),
+                            },
+                        )
+                        .with_style(Style {
+                            margin: UiRect::top(Val::Px(16.)),
+                            ..default()
+                        }),
+                    );
+                }
+            }
+
+            // seed
+            children.spawn((
+                TextBundle::from_section(
+                    "",
+                    TextStyle {
+                        font_size: 40.0,
+                        color: Color::rgb(0.9, 0.9, 0.9),
+                        font: fonts.texts.clone(),
+                    },
+                )
+                .with_style(Style {
+                    margin: UiRect::top(Val::Px(32.)),
+                    ..default()
+                }),
+                SeedText,
+            ));
+            children
+                .spawn((
+                    ButtonBundle {
+                        style: Style {
+                            width: Val::Px(192.0),
+                            height: Val::Px(48.0),
+                            margin: UiRect::top(Val::Px(8.)),
+                            justify_content: JustifyContent::Center,
+                            align_items: AlignItems::Center,
+                            ..Default::default()
+                        },
+                        background_color: ButtonColors::default().normal.into(),
+                        ..Default::default()
+                    },
+                    ButtonColors::default(),
+                    RandomizeSeed,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(TextBundle::from_section(
+                        "New seed",
+                        TextStyle {
+                            font_size: 32.0,
+                            color: Color::rgb(0.9, 0.9, 0.9),
+                            font: fonts.texts.clone(),
+                        },
+                    ));
+                });
         });
     commands
         .spawn((
@@ -226,9 +350,18 @@ struct ChangeState(GameState);
 #[derive(Component)]
 struct OpenLink(&'static str);
 
+#[derive(Component)]
+struct RandomizeSeed;
+
+#[derive(Component)]
+struct ContinueRun;
+
 fn click_play_button(
     mut next_state: ResMut<NextState<GameState>>,
     mut next_screen: ResMut<NextState<GameScreen>>,
+    mut run_seed: ResMut<RunSeed>,
+    mut seed_input: ResMut<SeedInput>,
+    mut continue_run_event: EventWriter<ContinueRunEvent>,
     mut interaction_query: Query<
         (
             &Interaction,
@@ -236,16 +369,38 @@ fn click_play_button(
             &ButtonColors,
             Option<&ChangeState>,
             Option<&OpenLink>,
+            Option<&RandomizeSeed>,
+            Option<&ContinueRun>,
         ),
         (Changed<Interaction>, With<Button>),
     >,
 ) {
-    for (interaction, mut color, button_colors, change_state, open_link) in &mut interaction_query {
+    for (
+        interaction,
+        mut color,
+        button_colors,
+        change_state,
+        open_link,
+        randomize_seed,
+        continue_run,
+    ) in &mut interaction_query
+    {
         match *interaction {
             Interaction::Pressed => {
                 if let Some(state) = change_state {
+                    *run_seed = match seed_input.text.parse() {
+                        Ok(seed) => RunSeed(seed),
+                        Err(_) => RunSeed::random(),
+                    };
+                    info!("starting run with seed {}", run_seed.0);
+
                     next_state.set(state.0.clone());
                     next_screen.set(GameScreen::Summoning);
+                } else if continue_run.is_some() {
+                    continue_run_event.send(ContinueRunEvent);
+                } else if randomize_seed.is_some() {
+                    seed_input.text = RunSeed::random().0.to_string();
+                    seed_input.edited = false;
                 } else if let Some(link) = open_link {
                     if let Err(error) = webbrowser::open(link.0) {
                         warn!("Failed to open link {error:?}");
@@ -262,6 +417,60 @@ fn click_play_button(
     }
 }
 
+fn handle_seed_input(
+    mut keyboard_events: EventReader<KeyboardInput>,
+    mut seed_input: ResMut<SeedInput>,
+) {
+    for event in keyboard_events.read() {
+        if event.state != ButtonState::Pressed {
+            continue;
+        }
+
+        if event.key_code == KeyCode::Backspace {
+            seed_input.text.pop();
+            seed_input.edited = true;
+            continue;
+        }
+
+        let digit = match event.key_code {
+            KeyCode::Digit0 | KeyCode::Numpad0 => '0',
+            KeyCode::Digit1 | KeyCode::Numpad1 => '1',
+            KeyCode::Digit2 | KeyCode::Numpad2 => '2',
+            KeyCode::Digit3 | KeyCode::Numpad3 => '3',
+            KeyCode::Digit4 | KeyCode::Numpad4 => '4',
+            KeyCode::Digit5 | KeyCode::Numpad5 => '5',
+            KeyCode::Digit6 | KeyCode::Numpad6 => '6',
+            KeyCode::Digit7 | KeyCode::Numpad7 => '7',
+            KeyCode::Digit8 | KeyCode::Numpad8 => '8',
+            KeyCode::Digit9 | KeyCode::Numpad9 => '9',
+            _ => continue,
+        };
+
+        // typing replaces the random seed
+        if !seed_input.edited {
+            seed_input.text.clear();
+            seed_input.edited = true;
+        }
+        if seed_input.text.len() < MAX_SEED_LENGTH {
+            seed_input.text.push(digit);
+        }
+    }
+}
+
+fn update_seed_text(seed_input: Res<SeedInput>, mut query: Query<&mut Text, With<SeedText>>) {
+    if !seed_input.is_changed() {
+        return;
+    }
+
+    for mut text in query.iter_mut() {
+        text.sections[0].value = if seed_input.text.is_empty() {
+            "Seed: random (type to change)".to_string()
+        } else {
+            format!("Seed: {} (type to change)", seed_input.text)
+        };
+    }
+}
+
 fn cleanup_menu(mut commands: Commands, menu: Query<Entity, With<Menu>>) {
     for entity in menu.iter() {
         commands.entity(entity).despawn_recursive();
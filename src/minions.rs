@@ -1,17 +1,22 @@
 use crate::{
+    balance::Balance,
+    experience::MinionLevel,
+    formation::FormationSlot,
+    gear::{Equipment, Gear},
+    health_bar::HealthBar,
     loading::{FontAssets, TextureAssets},
     mouse_control::Clickable,
-    stats::{
-        Stats, MINION_DMG_BASE, MINION_DMG_INC, MINION_HP_BASE, MINION_HP_INC,
-        MINION_HP_REGEN_BASE, MINION_HP_REGEN_INC, MINION_SPEED_BASE, MINION_SPEED_INC,
-    },
+    stats::{Row, StatKind, Stats, TargetingPolicy},
+    status_effects::status_effect_icon,
+    summoning::{ability_icon, stat_icon, targeting_icon},
     utils::num_to_roman,
     GameScreen, GameState,
 };
 use bevy::prelude::*;
 
 const NDC_SPAWN_AREA_SIZE: f32 = 2.5;
-const NDC_SPAWN_X: f32 = -0.2;
+const NDC_FRONT_ROW_X: f32 = -0.2;
+const NDC_BACK_ROW_X: f32 = -0.5;
 
 pub const MAX_MINION_COUNT: usize = 4;
 pub const MINION_SIZE: f32 = 128.;
@@ -39,19 +44,53 @@ struct HoverWindow;
 #[derive(Component)]
 pub struct Minion;
 
+pub fn spawn_minion(
+    commands: &mut Commands,
+    textures: &TextureAssets,
+    stats: Stats,
+    slot: FormationSlot,
+) -> Entity {
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: textures.minion.clone(),
+                sprite: Sprite {
+                    custom_size: Some(Vec2::splat(MINION_SIZE)),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(slot.position()),
+                ..Default::default()
+            },
+            Minion,
+            stats,
+            slot,
+            MinionLevel::default(),
+            Equipment::default(),
+            HealthBar::default(),
+        ))
+        .id()
+}
+
+/// Minions stand in two columns in the battle, the front row is closer to the enemies.
 fn reposition_minions(
     camera: Query<(&Camera, &GlobalTransform), With<Camera2d>>,
-    mut query: Query<&mut Transform, With<Minion>>,
+    mut query: Query<(&mut Transform, &FormationSlot), With<Minion>>,
 ) {
     let (camera, camera_transform) = camera.single();
 
-    for (i, mut transform) in query.iter_mut().enumerate() {
-        let ndc_spawn_pos_y =
-            (NDC_SPAWN_AREA_SIZE / (MAX_MINION_COUNT + 2) as f32) * (i + 1) as f32 - 1. - 0.1;
+    for (mut transform, slot) in query.iter_mut() {
+        let ndc_spawn_pos_x = match slot.row {
+            Row::Front => NDC_FRONT_ROW_X,
+            Row::Back => NDC_BACK_ROW_X,
+        };
+        let ndc_spawn_pos_y = (NDC_SPAWN_AREA_SIZE / (MAX_MINION_COUNT + 2) as f32)
+            * (slot.index + 1) as f32
+            - 1.
+            - 0.1;
         let spawn_pos = camera
             .ndc_to_world(
                 camera_transform,
-                Vec3::new(NDC_SPAWN_X, ndc_spawn_pos_y, 0.),
+                Vec3::new(ndc_spawn_pos_x, ndc_spawn_pos_y, 0.),
             )
             .unwrap();
 
@@ -65,27 +104,27 @@ fn spawn_icon(
     font: Handle<Font>,
     texture: Handle<Image>,
     position: Vec3,
-    tier: u8,
+    tier: Option<u8>,
 ) {
-    println!("tier: {}", tier);
-
-    // tier number
-    parent.spawn(Text2dBundle {
-        text: Text {
-            sections: vec![TextSection::new(
-                num_to_roman(tier),
-                TextStyle {
-                    color: Color::WHITE,
-                    font,
-                    font_size: ICON_SIZE * 1.2,
-                },
-            )],
+    // tier number, tactics have none
+    if let Some(tier) = tier {
+        parent.spawn(Text2dBundle {
+            text: Text {
+                sections: vec![TextSection::new(
+                    num_to_roman(tier),
+                    TextStyle {
+                        color: Color::WHITE,
+                        font,
+                        font_size: ICON_SIZE * 1.2,
+                    },
+                )],
+                ..Default::default()
+            },
+            text_anchor: bevy::sprite::Anchor::CenterRight,
+            transform: Transform::from_translation(position - Vec3::new(8., 0., 0.)),
             ..Default::default()
-        },
-        text_anchor: bevy::sprite::Anchor::CenterRight,
-        transform: Transform::from_translation(position - Vec3::new(8., 0., 0.)),
-        ..Default::default()
-    });
+        });
+    }
 
     // icon
     parent.spawn(SpriteBundle {
@@ -101,14 +140,145 @@ fn spawn_icon(
     });
 }
 
+/// Window with tiers of stats, ability, on hit effect, tactics and equipped gear of a minion,
+/// titled with its recipe and level. The window grows upwards from the given bottom center.
+#[allow(clippy::too_many_arguments)]
+pub fn spawn_stats_window(
+    commands: &mut Commands,
+    textures: &TextureAssets,
+    fonts: &FontAssets,
+    balance: &Balance,
+    stats: &Stats,
+    level: Option<&MinionLevel>,
+    gear: &[Gear],
+    bottom: Vec3,
+) -> Entity {
+    // abilities, on hit effects, tactics and gear get their own rows, window grows upwards
+    let mut extra_rows = Vec::new();
+    if stats.ability.is_some() || stats.on_hit.is_some() {
+        let ability = stats.ability.map(|ability| {
+            (
+                ability_icon(textures, ability.kind),
+                Some(balance.abilities.tier(&ability)),
+            )
+        });
+        let on_hit = stats.on_hit.map(|effect| {
+            (
+                status_effect_icon(textures, effect.kind),
+                balance.status_effects.tier(&effect),
+            )
+        });
+        extra_rows.push([ability, on_hit]);
+    }
+    if stats.targeting != TargetingPolicy::FrontMost || stats.taunt {
+        let targeting = (stats.targeting != TargetingPolicy::FrontMost)
+            .then(|| (targeting_icon(textures, stats.targeting), None));
+        let taunt = stats.taunt.then(|| (textures.taunt_icon.clone(), None));
+        extra_rows.push([targeting, taunt]);
+    }
+    for pair in gear.chunks(2) {
+        let mut icons = pair
+            .iter()
+            .map(|gear| (gear.kind.icon(textures), Some(gear.tier)));
+        extra_rows.push([icons.next(), icons.next()]);
+    }
+
+    let stat_row_count = StatKind::ALL.len().div_ceil(2) as f32;
+    let row_count = extra_rows.len() as f32 + stat_row_count;
+    let row_height = HOVER_WINDOW_SIZE.y / stat_row_count;
+    let window_size = Vec2::new(HOVER_WINDOW_SIZE.x, row_height * row_count);
+    let window_offset = bottom + Vec3::new(0., window_size.y / 2., 0.);
+
+    commands
+        .spawn(SpriteBundle {
+            texture: textures.square.clone(),
+            sprite: Sprite {
+                color: Color::BLACK.with_a(0.95),
+                custom_size: Some(window_size),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(window_offset),
+            ..Default::default()
+        })
+        .with_children(|parent| {
+            // level with experience towards the next one, recipe name above it
+            let mut titles = Vec::new();
+            if let Some(level) = level {
+                let next_level_experience = balance.experience.level_experience(level.level);
+                titles.push((
+                    format!(
+                        "LV {}  {:.0}/{:.0} XP",
+                        level.level, level.experience, next_level_experience
+                    ),
+                    Color::WHITE,
+                ));
+            }
+            if let Some(recipe) = stats.recipe {
+                titles.push((recipe.name().to_string(), Color::GOLD));
+            }
+            for (index, (title, color)) in titles.into_iter().enumerate() {
+                parent.spawn(Text2dBundle {
+                    text: Text::from_section(
+                        title,
+                        TextStyle {
+                            color,
+                            font: fonts.texts.clone(),
+                            font_size: ICON_SIZE,
+                        },
+                    ),
+                    text_anchor: bevy::sprite::Anchor::BottomCenter,
+                    transform: Transform::from_xyz(
+                        0.,
+                        window_size.y / 2. + 4. + (ICON_SIZE + 4.) * index as f32,
+                        HOVER_WINDOW_Z + 1.,
+                    ),
+                    ..Default::default()
+                });
+            }
+
+            let x = window_size.x / 4.;
+            let row_y = |row: f32| window_size.y / 2. - row_height * (row + 0.5);
+
+            for (row, icons) in extra_rows.into_iter().enumerate() {
+                for (icon, icon_x) in icons.into_iter().zip([-x, x]) {
+                    if let Some((texture, tier)) = icon {
+                        spawn_icon(
+                            parent,
+                            fonts.tier_numbers.clone(),
+                            texture,
+                            Vec3::new(icon_x, row_y(row as f32), HOVER_WINDOW_Z + 1.),
+                            tier,
+                        );
+                    }
+                }
+            }
+
+            // stats come in pairs below the extra rows
+            let first_row = row_count - stat_row_count;
+            for (i, stat) in StatKind::ALL.into_iter().enumerate() {
+                let row = first_row + (i / 2) as f32;
+                let icon_x = if i % 2 == 0 { -x } else { x };
+                spawn_icon(
+                    parent,
+                    fonts.tier_numbers.clone(),
+                    stat_icon(textures, stat),
+                    Vec3::new(icon_x, row_y(row), HOVER_WINDOW_Z + 1.),
+                    Some(balance.minion.tier(stat, stats)),
+                );
+            }
+        })
+        .id()
+}
+
 fn handle_minion_stats_hover(
     mut commands: Commands,
     textures: Res<TextureAssets>,
     fonts: Res<FontAssets>,
-    minion_query: Query<(Entity, &Clickable, &Stats), With<Minion>>,
+    balance: Res<Balance>,
+    minion_query: Query<(Entity, &Clickable, &Stats, &MinionLevel, &Equipment), With<Minion>>,
     hover_window_query: Query<Entity, With<HoverWindow>>,
 ) {
-    for (entity, clickable, stats) in minion_query.iter() {
+    for (entity, clickable, stats, level, equipment) in minion_query.iter() {
         if clickable.hover_ended {
             for entity in hover_window_query.iter() {
                 commands.entity(entity).despawn_recursive();
@@ -120,66 +290,20 @@ fn handle_minion_stats_hover(
             continue;
         }
 
-        // spawn hover window
-        let window_entity = commands
-            .spawn((
-                SpriteBundle {
-                    texture: textures.square.clone(),
-                    sprite: Sprite {
-                        color: Color::BLACK.with_a(0.95),
-                        custom_size: Some(HOVER_WINDOW_SIZE),
-                        ..Default::default()
-                    },
-                    transform: Transform::from_translation(
-                        HOVER_WINDOW_OFFSET.extend(HOVER_WINDOW_Z),
-                    ),
-                    ..Default::default()
-                },
-                HoverWindow,
-            ))
-            .with_children(|parent| {
-                let x = HOVER_WINDOW_SIZE.x / 4.;
-                let y = HOVER_WINDOW_SIZE.y / 4.;
-
-                let tier = ((stats.damage - MINION_DMG_BASE) / MINION_DMG_INC).ceil() as u8;
-                spawn_icon(
-                    parent,
-                    fonts.tier_numbers.clone(),
-                    textures.sword_icon.clone(),
-                    Vec3::new(-x, y, HOVER_WINDOW_Z + 1.),
-                    tier,
-                );
-
-                let tier = ((stats.speed - MINION_SPEED_BASE) / MINION_SPEED_INC).ceil() as u8;
-                spawn_icon(
-                    parent,
-                    fonts.tier_numbers.clone(),
-                    textures.boot_icon.clone(),
-                    Vec3::new(x, y, HOVER_WINDOW_Z + 1.),
-                    tier,
-                );
-
-                let tier = ((stats.max_hp - MINION_HP_BASE) / MINION_HP_INC).ceil() as u8;
-                spawn_icon(
-                    parent,
-                    fonts.tier_numbers.clone(),
-                    textures.hearth_icon.clone(),
-                    Vec3::new(-x, -y, HOVER_WINDOW_Z + 1.),
-                    tier,
-                );
-
-                let tier = ((stats.hp_regeneration - MINION_HP_REGEN_BASE) / MINION_HP_REGEN_INC)
-                    .ceil() as u8;
-                spawn_icon(
-                    parent,
-                    fonts.tier_numbers.clone(),
-                    textures.hp_regeneration_icon.clone(),
-                    Vec3::new(x, -y, HOVER_WINDOW_Z + 1.),
-                    tier,
-                );
-            })
-            .id();
-
-        commands.entity(entity).add_child(window_entity);
+        let bottom = HOVER_WINDOW_OFFSET - Vec2::new(0., HOVER_WINDOW_SIZE.y / 2.);
+        let window_entity = spawn_stats_window(
+            &mut commands,
+            &textures,
+            &fonts,
+            &balance,
+            stats,
+            Some(level),
+            &equipment.0,
+            bottom.extend(HOVER_WINDOW_Z),
+        );
+        commands
+            .entity(window_entity)
+            .insert(HoverWindow)
+            .set_parent(entity);
     }
 }
@@ -1,84 +1,241 @@
-use bevy::prelude::*;
+use bevy::{
+    prelude::*,
+    tasks::{block_on, futures_lite::future, AsyncComputeTaskPool, Task},
+};
 use rand::{rngs::StdRng, Rng, SeedableRng};
 
 use crate::{
-    enemy::{DropRewards, Enemy},
+    balance::{ArchetypeBalance, Balance, BossBalance, DungeonBalance},
+    battle::simulation::{estimate_battle, BattleOutcome},
+    dungeon_map::{DungeonMap, NodeKind},
+    enemy::{DropRewards, EliteModifier, Enemy, RewardRng, MAX_ENEMY_COUNT},
+    formation::{lineup, FormationSlot},
+    gear::{Gear, GearKind, GEAR_COLOR},
     loading::{FontAssets, TextureAssets},
+    minions::Minion,
     mouse_control::Clickable,
-    stats::{
-        Stats, BATTLES_TO_ENEMY_TIER_INC, BATTLES_TO_ITEM_TIER_INC, ENEMY_DMG_BASE, ENEMY_DMG_INC,
-        ENEMY_HP_BASE, ENEMY_HP_INC, ENEMY_HP_REGEN_BASE, ENEMY_HP_REGEN_INC, ENEMY_SPEED_BASE,
-        ENEMY_SPEED_INC,
-    },
-    summoning::{SummoningItem, SummoningItemType},
+    recovery::PendingRest,
+    rewards::PendingRewards,
+    run_seed::{RngStream, RunSeed},
+    shop::ShopStock,
+    stats::{AbilityKind, StatKind, Stats, TargetingPolicy},
+    status_effects::{status_effect_icon, StatusEffectKind},
+    summoning::{ability_icon, stat_icon, targeting_icon, SummoningItem, SummoningItemType},
     utils::num_to_roman,
     BattleCount, GameScreen, GameState,
 };
 
-const MAX_CARD_COUNT: usize = 3;
 const MAX_REWARD_COUNT: usize = 3;
 
-const CARD_SIZE: Vec2 = Vec2::new(400., 600.);
-const CARDS_Y: f32 = 30.;
+/// Floors of the map go from left to right, nodes of a floor are stacked vertically.
+const MAP_LEFT: f32 = -860.;
+const MAP_RIGHT: f32 = 320.;
+const MAP_Y: f32 = -40.;
+const MAP_NODE_SPACING: f32 = 200.;
+const MAP_NODE_SIZE: f32 = 96.;
+const MAP_NODE_ICON_SIZE: f32 = 64.;
+const MAP_PATH_WIDTH: f32 = 6.;
+
+/// Card describing a hovered node is shown right of the map.
+const CARD_SIZE: Vec2 = Vec2::new(400., 680.);
+const NODE_PREVIEW_POS: Vec2 = Vec2::new(1920. / 2. - CARD_SIZE.x / 2. - 60., -40.);
+const NODE_PREVIEW_LABEL_SIZE: f32 = 64.;
 const ENEMY_STAT_ICON_SIZE: f32 = 64.;
 const CARD_STAT_ICON_OFFSET_Y: f32 = 32.;
 const CARD_STAT_TIER_OFFSET_X: f32 = 16.;
+/// Every enemy of the group gets a block of icon rows with this many icons per row.
+const ENEMY_BLOCK_ROW_LENGTH: usize = 4;
+const ENEMY_BLOCK_MARGIN: f32 = 8.;
+/// Archetype, passive and elite modifier of the enemy are written above its icons.
+const ENEMY_BLOCK_LABEL_SIZE: f32 = 32.;
+
+/// Scale of the stat icons for each group size, so that all enemies fit on the card.
+const ENEMY_BLOCK_SCALES: [f32; MAX_ENEMY_COUNT] = [0.75, 0.6, 0.5];
+
+const ABILITY_KINDS: [AbilityKind; 4] = [
+    AbilityKind::AreaHit,
+    AbilityKind::Heal,
+    AbilityKind::Shield,
+    AbilityKind::Stun,
+];
+
+const ON_HIT_KINDS: [StatusEffectKind; 4] = [
+    StatusEffectKind::Poison,
+    StatusEffectKind::Burn,
+    StatusEffectKind::Slow,
+    StatusEffectKind::Bleed,
+];
+
+const TARGETING_POLICIES: [TargetingPolicy; 4] = [
+    TargetingPolicy::FrontMost,
+    TargetingPolicy::LowestHp,
+    TargetingPolicy::HighestDamage,
+    TargetingPolicy::Random,
+];
 
 const REWARD_CARD_SIZE: Vec2 = Vec2::new(CARD_SIZE.x, ENEMY_STAT_ICON_SIZE);
+/// Win estimate is written right below a fight card and cost of skipping the fight below it.
+const ESTIMATE_TEXT_SIZE: f32 = 40.;
+const SKIP_TEXT_SIZE: f32 = 32.;
+const CARD_BORDER_WIDTH: f32 = 8.;
+/// Battles simulated for every fight card, in batches on background threads so the screen stays
+/// smooth and shows the results so far.
+const ESTIMATE_SIMULATION_COUNT: usize = 100;
+const ESTIMATE_BATCH_SIZE: usize = 10;
+
+/// Reroll button sits in the top left corner, minions stand below the map.
+const REROLL_BUTTON_SIZE: Vec2 = Vec2::new(400., 64.);
+const REROLL_BUTTON_POS: Vec2 = Vec2::new(
+    -1920. / 2. + 20. + REROLL_BUTTON_SIZE.x / 2.,
+    1080. / 2. - 20. - REROLL_BUTTON_SIZE.y / 2.,
+);
+const REROLL_TEXT_SIZE: f32 = 36.;
 
 pub struct PlanningScreenPlugin;
 
 impl Plugin for PlanningScreenPlugin {
     fn build(&self, app: &mut App) {
-        app.insert_resource(PlanningRng(StdRng::from_entropy()))
+        app.init_resource::<RerollsUsed>()
             .add_systems(
                 OnEnter(GameScreen::Planning),
-                (spawn_title_and_background, spawn_enemy_cards),
+                (
+                    start_next_act,
+                    reset_planning_rngs,
+                    spawn_title_and_background,
+                    spawn_map,
+                    spawn_reroll_button,
+                )
+                    .chain(),
             )
             .add_systems(OnExit(GameScreen::Planning), clean_planning_screen)
             .add_systems(
                 Update,
-                (handle_enemy_selection)
+                (
+                    handle_node_hover,
+                    handle_node_selection,
+                    handle_fight_skip,
+                    (handle_reroll, start_win_estimates, run_win_estimates).chain(),
+                )
                     .run_if(in_state(GameState::Playing).and_then(in_state(GameScreen::Planning))),
             );
     }
 }
 
+/// Group of enemies waiting at a fight node, each with its own rewards and sprite.
+#[derive(Component)]
+struct EnemyCard {
+    enemies: Vec<(Stats, DropRewards, Handle<Image>)>,
+}
+
+/// Win chance against a fight card estimated by simulating battles of the current minions.
+#[derive(Component)]
+struct WinEstimate {
+    /// Batches still running, each gives its wins and lost minions.
+    batches: Vec<Task<(usize, usize)>>,
+    simulations: usize,
+    wins: usize,
+    minions_lost: usize,
+}
+
+/// Border of the card of a fight node, coloured by the estimated difficulty.
+#[derive(Component)]
+struct CardBorder(usize);
+
+#[derive(Component)]
+struct EstimateText(usize);
+
+/// Node of the map which can be visited next, holds index of the node on the current floor.
 #[derive(Component)]
-struct EnemyCard;
+struct MapNodeButton(usize);
+
+/// Card describing what waits at a node of the current floor, shown while the node is hovered.
+#[derive(Component)]
+struct NodePreview(usize);
 
 #[derive(Resource)]
 struct PlanningRng(StdRng);
 
+/// Rerolls of fights used during the run, the balance decides how many there are.
+#[derive(Resource, Default)]
+pub struct RerollsUsed(pub usize);
+
+impl RerollsUsed {
+    fn left(&self, balance: &DungeonBalance) -> usize {
+        balance.rerolls.saturating_sub(self.0)
+    }
+
+    /// Returns false when there is no reroll left.
+    fn use_reroll(&mut self, balance: &DungeonBalance) -> bool {
+        if self.left(balance) == 0 {
+            return false;
+        }
+
+        self.0 += 1;
+        true
+    }
+}
+
+#[derive(Component)]
+struct RerollButton;
+
 #[derive(Component)]
 struct PlanningScreenEntity;
 
+/// Generate map of the next act once the boss of the current one is beaten.
+pub fn start_next_act(
+    run_seed: Res<RunSeed>,
+    balance: Res<Balance>,
+    mut dungeon_map: ResMut<DungeonMap>,
+) {
+    if !dungeon_map.is_finished() {
+        return;
+    }
+
+    let mut rng = run_seed.rng(RngStream::Map, dungeon_map.act + 1);
+    dungeon_map.start_next_act(&mut rng, &balance.dungeon);
+}
+
+fn reset_planning_rngs(
+    mut commands: Commands,
+    run_seed: Res<RunSeed>,
+    dungeon_map: Res<DungeonMap>,
+) {
+    commands.insert_resource(PlanningRng(
+        run_seed.rng(RngStream::Planning, dungeon_map.steps),
+    ));
+    commands.insert_resource(RewardRng(
+        run_seed.rng(RngStream::Rewards, dungeon_map.steps),
+    ));
+}
+
 fn spawn_icon(
     parent: &mut ChildBuilder,
     font: Handle<Font>,
     texture: Handle<Image>,
     position: Vec3,
-    tier: u8,
+    tier: Option<u8>,
 ) {
-    // tier number
-    parent.spawn(Text2dBundle {
-        text: Text {
-            sections: vec![TextSection::new(
-                num_to_roman(tier),
-                TextStyle {
-                    color: Color::WHITE,
-                    font,
-                    font_size: ENEMY_STAT_ICON_SIZE,
-                },
-            )],
+    // tier number, targeting has none
+    if let Some(tier) = tier {
+        parent.spawn(Text2dBundle {
+            text: Text {
+                sections: vec![TextSection::new(
+                    num_to_roman(tier),
+                    TextStyle {
+                        color: Color::WHITE,
+                        font,
+                        font_size: ENEMY_STAT_ICON_SIZE,
+                    },
+                )],
+                ..Default::default()
+            },
+            text_anchor: bevy::sprite::Anchor::TopRight,
+            transform: Transform::from_translation(
+                position - Vec3::new(CARD_STAT_TIER_OFFSET_X, 0., 0.),
+            ),
             ..Default::default()
-        },
-        text_anchor: bevy::sprite::Anchor::TopRight,
-        transform: Transform::from_translation(
-            position - Vec3::new(CARD_STAT_TIER_OFFSET_X, 0., 0.),
-        ),
-        ..Default::default()
-    });
+        });
+    }
 
     // icon
     parent.spawn(SpriteBundle {
@@ -96,10 +253,12 @@ fn spawn_icon(
 
 fn spawn_reward_card(
     parent: &mut ChildBuilder,
-    fonts: &Res<FontAssets>,
-    textures: &Res<TextureAssets>,
+    fonts: &FontAssets,
+    textures: &TextureAssets,
     index: usize,
-    item: &SummoningItem,
+    icon: Handle<Image>,
+    tier: u8,
+    category_color: Color,
 ) {
     // reward card
     parent
@@ -123,7 +282,7 @@ fn spawn_reward_card(
             card.spawn(SpriteBundle {
                 texture: textures.circle.clone(),
                 sprite: Sprite {
-                    color: Color::YELLOW,
+                    color: category_color,
                     custom_size: Some(Vec2::splat(REWARD_CARD_SIZE.y * 0.5)),
                     anchor: bevy::sprite::Anchor::CenterRight,
                     ..Default::default()
@@ -136,7 +295,7 @@ fn spawn_reward_card(
             card.spawn(Text2dBundle {
                 text: Text {
                     sections: vec![TextSection::new(
-                        num_to_roman(item.tier),
+                        num_to_roman(tier),
                         TextStyle {
                             color: Color::WHITE,
                             font: fonts.tier_numbers.clone(),
@@ -150,14 +309,9 @@ fn spawn_reward_card(
                 ..Default::default()
             });
 
-            // item type icon
+            // item type or gear kind icon
             card.spawn(SpriteBundle {
-                texture: match item.item_type {
-                    SummoningItemType::Damage => textures.sword_icon.clone(),
-                    SummoningItemType::Speed => textures.boot_icon.clone(),
-                    SummoningItemType::MaxHP => textures.hearth_icon.clone(),
-                    SummoningItemType::HPRegeneration => textures.hp_regeneration_icon.clone(),
-                },
+                texture: icon,
                 sprite: Sprite {
                     color: Color::CYAN,
                     custom_size: Some(Vec2::splat(REWARD_CARD_SIZE.y * 0.7)),
@@ -174,6 +328,7 @@ fn spawn_title_and_background(
     mut commands: Commands,
     textures: Res<TextureAssets>,
     fonts: Res<FontAssets>,
+    dungeon_map: Res<DungeonMap>,
 ) {
     // background
     commands.spawn((
@@ -202,7 +357,7 @@ fn spawn_title_and_background(
         Text2dBundle {
             text: Text {
                 sections: vec![TextSection::new(
-                    "SELECT AN OPONENT:",
+                    format!("ACT {}: CHOOSE YOUR PATH", dungeon_map.act),
                     TextStyle {
                         color: Color::WHITE,
                         font: fonts.texts.clone(),
@@ -218,160 +373,1077 @@ fn spawn_title_and_background(
     ));
 }
 
-fn spawn_enemy_cards(
+/// Tiers rolled for a single enemy together with its archetype and elite modifier, they are shown
+/// on its card.
+struct EnemyTiers {
+    stats: Vec<(StatKind, u8)>,
+    ability: Option<(AbilityKind, u8)>,
+    on_hit: Option<(StatusEffectKind, u8)>,
+    targeting: TargetingPolicy,
+    taunt: bool,
+    archetype: Option<ArchetypeBalance>,
+    modifier: Option<EliteModifier>,
+}
+
+/// Roll stats of a single enemy, the tier bonus and elite modifier make elites and bosses
+/// stronger. Bosses have no archetype.
+fn roll_enemy(
+    planning_rng: &mut StdRng,
+    battle_count: usize,
+    balance: &Balance,
+    group_size: usize,
+    tier_bonus: usize,
+    archetype: Option<&ArchetypeBalance>,
+    modifier: Option<EliteModifier>,
+) -> (Stats, EnemyTiers) {
+    let max_tier = battle_count / balance.battles_to_enemy_tier_inc + 2;
+    let mut stat_tiers: Vec<_> = StatKind::ALL
+        .into_iter()
+        .map(|stat| {
+            let tier = planning_rng.gen_range(0..max_tier) + tier_bonus;
+            (stat, tier.min(10) as u8)
+        })
+        .collect();
+    if let Some(archetype) = archetype {
+        archetype.bias(&mut stat_tiers);
+    }
+    // tier zero means the enemy has no ability or on hit effect
+    let ability_tier = planning_rng.gen_range(0..(max_tier - 1)).min(10) as u8;
+    let ability_kind = ABILITY_KINDS[planning_rng.gen_range(0..ABILITY_KINDS.len())];
+    let on_hit_tier = planning_rng.gen_range(0..(max_tier - 1)).min(10) as u8;
+    let on_hit_kind = ON_HIT_KINDS[planning_rng.gen_range(0..ON_HIT_KINDS.len())];
+    let targeting = TARGETING_POLICIES[planning_rng.gen_range(0..TARGETING_POLICIES.len())];
+
+    // HP and damage of a group are split among its enemies
+    let group_multiplier =
+        balance.enemy_group_strength.powi(group_size as i32 - 1) / group_size as f32;
+
+    let mut stats = balance.enemy.stats(&stat_tiers);
+    stats.max_hp *= group_multiplier;
+    stats.current_hp = stats.max_hp;
+    stats.damage *= group_multiplier;
+    stats.targeting = targeting;
+    if ability_tier > 0 {
+        stats.ability = Some(balance.abilities.ability(ability_kind, ability_tier));
+        stats.max_mana = balance.abilities.mana;
+        stats.mana_regeneration = balance.abilities.mana_regeneration;
+    }
+    if on_hit_tier > 0 {
+        stats.on_hit = balance.status_effects.effect(on_hit_kind, on_hit_tier);
+    }
+    if let Some(archetype) = archetype {
+        archetype.apply_passive(&mut stats);
+    }
+    if let Some(modifier) = modifier {
+        balance.elites.apply(modifier, &mut stats);
+    }
+
+    let tiers = EnemyTiers {
+        stats: stat_tiers,
+        ability: (ability_tier > 0).then_some((ability_kind, ability_tier)),
+        on_hit: (on_hit_tier > 0).then_some((on_hit_kind, on_hit_tier)),
+        targeting,
+        taunt: stats.taunt,
+        archetype: archetype.cloned(),
+        modifier,
+    };
+
+    (stats, tiers)
+}
+
+/// Block with the label and stat icons of a single enemy of the group, blocks are stacked from the
+/// top of the card.
+fn spawn_enemy_block(
+    parent: &mut ChildBuilder,
+    fonts: &FontAssets,
+    textures: &TextureAssets,
+    index: usize,
+    group_size: usize,
+    tiers: &EnemyTiers,
+) {
+    let scale = ENEMY_BLOCK_SCALES[group_size - 1];
+
+    let mut icons: Vec<_> = tiers
+        .stats
+        .iter()
+        .map(|&(stat, tier)| (stat_icon(textures, stat), Some(tier)))
+        .collect();
+    if let Some((kind, tier)) = tiers.ability {
+        icons.push((ability_icon(textures, kind), Some(tier)));
+    }
+    if let Some((kind, tier)) = tiers.on_hit {
+        icons.push((status_effect_icon(textures, kind), Some(tier)));
+    }
+    icons.push((targeting_icon(textures, tiers.targeting), None));
+    if tiers.taunt {
+        icons.push((textures.taunt_icon.clone(), None));
+    }
+
+    // elite modifier comes first, then the archetype and its passive
+    let mut label = Vec::new();
+    if let Some(modifier) = tiers.modifier {
+        label.push((modifier.name().to_string() + " ", Color::ORANGE_RED));
+    }
+    if let Some(archetype) = &tiers.archetype {
+        label.push((archetype.name.clone(), Color::GOLD));
+        label.push((format!(" ({})", archetype.passive.name()), Color::WHITE));
+    }
+    let label_height = if label.is_empty() {
+        0.
+    } else {
+        ENEMY_BLOCK_LABEL_SIZE
+    };
+
+    let row_count = icons.len().div_ceil(ENEMY_BLOCK_ROW_LENGTH);
+    let block_height =
+        (ENEMY_STAT_ICON_SIZE * row_count as f32 + label_height) * scale + ENEMY_BLOCK_MARGIN;
+    let y = CARD_SIZE.y / 2. - CARD_STAT_ICON_OFFSET_Y - block_height * index as f32;
+    let slot_width = CARD_SIZE.x / ENEMY_BLOCK_ROW_LENGTH as f32;
+
+    parent
+        .spawn(SpatialBundle::from_transform(
+            Transform::from_xyz(0., y, 1.).with_scale(Vec3::splat(scale)),
+        ))
+        .with_children(|block| {
+            if !label.is_empty() {
+                let sections = label.into_iter().map(|(text, color)| {
+                    TextSection::new(
+                        text,
+                        TextStyle {
+                            color,
+                            font: fonts.texts.clone(),
+                            font_size: ENEMY_BLOCK_LABEL_SIZE,
+                        },
+                    )
+                });
+                block.spawn(Text2dBundle {
+                    text: Text::from_sections(sections),
+                    text_anchor: bevy::sprite::Anchor::TopCenter,
+                    transform: Transform::from_xyz(0., 0., 0.),
+                    ..Default::default()
+                });
+            }
+
+            for (i, (texture, tier)) in icons.into_iter().enumerate() {
+                let row = i / ENEMY_BLOCK_ROW_LENGTH;
+                let column = i % ENEMY_BLOCK_ROW_LENGTH;
+                // icons sit right of the slot center and tier numbers left of it
+                let x = slot_width * (column as f32 + 0.5) - CARD_SIZE.x / 2.
+                    + CARD_STAT_TIER_OFFSET_X / 2.;
+                let y = -ENEMY_STAT_ICON_SIZE * row as f32 - label_height;
+                let position = Vec3::new(x / scale, y, 0.);
+                spawn_icon(block, fonts.tier_numbers.clone(), texture, position, tier);
+            }
+        });
+}
+
+fn roll_item(
+    reward_rng: &mut StdRng,
+    battle_count: usize,
+    balance: &Balance,
+    tier_bonus: usize,
+) -> SummoningItem {
+    let item_type = SummoningItemType::ALL[reward_rng.gen_range(0..SummoningItemType::ALL.len())];
+    let tier = if item_type.is_tactic() {
+        1
+    } else {
+        let tier = reward_rng.gen_range(1..(battle_count / balance.battles_to_item_tier_inc + 2));
+        (tier + tier_bonus).min(10) as u8
+    };
+
+    SummoningItem {
+        item_type,
+        tier,
+        quantity: 1,
+    }
+}
+
+fn roll_gear(
+    reward_rng: &mut StdRng,
+    battle_count: usize,
+    balance: &Balance,
+    tier_bonus: usize,
+) -> Gear {
+    let kind = GearKind::ALL[reward_rng.gen_range(0..GearKind::ALL.len())];
+    let tier = reward_rng.gen_range(1..(battle_count / balance.battles_to_item_tier_inc + 2));
+
+    Gear {
+        kind,
+        tier: (tier + tier_bonus).min(10) as u8,
+    }
+}
+
+/// Roll enemies waiting at a normal or elite fight node together with their rewards and sprites.
+/// Elite groups share a modifier which adds to their rewards.
+fn roll_encounter(
+    planning_rng: &mut StdRng,
+    reward_rng: &mut StdRng,
+    asset_server: &AssetServer,
+    battle_count: usize,
+    balance: &Balance,
+    kind: NodeKind,
+) -> (Vec<(Stats, DropRewards, Handle<Image>)>, Vec<EnemyTiers>) {
+    let dungeon = &balance.dungeon;
+    let (group_size, tier_bonus, item_tier_bonus) = match kind {
+        NodeKind::Elite => (
+            planning_rng.gen_range(1..=MAX_ENEMY_COUNT),
+            dungeon.elite_tier_bonus,
+            dungeon.elite_item_tier_bonus,
+        ),
+        _ => (planning_rng.gen_range(1..=MAX_ENEMY_COUNT), 0, 0),
+    };
+    let modifier = (kind == NodeKind::Elite)
+        .then(|| EliteModifier::ALL[planning_rng.gen_range(0..EliteModifier::ALL.len())]);
+
+    let mut enemies = Vec::new();
+    let mut enemy_tiers = Vec::new();
+    for _ in 0..group_size {
+        let archetype = &balance.archetypes[planning_rng.gen_range(0..balance.archetypes.len())];
+        let (stats, tiers) = roll_enemy(
+            planning_rng,
+            battle_count,
+            balance,
+            group_size,
+            tier_bonus,
+            Some(archetype),
+            modifier,
+        );
+
+        // every enemy drops its own rewards, a group drops as many as a single enemy at most,
+        // elites always drop as many as they can and the first one drops more for the modifier
+        let max_reward_count = MAX_REWARD_COUNT / group_size;
+        let mut reward_count = if kind == NodeKind::Fight {
+            reward_rng.gen_range(1..=max_reward_count)
+        } else {
+            max_reward_count
+        };
+        if enemies.is_empty() && modifier.is_some() {
+            reward_count += balance.elites.modifier_reward_count;
+        }
+
+        // gear takes one of the rewards of the first enemy, elites always drop it
+        let gear = (enemies.is_empty()
+            && (kind == NodeKind::Elite || reward_rng.gen_bool(balance.gear.drop_chance as f64)))
+        .then(|| roll_gear(reward_rng, battle_count, balance, item_tier_bonus));
+        let items = (0..reward_count - gear.iter().len())
+            .map(|_| roll_item(reward_rng, battle_count, balance, item_tier_bonus))
+            .collect();
+
+        let sprite = asset_server.load(archetype.sprite.clone());
+        enemies.push((stats, DropRewards { items, gear }, sprite));
+        enemy_tiers.push(tiers);
+    }
+
+    (enemies, enemy_tiers)
+}
+
+/// Roll the boss of the act, it always drops gear and stat, ability or on hit items of the highest
+/// tier which can currently drop.
+fn roll_boss(
+    planning_rng: &mut StdRng,
+    reward_rng: &mut StdRng,
+    battle_count: usize,
+    balance: &Balance,
+    boss: &BossBalance,
+) -> ((Stats, DropRewards), EnemyTiers) {
+    let (mut stats, mut tiers) = roll_enemy(
+        planning_rng,
+        battle_count,
+        balance,
+        1,
+        balance.dungeon.boss_tier_bonus,
+        None,
+        None,
+    );
+    stats.max_hp *= boss.hp_multiplier;
+    stats.current_hp = stats.max_hp;
+    stats.damage *= boss.damage_multiplier;
+    stats.ability = boss
+        .ability
+        .map(|(kind, tier)| balance.abilities.ability(kind, tier));
+    if stats.ability.is_some() {
+        stats.max_mana = balance.abilities.mana;
+        stats.mana_regeneration = balance.abilities.mana_regeneration;
+    } else {
+        stats.max_mana = 0.;
+        stats.mana_regeneration = 0.;
+    }
+    stats.on_hit = boss
+        .on_hit
+        .and_then(|(kind, tier)| balance.status_effects.effect(kind, tier));
+    stats.phases = boss.phases.clone();
+    tiers.ability = boss.ability;
+    tiers.on_hit = boss.on_hit;
+
+    let item_types: Vec<_> = SummoningItemType::ALL
+        .into_iter()
+        .filter(|item_type| !item_type.is_tactic() && !item_type.is_consumable())
+        .collect();
+    let tier =
+        battle_count / balance.battles_to_item_tier_inc + 1 + balance.dungeon.boss_item_tier_bonus;
+    let tier = tier.min(10) as u8;
+    let items = (0..MAX_REWARD_COUNT - 1)
+        .map(|_| SummoningItem {
+            item_type: item_types[reward_rng.gen_range(0..item_types.len())],
+            tier,
+            quantity: 1,
+        })
+        .collect();
+    let gear = Gear {
+        kind: GearKind::ALL[reward_rng.gen_range(0..GearKind::ALL.len())],
+        tier,
+    };
+
+    (
+        (
+            stats,
+            DropRewards {
+                items,
+                gear: Some(gear),
+            },
+        ),
+        tiers,
+    )
+}
+
+fn node_icon(textures: &TextureAssets, kind: NodeKind) -> Handle<Image> {
+    match kind {
+        NodeKind::Fight => textures.sword_icon.clone(),
+        NodeKind::Elite => textures.elite_icon.clone(),
+        NodeKind::Rest => textures.rest_icon.clone(),
+        NodeKind::Treasure => textures.treasure_icon.clone(),
+        NodeKind::Shop => textures.shop_icon.clone(),
+        NodeKind::Boss => textures.boss_icon.clone(),
+    }
+}
+
+fn node_label(kind: NodeKind, balance: &Balance, act: usize) -> String {
+    match kind {
+        NodeKind::Fight | NodeKind::Elite => format!(
+            "{}\nSKIP COSTS {}% OF HP",
+            if kind == NodeKind::Fight {
+                "FIGHT"
+            } else {
+                "ELITE"
+            },
+            (balance.dungeon.skip_hp_cost * 100.).round()
+        ),
+        NodeKind::Rest => format!(
+            "REST\nHEALS {}% OF HP",
+            (balance.dungeon.rest_heal * 100.).round()
+        ),
+        NodeKind::Treasure => "TREASURE".to_string(),
+        NodeKind::Shop => "SHOP".to_string(),
+        NodeKind::Boss => format!("BOSS\n{}", balance.boss(act).name),
+    }
+}
+
+/// Line between two connected nodes.
+fn spawn_path(
+    commands: &mut Commands,
+    textures: &TextureAssets,
+    from: Vec2,
+    to: Vec2,
+    color: Color,
+) {
+    let delta = to - from;
+
+    commands.spawn((
+        SpriteBundle {
+            texture: textures.square.clone(),
+            sprite: Sprite {
+                color,
+                custom_size: Some(Vec2::new(delta.length(), MAP_PATH_WIDTH)),
+                ..Default::default()
+            },
+            transform: Transform::from_translation(((from + to) / 2.).extend(0.))
+                .with_rotation(Quat::from_rotation_z(delta.y.atan2(delta.x))),
+            ..Default::default()
+        },
+        PlanningScreenEntity,
+    ));
+}
+
+/// Hidden card with the label of the node, the content is spawned by the caller.
+fn spawn_node_preview(
+    commands: &mut Commands,
+    textures: &TextureAssets,
+    fonts: &FontAssets,
+    index: usize,
+    label: String,
+    content: impl FnOnce(&mut ChildBuilder),
+) -> Entity {
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: textures.square.clone(),
+                sprite: Sprite {
+                    color: Color::DARK_GRAY.with_a(0.9),
+                    custom_size: Some(CARD_SIZE),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(NODE_PREVIEW_POS.extend(3.)),
+                visibility: Visibility::Hidden,
+                ..Default::default()
+            },
+            NodePreview(index),
+            PlanningScreenEntity,
+        ))
+        .with_children(|card| {
+            card.spawn(Text2dBundle {
+                text: Text {
+                    sections: vec![TextSection::new(
+                        label,
+                        TextStyle {
+                            color: Color::WHITE,
+                            font: fonts.texts.clone(),
+                            font_size: NODE_PREVIEW_LABEL_SIZE,
+                        },
+                    )],
+                    justify: JustifyText::Center,
+                    ..Default::default()
+                },
+                text_anchor: bevy::sprite::Anchor::BottomCenter,
+                transform: Transform::from_xyz(0., CARD_SIZE.y / 2. + 8., 1.),
+                ..Default::default()
+            });
+
+            content(card);
+        })
+        .id()
+}
+
+/// Roll enemies of a fight, elite or boss node of the current floor and spawn their card. Normal and
+/// elite fights can be skipped, their card tells what it costs. Returns the hidden card.
+#[allow(clippy::too_many_arguments)]
+fn spawn_fight_node(
+    commands: &mut Commands,
+    planning_rng: &mut StdRng,
+    reward_rng: &mut StdRng,
+    asset_server: &AssetServer,
+    textures: &TextureAssets,
+    fonts: &FontAssets,
+    battle_count: usize,
+    balance: &Balance,
+    dungeon_map: &DungeonMap,
+    node_entity: Entity,
+    index: usize,
+) -> Entity {
+    let kind = dungeon_map.floors[dungeon_map.current_floor()][index].kind;
+    let (enemies, enemy_tiers) = if kind == NodeKind::Boss {
+        let boss = balance.boss(dungeon_map.act);
+        let ((stats, rewards), tiers) =
+            roll_boss(planning_rng, reward_rng, battle_count, balance, boss);
+        let sprite = asset_server.load(boss.sprite.clone());
+        (vec![(stats, rewards, sprite)], vec![tiers])
+    } else {
+        roll_encounter(
+            planning_rng,
+            reward_rng,
+            asset_server,
+            battle_count,
+            balance,
+            kind,
+        )
+    };
+    let rewards: Vec<_> = enemies
+        .iter()
+        .flat_map(|(_, rewards, _)| {
+            let items = rewards
+                .items
+                .iter()
+                .map(|item| (item.item_type.icon(textures), item.tier, Color::YELLOW));
+            let gear = rewards
+                .gear
+                .iter()
+                .map(|gear| (gear.kind.icon(textures), gear.tier, GEAR_COLOR));
+            gear.chain(items).collect::<Vec<_>>()
+        })
+        .collect();
+    commands.entity(node_entity).insert(EnemyCard { enemies });
+
+    let label = node_label(kind, balance, dungeon_map.act);
+    let group_size = enemy_tiers.len();
+    spawn_node_preview(commands, textures, fonts, index, label, |card| {
+        card.spawn((
+            SpriteBundle {
+                texture: textures.square.clone(),
+                sprite: Sprite {
+                    color: Color::GRAY,
+                    custom_size: Some(CARD_SIZE + 2. * CARD_BORDER_WIDTH),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0., 0., -0.5),
+                ..Default::default()
+            },
+            CardBorder(index),
+        ));
+        card.spawn((
+            Text2dBundle {
+                text: Text::from_section(
+                    "ESTIMATING...",
+                    TextStyle {
+                        color: Color::WHITE,
+                        font: fonts.texts.clone(),
+                        font_size: ESTIMATE_TEXT_SIZE,
+                    },
+                ),
+                text_anchor: bevy::sprite::Anchor::TopCenter,
+                transform: Transform::from_xyz(0., -CARD_SIZE.y / 2. - CARD_BORDER_WIDTH, 1.),
+                ..Default::default()
+            },
+            EstimateText(index),
+        ));
+
+        for (index, tiers) in enemy_tiers.iter().enumerate() {
+            spawn_enemy_block(card, fonts, textures, index, group_size, tiers);
+        }
+        for (index, (icon, tier, color)) in rewards.into_iter().enumerate() {
+            spawn_reward_card(card, fonts, textures, index, icon, tier, color);
+        }
+
+        if kind != NodeKind::Boss {
+            card.spawn(Text2dBundle {
+                text: Text::from_section(
+                    "RMB - SKIP FIGHT\nNEEDS MINIONS TO PAY THE HP",
+                    TextStyle {
+                        color: Color::ORANGE_RED,
+                        font: fonts.texts.clone(),
+                        font_size: SKIP_TEXT_SIZE,
+                    },
+                )
+                .with_justify(JustifyText::Center),
+                text_anchor: bevy::sprite::Anchor::TopCenter,
+                transform: Transform::from_xyz(
+                    0.,
+                    -CARD_SIZE.y / 2. - CARD_BORDER_WIDTH - ESTIMATE_TEXT_SIZE,
+                    1.,
+                ),
+                ..Default::default()
+            });
+        }
+    })
+}
+
+#[allow(clippy::too_many_arguments)]
+fn spawn_map(
     mut commands: Commands,
     mut planning_rng: ResMut<PlanningRng>,
+    mut reward_rng: ResMut<RewardRng>,
+    asset_server: Res<AssetServer>,
     textures: Res<TextureAssets>,
     fonts: Res<FontAssets>,
     battle_count: Res<BattleCount>,
+    balance: Res<Balance>,
+    dungeon_map: Res<DungeonMap>,
 ) {
-    let card_count = planning_rng.0.gen_range(2..=MAX_CARD_COUNT);
-
-    for i in 0..card_count {
-        let damage_tier = planning_rng
-            .0
-            .gen_range(0..(battle_count.0 / BATTLES_TO_ENEMY_TIER_INC + 2))
-            .min(10) as u8;
-        let speed_tier = planning_rng
-            .0
-            .gen_range(0..(battle_count.0 / BATTLES_TO_ENEMY_TIER_INC + 2))
-            .min(10) as u8;
-        let hp_tier = planning_rng
-            .0
-            .gen_range(0..(battle_count.0 / BATTLES_TO_ENEMY_TIER_INC + 2))
-            .min(10) as u8;
-        let hp_regeneration_tier = planning_rng
-            .0
-            .gen_range(0..(battle_count.0 / BATTLES_TO_ENEMY_TIER_INC + 2))
-            .min(10) as u8;
-
-        let stats = Stats {
-            current_hp: ENEMY_HP_BASE + hp_tier as f32 * ENEMY_HP_INC,
-            max_hp: ENEMY_HP_BASE + hp_tier as f32 * ENEMY_HP_INC,
-            hp_regeneration: ENEMY_HP_REGEN_BASE + hp_regeneration_tier as f32 * ENEMY_HP_REGEN_INC,
-            damage: ENEMY_DMG_BASE + damage_tier as f32 * ENEMY_DMG_INC,
-            speed: ENEMY_SPEED_BASE + speed_tier as f32 * ENEMY_SPEED_INC,
-            ..Default::default()
-        };
+    let floor_count = dungeon_map.floors.len();
+    let node_position = |floor: usize, index: usize| {
+        let node_count = dungeon_map.floors[floor].len();
+        let x = MAP_LEFT + (MAP_RIGHT - MAP_LEFT) * floor as f32 / (floor_count - 1).max(1) as f32;
+        let y = MAP_Y + ((node_count - 1) as f32 / 2. - index as f32) * MAP_NODE_SPACING;
+        Vec2::new(x, y)
+    };
+    let current_floor = dungeon_map.current_floor();
+    let reachable = dungeon_map.reachable();
+    let is_visited = |floor: usize, index: usize| dungeon_map.route.get(floor) == Some(&index);
 
-        let reward_count = planning_rng.0.gen_range(1..=MAX_REWARD_COUNT);
-        let mut rewards = DropRewards(Vec::new());
-        for _ in 0..reward_count {
-            rewards.0.push(SummoningItem {
-                item_type: match planning_rng.0.gen_range(0..4) {
-                    0 => SummoningItemType::Damage,
-                    1 => SummoningItemType::Speed,
-                    2 => SummoningItemType::MaxHP,
-                    3 => SummoningItemType::HPRegeneration,
-                    _ => panic!("Invalid item type!"),
-                },
-                tier: planning_rng
-                    .0
-                    .gen_range(1..(battle_count.0 / BATTLES_TO_ITEM_TIER_INC + 2))
-                    .min(10) as u8,
-                quantity: 1,
-            });
+    // paths, the route taken so far and the paths leading on from it are highlighted
+    for (floor, nodes) in dungeon_map.floors.iter().enumerate() {
+        for (index, node) in nodes.iter().enumerate() {
+            for &next in node.next.iter() {
+                let color = if is_visited(floor, index) && is_visited(floor + 1, next) {
+                    Color::WHITE
+                } else if is_visited(floor, index) && floor + 1 == current_floor {
+                    Color::GOLD
+                } else {
+                    Color::GRAY.with_a(0.5)
+                };
+                spawn_path(
+                    &mut commands,
+                    &textures,
+                    node_position(floor, index),
+                    node_position(floor + 1, next),
+                    color,
+                );
+            }
         }
+    }
 
-        let x_pos = (1920. / (card_count as f32 + 1.)) * (i as f32 + 1.) - 1920. / 2.;
+    // nodes
+    for (floor, nodes) in dungeon_map.floors.iter().enumerate() {
+        for (index, node) in nodes.iter().enumerate() {
+            let can_visit = floor == current_floor && reachable.contains(&index);
+            let (background_color, icon_color) = if is_visited(floor, index) {
+                (Color::DARK_GREEN, Color::WHITE)
+            } else if can_visit {
+                (Color::GOLD, Color::BLACK)
+            } else {
+                (Color::DARK_GRAY, Color::GRAY)
+            };
 
-        // enemy card
-        commands
-            .spawn((
-                SpriteBundle {
-                    texture: textures.square.clone(),
-                    sprite: Sprite {
-                        color: Color::DARK_GRAY.with_a(0.7),
-                        custom_size: Some(CARD_SIZE),
+            let node_entity = commands
+                .spawn((
+                    SpriteBundle {
+                        texture: textures.circle.clone(),
+                        sprite: Sprite {
+                            color: background_color,
+                            custom_size: Some(Vec2::splat(MAP_NODE_SIZE)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_translation(
+                            node_position(floor, index).extend(1.),
+                        ),
                         ..Default::default()
                     },
-                    transform: Transform::from_xyz(x_pos, CARDS_Y, 0.),
-                    ..Default::default()
-                },
-                Clickable::default(),
-                EnemyCard,
-                stats,
-                rewards.clone(),
-                PlanningScreenEntity,
-            ))
-            .with_children(|parent| {
-                let first_row_y = CARD_SIZE.y / 2. - CARD_STAT_ICON_OFFSET_Y;
-                let second_row_y =
-                    CARD_SIZE.y / 2. - 2. * CARD_STAT_ICON_OFFSET_Y - ENEMY_STAT_ICON_SIZE;
-                let left_x = -CARD_SIZE.x / 4.;
-                let right_x = CARD_SIZE.x / 4.;
-
-                // damage icon
-                let position = Vec3::new(left_x, first_row_y, 1.);
-                spawn_icon(
-                    parent,
-                    fonts.tier_numbers.clone(),
-                    textures.sword_icon.clone(),
-                    position,
-                    damage_tier,
-                );
+                    PlanningScreenEntity,
+                ))
+                .with_children(|parent| {
+                    parent.spawn(SpriteBundle {
+                        texture: node_icon(&textures, node.kind),
+                        sprite: Sprite {
+                            color: icon_color,
+                            custom_size: Some(Vec2::splat(MAP_NODE_ICON_SIZE)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_xyz(0., 0., 1.),
+                        ..Default::default()
+                    });
+                })
+                .id();
 
-                // speed icon
-                let position = Vec3::new(right_x, first_row_y, 1.);
-                spawn_icon(
-                    parent,
-                    fonts.tier_numbers.clone(),
-                    textures.boot_icon.clone(),
-                    position,
-                    speed_tier,
-                );
+            if !can_visit {
+                continue;
+            }
+            commands
+                .entity(node_entity)
+                .insert((Clickable::default(), MapNodeButton(index)));
 
-                // hp icon
-                let position = Vec3::new(left_x, second_row_y, 1.);
-                spawn_icon(
-                    parent,
-                    fonts.tier_numbers.clone(),
-                    textures.hearth_icon.clone(),
-                    position,
-                    hp_tier,
-                );
+            match node.kind {
+                NodeKind::Fight | NodeKind::Elite | NodeKind::Boss => {
+                    spawn_fight_node(
+                        &mut commands,
+                        &mut planning_rng.0,
+                        &mut reward_rng.0,
+                        &asset_server,
+                        &textures,
+                        &fonts,
+                        battle_count.0,
+                        &balance,
+                        &dungeon_map,
+                        node_entity,
+                        index,
+                    );
+                }
+                NodeKind::Treasure => {
+                    let label = node_label(node.kind, &balance, dungeon_map.act);
+                    let items: Vec<_> = (0..balance.dungeon.treasure_item_count)
+                        .map(|_| roll_item(&mut reward_rng.0, battle_count.0, &balance, 0))
+                        .collect();
+                    commands.entity(node_entity).insert(DropRewards {
+                        items: items.clone(),
+                        gear: None,
+                    });
 
-                // hp regeneration icon
-                let position = Vec3::new(right_x, second_row_y, 1.);
-                spawn_icon(
-                    parent,
-                    fonts.tier_numbers.clone(),
-                    textures.hp_regeneration_icon.clone(),
-                    position,
-                    hp_regeneration_tier,
-                );
+                    spawn_node_preview(&mut commands, &textures, &fonts, index, label, |card| {
+                        for (index, item) in items.iter().enumerate() {
+                            let icon = item.item_type.icon(&textures);
+                            spawn_reward_card(
+                                card,
+                                &fonts,
+                                &textures,
+                                index,
+                                icon,
+                                item.tier,
+                                Color::YELLOW,
+                            );
+                        }
+                    });
+                }
+                NodeKind::Shop => {
+                    let label = node_label(node.kind, &balance, dungeon_map.act);
+                    let items: Vec<_> = (0..balance.shop.stock_size)
+                        .map(|_| roll_item(&mut planning_rng.0, battle_count.0, &balance, 0))
+                        .collect();
+                    commands
+                        .entity(node_entity)
+                        .insert(ShopStock(items.clone()));
 
-                // reward cards
-                for (index, item) in rewards.0.iter().enumerate() {
-                    spawn_reward_card(parent, &fonts, &textures, index, item);
+                    spawn_node_preview(&mut commands, &textures, &fonts, index, label, |card| {
+                        for (index, item) in items.iter().enumerate() {
+                            let icon = item.item_type.icon(&textures);
+                            spawn_reward_card(
+                                card,
+                                &fonts,
+                                &textures,
+                                index,
+                                icon,
+                                item.tier,
+                                Color::YELLOW,
+                            );
+                        }
+                    });
                 }
-            });
+                NodeKind::Rest => {
+                    let label = node_label(node.kind, &balance, dungeon_map.act);
+                    spawn_node_preview(&mut commands, &textures, &fonts, index, label, |_| {});
+                }
+            }
+        }
+    }
+}
+
+fn handle_node_hover(
+    node_query: Query<(&Clickable, &MapNodeButton)>,
+    mut preview_query: Query<(&NodePreview, &mut Visibility)>,
+) {
+    for (clickable, &MapNodeButton(index)) in node_query.iter() {
+        if !clickable.hover_started && !clickable.hover_ended {
+            continue;
+        }
+
+        for (preview, mut visibility) in preview_query.iter_mut() {
+            if preview.0 == index {
+                *visibility = if clickable.current_hover {
+                    Visibility::Inherited
+                } else {
+                    Visibility::Hidden
+                };
+            }
+        }
     }
 }
 
-fn handle_enemy_selection(
+fn handle_node_selection(
     mut commands: Commands,
-    textures: Res<TextureAssets>,
+    mut dungeon_map: ResMut<DungeonMap>,
     mut next_screen: ResMut<NextState<GameScreen>>,
-    query: Query<(&Clickable, &Stats, &DropRewards), With<EnemyCard>>,
+    mut pending_rest: ResMut<PendingRest>,
+    node_query: Query<(
+        &Clickable,
+        &MapNodeButton,
+        Option<&EnemyCard>,
+        Option<&DropRewards>,
+        Option<&ShopStock>,
+    )>,
 ) {
-    for (clickable, stats, drop_rewards) in query.iter() {
+    for (clickable, &MapNodeButton(index), enemy_card, treasure, shop_stock) in node_query.iter() {
         if !clickable.just_left_clicked {
             continue;
         }
 
-        commands.spawn((
+        let kind = dungeon_map.floors[dungeon_map.current_floor()][index].kind;
+        dungeon_map.visit(index);
+
+        match kind {
+            NodeKind::Fight | NodeKind::Elite | NodeKind::Boss => {
+                let enemy_card = enemy_card.expect("Fight nodes should have their enemies rolled.");
+                for (stats, drop_rewards, sprite) in enemy_card.enemies.iter() {
+                    commands.spawn((
+                        SpriteBundle {
+                            texture: sprite.clone(),
+                            visibility: Visibility::Hidden,
+                            ..default()
+                        },
+                        stats.clone(),
+                        drop_rewards.clone(),
+                        Enemy,
+                    ));
+                }
+
+                next_screen.set(GameScreen::Battle);
+            }
+            NodeKind::Rest => {
+                pending_rest.0 = true;
+
+                next_screen.set(GameScreen::Summoning);
+            }
+            NodeKind::Treasure => {
+                let treasure = treasure.expect("Treasure nodes should have their items rolled.");
+                commands.insert_resource(PendingRewards {
+                    title: "TREASURE",
+                    items: treasure.items.clone(),
+                    ..Default::default()
+                });
+
+                next_screen.set(GameScreen::Rewards);
+            }
+            NodeKind::Shop => {
+                let shop_stock = shop_stock.expect("Shop nodes should have their stock rolled.");
+                commands.insert_resource(shop_stock.clone());
+
+                next_screen.set(GameScreen::Shop);
+            }
+        }
+
+        // only a single node can be visited
+        break;
+    }
+}
+
+/// Skipped fights count as won battles without rewards, every minion pays the cost in HP but
+/// survives it. Without minions nobody can pay, so fights can not be skipped.
+fn handle_fight_skip(
+    mut dungeon_map: ResMut<DungeonMap>,
+    mut battle_count: ResMut<BattleCount>,
+    mut next_screen: ResMut<NextState<GameScreen>>,
+    balance: Res<Balance>,
+    node_query: Query<(&Clickable, &MapNodeButton)>,
+    mut minion_query: Query<&mut Stats, With<Minion>>,
+) {
+    for (clickable, &MapNodeButton(index)) in node_query.iter() {
+        let kind = dungeon_map.floors[dungeon_map.current_floor()][index].kind;
+        if !clickable.just_right_clicked || !matches!(kind, NodeKind::Fight | NodeKind::Elite) {
+            continue;
+        }
+        // the cost is paid by minions, without them there is nothing to pay it with
+        if minion_query.is_empty() {
+            break;
+        }
+
+        dungeon_map.visit(index);
+        battle_count.0 += 1;
+        for mut stats in minion_query.iter_mut() {
+            let cost = stats.max_hp * balance.dungeon.skip_hp_cost;
+            stats.current_hp = (stats.current_hp - cost).max(1.);
+        }
+
+        next_screen.set(GameScreen::Summoning);
+        break;
+    }
+}
+
+fn reroll_label(rerolls_left: usize) -> String {
+    if rerolls_left == 0 {
+        "NO REROLLS LEFT".to_string()
+    } else {
+        format!("REROLL FIGHTS - {rerolls_left} LEFT")
+    }
+}
+
+fn spawn_reroll_button(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    fonts: Res<FontAssets>,
+    balance: Res<Balance>,
+    rerolls_used: Res<RerollsUsed>,
+) {
+    commands
+        .spawn((
             SpriteBundle {
-                texture: textures.enemy1.clone(),
-                visibility: Visibility::Hidden,
-                ..default()
+                texture: textures.square.clone(),
+                sprite: Sprite {
+                    color: Color::DARK_GRAY,
+                    custom_size: Some(REROLL_BUTTON_SIZE),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(REROLL_BUTTON_POS.extend(1.)),
+                ..Default::default()
             },
-            stats.clone(),
-            drop_rewards.clone(),
-            Enemy,
-        ));
+            Clickable::default(),
+            RerollButton,
+            PlanningScreenEntity,
+        ))
+        .with_children(|button| {
+            button.spawn(Text2dBundle {
+                text: Text::from_section(
+                    reroll_label(rerolls_used.left(&balance.dungeon)),
+                    TextStyle {
+                        color: Color::WHITE,
+                        font: fonts.texts.clone(),
+                        font_size: REROLL_TEXT_SIZE,
+                    },
+                ),
+                transform: Transform::from_xyz(0., 0., 1.),
+                ..Default::default()
+            });
+        });
+}
+
+/// Fights and elites of the current floor are rolled again from the planning generator, the boss
+/// stays the same.
+#[allow(clippy::too_many_arguments)]
+fn handle_reroll(
+    mut commands: Commands,
+    mut rerolls_used: ResMut<RerollsUsed>,
+    mut planning_rng: ResMut<PlanningRng>,
+    mut reward_rng: ResMut<RewardRng>,
+    asset_server: Res<AssetServer>,
+    textures: Res<TextureAssets>,
+    fonts: Res<FontAssets>,
+    battle_count: Res<BattleCount>,
+    balance: Res<Balance>,
+    dungeon_map: Res<DungeonMap>,
+    button_query: Query<(&Clickable, &Children), With<RerollButton>>,
+    mut text_query: Query<&mut Text>,
+    node_query: Query<(Entity, &Clickable, &MapNodeButton), With<EnemyCard>>,
+    preview_query: Query<(Entity, &NodePreview)>,
+) {
+    let Ok((clickable, children)) = button_query.get_single() else {
+        return;
+    };
+    if !clickable.just_left_clicked || !rerolls_used.use_reroll(&balance.dungeon) {
+        return;
+    }
 
-        next_screen.set(GameScreen::Battle);
+    for &child in children.iter() {
+        if let Ok(mut text) = text_query.get_mut(child) {
+            text.sections[0].value = reroll_label(rerolls_used.left(&balance.dungeon));
+        }
+    }
+
+    let floor = &dungeon_map.floors[dungeon_map.current_floor()];
+    for (node_entity, node_clickable, &MapNodeButton(index)) in node_query.iter() {
+        if floor[index].kind == NodeKind::Boss {
+            continue;
+        }
+
+        for (preview_entity, preview) in preview_query.iter() {
+            if preview.0 == index {
+                commands.entity(preview_entity).despawn_recursive();
+            }
+        }
+        let preview_entity = spawn_fight_node(
+            &mut commands,
+            &mut planning_rng.0,
+            &mut reward_rng.0,
+            &asset_server,
+            &textures,
+            &fonts,
+            battle_count.0,
+            &balance,
+            &dungeon_map,
+            node_entity,
+            index,
+        );
+
+        // card of a hovered node stays shown, hover is only handled when it starts or ends
+        if node_clickable.current_hover {
+            commands
+                .entity(preview_entity)
+                .insert(Visibility::Inherited);
+        }
+    }
+}
+
+/// Estimates start over whenever enemies of a card are rolled, each batch of simulations gets its
+/// own seed.
+fn start_win_estimates(
+    mut commands: Commands,
+    run_seed: Res<RunSeed>,
+    balance: Res<Balance>,
+    dungeon_map: Res<DungeonMap>,
+    card_query: Query<(Entity, &EnemyCard), Changed<EnemyCard>>,
+    minion_query: Query<(&Stats, &FormationSlot), With<Minion>>,
+) {
+    let task_pool = AsyncComputeTaskPool::get();
+
+    for (entity, enemy_card) in card_query.iter() {
+        let (minions, rows) = lineup(
+            minion_query
+                .iter()
+                .map(|(stats, &slot)| (slot, stats.clone()))
+                .collect(),
+        );
+        let enemies: Vec<_> = enemy_card
+            .enemies
+            .iter()
+            .map(|(stats, _, _)| stats.clone())
+            .collect();
+        let formation = balance.formation.formation(rows);
+        let rules = balance.battle_rules.rules();
+        let mut rng = run_seed.rng(RngStream::Estimates, dungeon_map.steps);
+
+        let batches = (0..ESTIMATE_SIMULATION_COUNT / ESTIMATE_BATCH_SIZE)
+            .map(|_| {
+                let (minions, enemies, formation, rules) = (
+                    minions.clone(),
+                    enemies.clone(),
+                    formation.clone(),
+                    rules.clone(),
+                );
+                let seed: u64 = rng.gen();
+                task_pool.spawn(async move {
+                    let mut rng = StdRng::seed_from_u64(seed);
+                    let (mut wins, mut minions_lost) = (0, 0);
+                    for _ in 0..ESTIMATE_BATCH_SIZE {
+                        let result =
+                            estimate_battle(&minions, &enemies, &formation, &rules, rng.gen());
+                        if result.outcome == BattleOutcome::Victory {
+                            wins += 1;
+                        }
+                        minions_lost += result.minions_hp.iter().filter(|&&hp| hp <= 0.).count();
+                    }
+                    (wins, minions_lost)
+                })
+            })
+            .collect();
+
+        // dropping estimate of the previous enemies cancels its batches
+        commands.entity(entity).insert(WinEstimate {
+            batches,
+            simulations: 0,
+            wins: 0,
+            minions_lost: 0,
+        });
+    }
+}
+
+fn difficulty_color(win_chance: f32) -> Color {
+    if win_chance >= 0.8 {
+        Color::GREEN
+    } else if win_chance >= 0.5 {
+        Color::YELLOW
+    } else if win_chance >= 0.2 {
+        Color::ORANGE
+    } else {
+        Color::RED
+    }
+}
+
+/// Every card collects batches of its estimate as they finish, the card shows the results so far.
+fn run_win_estimates(
+    mut estimate_query: Query<(&MapNodeButton, &mut WinEstimate)>,
+    mut text_query: Query<(&mut Text, &EstimateText)>,
+    mut border_query: Query<(&mut Sprite, &CardBorder)>,
+) {
+    for (&MapNodeButton(index), mut estimate) in estimate_query.iter_mut() {
+        if estimate.batches.is_empty() {
+            continue;
+        }
+
+        let mut finished = Vec::new();
+        estimate
+            .batches
+            .retain_mut(|batch| match block_on(future::poll_once(batch)) {
+                Some(counts) => {
+                    finished.push(counts);
+                    false
+                }
+                None => true,
+            });
+        if finished.is_empty() {
+            continue;
+        }
+        for (wins, minions_lost) in finished {
+            estimate.simulations += ESTIMATE_BATCH_SIZE;
+            estimate.wins += wins;
+            estimate.minions_lost += minions_lost;
+        }
+
+        let win_chance = estimate.wins as f32 / estimate.simulations as f32;
+        let minions_lost = estimate.minions_lost as f32 / estimate.simulations as f32;
+        for (mut text, EstimateText(text_index)) in text_query.iter_mut() {
+            if *text_index == index {
+                text.sections[0].value =
+                    format!("WIN {:.0}%  LOSSES {:.1}", win_chance * 100., minions_lost);
+            }
+        }
+        for (mut sprite, CardBorder(border_index)) in border_query.iter_mut() {
+            if *border_index == index {
+                sprite.color = difficulty_color(win_chance);
+            }
+        }
     }
 }
 
@@ -380,3 +1452,100 @@ fn clean_planning_screen(mut commands: Commands, query: Query<Entity, With<Plann
         commands.entity(entity).despawn_recursive();
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use bevy::ecs::system::RunSystemOnce;
+
+    use super::*;
+    use crate::dungeon_map::MapNode;
+
+    fn balance() -> Balance {
+        ron::from_str(include_str!("../assets/balance.ron")).unwrap()
+    }
+
+    /// World after the right click on the first node of a map with a single fight.
+    fn skip_fight(minions: &[Stats]) -> World {
+        let mut world = World::new();
+        world.insert_resource(balance());
+        world.insert_resource(DungeonMap {
+            act: 1,
+            floors: vec![vec![MapNode {
+                kind: NodeKind::Fight,
+                next: Vec::new(),
+            }]],
+            ..Default::default()
+        });
+        world.init_resource::<BattleCount>();
+        world.init_resource::<NextState<GameScreen>>();
+        world.spawn((
+            Clickable {
+                just_right_clicked: true,
+                ..Default::default()
+            },
+            MapNodeButton(0),
+        ));
+        for stats in minions.iter() {
+            world.spawn((Minion, stats.clone()));
+        }
+
+        world.run_system_once(handle_fight_skip);
+        world
+    }
+
+    fn hps(world: &mut World) -> Vec<f32> {
+        let mut hps: Vec<_> = world
+            .query_filtered::<&Stats, With<Minion>>()
+            .iter(world)
+            .map(|stats| stats.current_hp)
+            .collect();
+        hps.sort_by(f32::total_cmp);
+        hps
+    }
+
+    #[test]
+    fn rerolls_are_limited_by_the_balance() {
+        let balance = balance();
+        let mut rerolls_used = RerollsUsed::default();
+
+        for left in (0..balance.dungeon.rerolls).rev() {
+            assert!(rerolls_used.use_reroll(&balance.dungeon));
+            assert_eq!(rerolls_used.left(&balance.dungeon), left);
+        }
+        assert!(!rerolls_used.use_reroll(&balance.dungeon));
+        assert_eq!(rerolls_used.0, balance.dungeon.rerolls);
+        assert_eq!(reroll_label(0), "NO REROLLS LEFT");
+    }
+
+    #[test]
+    fn skipping_costs_hp_but_minions_survive() {
+        let cost = balance().dungeon.skip_hp_cost;
+        let healthy = Stats {
+            current_hp: 100.,
+            max_hp: 100.,
+            ..Default::default()
+        };
+        let wounded = Stats {
+            current_hp: 100. * cost / 2.,
+            ..healthy.clone()
+        };
+        let mut world = skip_fight(&[healthy, wounded]);
+
+        assert_eq!(hps(&mut world), vec![1., 100. * (1. - cost)]);
+        assert_eq!(world.resource::<BattleCount>().0, 1);
+        assert_eq!(world.resource::<DungeonMap>().route, vec![0]);
+        assert!(matches!(
+            world.resource::<NextState<GameScreen>>().0,
+            Some(GameScreen::Summoning)
+        ));
+    }
+
+    #[test]
+    fn fights_can_not_be_skipped_without_minions() {
+        let world = skip_fight(&[]);
+
+        assert_eq!(world.resource::<BattleCount>().0, 0);
+        assert!(world.resource::<DungeonMap>().route.is_empty());
+        assert!(world.resource::<NextState<GameScreen>>().0.is_none());
+    }
+}
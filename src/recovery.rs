@@ -0,0 +1,180 @@
+use bevy::prelude::*;
+
+use crate::{
+    balance::Balance,
+    codex::CodexState,
+    experience::LevelUpChoice,
+    health_bar::HealthBar,
+    loading::{FontAssets, TextureAssets},
+    minions::Minion,
+    mouse_control::Clickable,
+    sacrifice::SacrificeConfirmation,
+    stats::Stats,
+    GameScreen, GameState,
+};
+
+/// Rest button sits in the top right corner, the bottom of the screen belongs to the formation.
+const REST_BUTTON_POS: Vec3 = Vec3::new(1920. / 2. - 200., 1080. / 2. - 34., 0.);
+const REST_BUTTON_SIZE: Vec2 = Vec2::new(336., 56.);
+const HP_LABEL_SIZE: f32 = 24.;
+
+pub struct RecoveryPlugin;
+
+/// This plugin lets wounded minions recover on the summoning screen, where their HP is shown next
+/// to their health bars. Minions also recover a part of their HP after every won battle.
+impl Plugin for RecoveryPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingRest>()
+            .add_systems(OnEnter(GameScreen::Summoning), spawn_rest_button)
+            .add_systems(OnExit(GameScreen::Summoning), clean_recovery)
+            .add_systems(
+                Update,
+                (
+                    update_hp_labels,
+                    handle_rest.run_if(
+                        in_state(CodexState::Closed)
+                            .and_then(not(resource_exists::<LevelUpChoice>))
+                            .and_then(not(resource_exists::<SacrificeConfirmation>)),
+                    ),
+                )
+                    .run_if(in_state(GameState::Playing).and_then(in_state(GameScreen::Summoning))),
+            );
+    }
+}
+
+/// Set by a visited rest site, the rest can be taken on the following summoning screen.
+#[derive(Resource, Default)]
+pub struct PendingRest(pub bool);
+
+#[derive(Component)]
+struct RestButton;
+
+#[derive(Component)]
+struct HpLabel;
+
+fn spawn_rest_button(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    fonts: Res<FontAssets>,
+    balance: Res<Balance>,
+    pending_rest: Res<PendingRest>,
+) {
+    if !pending_rest.0 {
+        return;
+    }
+
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: textures.square.clone(),
+                sprite: Sprite {
+                    custom_size: Some(REST_BUTTON_SIZE),
+                    color: Color::DARK_GREEN,
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(REST_BUTTON_POS),
+                ..Default::default()
+            },
+            Clickable::default(),
+            RestButton,
+        ))
+        .with_children(|parent| {
+            parent.spawn(Text2dBundle {
+                text: Text::from_section(
+                    format!(
+                        "REST - HEAL {}%",
+                        (balance.dungeon.rest_heal * 100.).round()
+                    ),
+                    TextStyle {
+                        font: fonts.texts.clone(),
+                        font_size: 36.,
+                        color: Color::WHITE,
+                    },
+                )
+                .with_justify(JustifyText::Center),
+                transform: Transform::from_xyz(0., 0., 1.),
+                ..Default::default()
+            });
+        });
+}
+
+/// Rest heals every minion, minions summoned before resting benefit as well.
+fn handle_rest(
+    mut commands: Commands,
+    balance: Res<Balance>,
+    mut pending_rest: ResMut<PendingRest>,
+    button_query: Query<(Entity, &Clickable), With<RestButton>>,
+    mut minion_query: Query<&mut Stats, With<Minion>>,
+) {
+    for (entity, clickable) in button_query.iter() {
+        if !clickable.just_left_clicked {
+            continue;
+        }
+
+        for mut stats in minion_query.iter_mut() {
+            stats.heal(balance.dungeon.rest_heal);
+        }
+
+        pending_rest.0 = false;
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+/// Current and maximum HP of minions are written above their health bars.
+fn update_hp_labels(
+    mut commands: Commands,
+    fonts: Res<FontAssets>,
+    minion_query: Query<(Entity, &Stats, &HealthBar, Option<&Children>), With<Minion>>,
+    mut label_query: Query<&mut Text, With<HpLabel>>,
+) {
+    for (entity, stats, health_bar, children) in minion_query.iter() {
+        let value = format!("{:.0}/{:.0}", stats.current_hp.ceil(), stats.max_hp);
+        let label = children
+            .into_iter()
+            .flatten()
+            .find(|&&child| label_query.contains(child));
+
+        if let Some(&label) = label {
+            let mut text = label_query.get_mut(label).unwrap();
+            if text.sections[0].value != value {
+                text.sections[0].value = value;
+            }
+            continue;
+        }
+
+        commands.entity(entity).with_children(|parent| {
+            parent.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        value,
+                        TextStyle {
+                            font: fonts.texts.clone(),
+                            font_size: HP_LABEL_SIZE,
+                            color: Color::WHITE,
+                        },
+                    ),
+                    text_anchor: bevy::sprite::Anchor::BottomCenter,
+                    transform: Transform::from_xyz(
+                        health_bar.offset.x,
+                        health_bar.offset.y + health_bar.height / 2. + 4.,
+                        1.,
+                    ),
+                    ..Default::default()
+                },
+                HpLabel,
+            ));
+        });
+    }
+}
+
+/// Rest which was not taken is lost once the summoning screen is left.
+fn clean_recovery(
+    mut commands: Commands,
+    mut pending_rest: ResMut<PendingRest>,
+    query: Query<Entity, Or<(With<RestButton>, With<HpLabel>)>>,
+) {
+    pending_rest.0 = false;
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
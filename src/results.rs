@@ -0,0 +1,387 @@
+use bevy::prelude::*;
+
+use crate::{
+    battle::report::{BattleReport, MinionReport},
+    loading::{FontAssets, TextureAssets},
+    mouse_control::Clickable,
+    rewards::PendingRewards,
+    stats::Row,
+    summoning::reposition_minions,
+    GameScreen, GameState,
+};
+
+const SUMMARY_Y: f32 = 1080. / 2. - 220.;
+const TABLE_HEADER_Y: f32 = 1080. / 2. - 320.;
+const FIRST_ROW_Y: f32 = TABLE_HEADER_Y - 72.;
+const ROW_SPACING: f32 = 64.;
+/// Minion column first, then damage dealt, damage taken, regenerated HP and the fate of the minion.
+const COLUMN_XS: [f32; 5] = [-600., -240., 40., 320., 600.];
+const MINION_ICON_SIZE: f32 = 56.;
+const TABLE_FONT_SIZE: f32 = 44.;
+const CONTINUE_BUTTON_POS: Vec3 = Vec3::new(0., -300., 0.);
+
+pub struct ResultsPlugin;
+
+/// This plugin shows the results of the battle with its combat breakdown, winning continues to
+/// the rewards screen and losing ends the run.
+impl Plugin for ResultsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            OnEnter(GameScreen::Results),
+            (spawn_results_screen, reposition_minions),
+        )
+        .add_systems(OnExit(GameScreen::Results), clean_results_screen)
+        .add_systems(
+            Update,
+            handle_continue
+                .run_if(in_state(GameState::Playing).and_then(in_state(GameScreen::Results))),
+        );
+    }
+}
+
+#[derive(Component)]
+struct ContinueButton;
+
+#[derive(Component)]
+struct ResultsScreenEntity;
+
+fn format_seconds(seconds: f32) -> String {
+    format!("{:.1}S", seconds)
+}
+
+/// Time to kill and lost minions, followed by kill times of single enemies, the gold gained and
+/// the drops still to be picked on the rewards screen.
+fn summary_text(report: &BattleReport, rewards: &PendingRewards) -> String {
+    let mut lines = Vec::new();
+
+    let lost = report.lost_count();
+    if report.is_victory() {
+        lines.push(format!(
+            "TIME TO KILL {}  MINIONS LOST {}",
+            format_seconds(report.kill_times.last().copied().unwrap_or_default()),
+            lost
+        ));
+        lines.push(format!(
+            "ENEMIES KILLED AT {}",
+            report
+                .kill_times
+                .iter()
+                .map(|&time| format_seconds(time))
+                .collect::<Vec<_>>()
+                .join(", ")
+        ));
+        lines.push(format!(
+            "{} GOLD GAINED  DROPS TO PICK {} ITEMS, {} GEAR",
+            rewards.gold,
+            rewards.items.len(),
+            rewards.gear.len()
+        ));
+    } else {
+        lines.push(format!(
+            "DEFEATED AFTER {}  MINIONS LOST {}",
+            format_seconds(report.duration),
+            lost
+        ));
+        lines.push(format!(
+            "ENEMIES KILLED {}/{}",
+            report.kill_times.len(),
+            report.enemy_count
+        ));
+    }
+
+    lines.join("\n")
+}
+
+fn spawn_table_text(
+    parent: &mut ChildBuilder,
+    fonts: &FontAssets,
+    value: String,
+    color: Color,
+    position: Vec3,
+) {
+    parent.spawn(Text2dBundle {
+        text: Text::from_section(
+            value,
+            TextStyle {
+                color,
+                font: fonts.texts.clone(),
+                font_size: TABLE_FONT_SIZE,
+            },
+        ),
+        transform: Transform::from_translation(position),
+        ..Default::default()
+    });
+}
+
+/// Row of the table with the breakdown of a single minion.
+fn spawn_minion_row(
+    parent: &mut ChildBuilder,
+    textures: &TextureAssets,
+    fonts: &FontAssets,
+    minion: &MinionReport,
+    y: f32,
+) {
+    let row = match minion.slot.row {
+        Row::Front => "FRONT",
+        Row::Back => "BACK",
+    };
+    let (fate, fate_color) = if minion.lost {
+        ("LOST", Color::RED)
+    } else {
+        ("ALIVE", Color::GREEN)
+    };
+
+    parent.spawn(SpriteBundle {
+        texture: textures.minion.clone(),
+        sprite: Sprite {
+            color: if minion.lost {
+                Color::DARK_GRAY
+            } else {
+                Color::WHITE
+            },
+            custom_size: Some(Vec2::splat(MINION_ICON_SIZE)),
+            anchor: bevy::sprite::Anchor::CenterRight,
+            ..Default::default()
+        },
+        transform: Transform::from_xyz(COLUMN_XS[0] - 72., y, 0.),
+        ..Default::default()
+    });
+
+    let values = [
+        (format!("{} {}", row, minion.slot.index + 1), Color::WHITE),
+        (format!("{:.0}", minion.damage_dealt), Color::ORANGE_RED),
+        (format!("{:.0}", minion.damage_taken), Color::RED),
+        (format!("{:.0}", minion.regenerated), Color::GREEN),
+        (fate.to_string(), fate_color),
+    ];
+    for ((value, color), x) in values.into_iter().zip(COLUMN_XS) {
+        spawn_table_text(parent, fonts, value, color, Vec3::new(x, y, 0.));
+    }
+}
+
+fn spawn_results_screen(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    fonts: Res<FontAssets>,
+    report: Res<BattleReport>,
+    rewards: Res<PendingRewards>,
+) {
+    // background
+    commands.spawn((
+        SpriteBundle {
+            texture: textures.dungeon_floor_background.clone(),
+            transform: Transform::from_xyz(0., 0., -2.),
+            ..Default::default()
+        },
+        ResultsScreenEntity,
+    ));
+    commands.spawn((
+        SpriteBundle {
+            texture: textures.square.clone(),
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(1920., 1080.)),
+                color: Color::BLACK.with_a(0.7),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0., 0., -1.),
+            ..Default::default()
+        },
+        ResultsScreenEntity,
+    ));
+
+    let (title, title_color) = if report.is_victory() {
+        ("VICTORY", Color::GOLD)
+    } else {
+        ("DEFEAT", Color::RED)
+    };
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                title,
+                TextStyle {
+                    color: title_color,
+                    font: fonts.texts.clone(),
+                    font_size: 96.,
+                },
+            ),
+            transform: Transform::from_xyz(0., 1080. / 2. - 96., 0.),
+            ..Default::default()
+        },
+        ResultsScreenEntity,
+    ));
+
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                summary_text(&report, &rewards),
+                TextStyle {
+                    color: Color::WHITE,
+                    font: fonts.texts.clone(),
+                    font_size: 40.,
+                },
+            )
+            .with_justify(JustifyText::Center),
+            transform: Transform::from_xyz(0., SUMMARY_Y, 0.),
+            ..Default::default()
+        },
+        ResultsScreenEntity,
+    ));
+
+    commands
+        .spawn((SpatialBundle::default(), ResultsScreenEntity))
+        .with_children(|parent| {
+            let headers = ["MINION", "DEALT", "TAKEN", "REGEN", ""];
+            for (header, x) in headers.into_iter().zip(COLUMN_XS) {
+                spawn_table_text(
+                    parent,
+                    &fonts,
+                    header.to_string(),
+                    Color::GRAY,
+                    Vec3::new(x, TABLE_HEADER_Y, 0.),
+                );
+            }
+
+            for (index, minion) in report.minions.iter().enumerate() {
+                let y = FIRST_ROW_Y - ROW_SPACING * index as f32;
+                spawn_minion_row(parent, &textures, &fonts, minion, y);
+            }
+        });
+
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: textures.square.clone(),
+                sprite: Sprite {
+                    custom_size: Some(Vec2::new(384., 96.)),
+                    color: Color::DARK_GRAY,
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(CONTINUE_BUTTON_POS),
+                ..Default::default()
+            },
+            Clickable::default(),
+            ContinueButton,
+            ResultsScreenEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn(Text2dBundle {
+                text: Text::from_section(
+                    "Continue",
+                    TextStyle {
+                        font: fonts.texts.clone(),
+                        font_size: 80.,
+                        color: Color::WHITE,
+                    },
+                ),
+                transform: Transform::from_xyz(0., 0., 1.),
+                ..Default::default()
+            });
+        });
+}
+
+fn clean_results_screen(mut commands: Commands, query: Query<Entity, With<ResultsScreenEntity>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn handle_continue(
+    mut next_screen: ResMut<NextState<GameScreen>>,
+    mut next_state: ResMut<NextState<GameState>>,
+    report: Res<BattleReport>,
+    button_query: Query<&Clickable, With<ContinueButton>>,
+) {
+    if !button_query.single().just_left_clicked {
+        return;
+    }
+
+    if report.is_victory() {
+        next_screen.set(GameScreen::Rewards);
+    } else {
+        next_screen.set(GameScreen::Other);
+        next_state.set(GameState::GameOver);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{
+        battle::simulation::BattleOutcome,
+        formation::FormationSlot,
+        gear::{Gear, GearKind},
+        summoning::{SummoningItem, SummoningItemType},
+    };
+
+    fn report(outcome: BattleOutcome, kill_times: Vec<f32>) -> BattleReport {
+        let mut report = BattleReport::default();
+        report.outcome = outcome;
+        report.enemy_count = 3;
+        report.kill_times = kill_times;
+        report.duration = 12.;
+        report.minions = [false, true]
+            .into_iter()
+            .enumerate()
+            .map(|(index, lost)| MinionReport {
+                entity: Entity::PLACEHOLDER,
+                slot: FormationSlot {
+                    row: Row::Front,
+                    index,
+                },
+                damage_dealt: 0.,
+                damage_taken: 0.,
+                regenerated: 0.,
+                lost,
+            })
+            .collect();
+        report
+    }
+
+    #[test]
+    fn victory_summary_shows_drops_to_pick() {
+        let rewards = PendingRewards {
+            title: "VICTORY",
+            items: vec![SummoningItem {
+                item_type: SummoningItemType::Damage,
+                tier: 1,
+                quantity: 1,
+            }],
+            gear: vec![
+                Gear {
+                    kind: GearKind::Axe,
+                    tier: 1,
+                },
+                Gear {
+                    kind: GearKind::Cloak,
+                    tier: 2,
+                },
+            ],
+            gold: 40,
+        };
+
+        let text = summary_text(
+            &report(BattleOutcome::Victory, vec![2., 5.5, 9.25]),
+            &rewards,
+        );
+
+        assert_eq!(
+            text,
+            "TIME TO KILL 9.2S  MINIONS LOST 1\n\
+             ENEMIES KILLED AT 2.0S, 5.5S, 9.2S\n\
+             40 GOLD GAINED  DROPS TO PICK 1 ITEMS, 2 GEAR"
+        );
+    }
+
+    #[test]
+    fn defeat_summary_shows_killed_enemies() {
+        let text = summary_text(
+            &report(BattleOutcome::Defeat, vec![4.]),
+            &PendingRewards::default(),
+        );
+
+        assert_eq!(
+            text,
+            "DEFEATED AFTER 12.0S  MINIONS LOST 1\nENEMIES KILLED 1/3"
+        );
+    }
+}
@@ -0,0 +1,777 @@
+use bevy::prelude::*;
+
+use crate::{
+    gear::{Gear, GearInventory, GEAR_COLOR, MAX_GEAR_COUNT},
+    loading::{FontAssets, TextureAssets},
+    mouse_control::{update_clickables, Clickable},
+    summoning::{reposition_minions, InventoryItems, SummoningItem, MAX_ITEM_COUNT},
+    utils::num_to_roman,
+    GameScreen, GameState,
+};
+
+const DROPS_X: f32 = -600.;
+const INVENTORY_X: f32 = 0.;
+const GEAR_RACK_X: f32 = 600.;
+const COLUMN_TITLE_Y: f32 = 1080. / 2. - 220.;
+const FIRST_CARD_Y: f32 = COLUMN_TITLE_Y - 72.;
+const CHOICE_CARD_SIZE: Vec2 = Vec2::new(440., 52.);
+const CHOICE_CARD_SPACING: f32 = 58.;
+const SUMMARY_Y: f32 = -40.;
+const CONTINUE_BUTTON_POS: Vec3 = Vec3::new(DROPS_X, -300., 0.);
+const CONTINUE_BUTTON_SIZE: Vec2 = Vec2::new(384., 96.);
+
+pub struct RewardsPlugin;
+
+/// This plugin handles the rewards screen, where the player picks which of the dropped items and
+/// gear to take and makes room for them by discarding items or gear they already have.
+impl Plugin for RewardsPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<PendingRewards>()
+            .add_systems(
+                OnEnter(GameScreen::Rewards),
+                (spawn_rewards_screen, reposition_minions),
+            )
+            .add_systems(OnExit(GameScreen::Rewards), clean_rewards_screen)
+            .add_systems(
+                Update,
+                (handle_choice_click, handle_continue)
+                    .chain()
+                    .run_if(in_state(GameState::Playing).and_then(in_state(GameScreen::Rewards))),
+            )
+            .add_systems(
+                PreUpdate,
+                spawn_choice_cards.after(update_clickables).run_if(
+                    in_state(GameState::Playing)
+                        .and_then(in_state(GameScreen::Rewards))
+                        .and_then(resource_exists::<RewardChoice>),
+                ),
+            );
+    }
+}
+
+/// Rewards waiting to be picked on the rewards screen, gold is already added and only shown.
+#[derive(Resource, Default)]
+pub struct PendingRewards {
+    pub title: &'static str,
+    pub items: Vec<SummoningItem>,
+    pub gear: Vec<Gear>,
+    pub gold: usize,
+}
+
+/// Drops the player takes and items or gear they discard to make room for them.
+#[derive(Resource)]
+struct RewardChoice {
+    taken_items: Vec<bool>,
+    taken_gear: Vec<bool>,
+    discarded_items: Vec<bool>,
+    discarded_gear: Vec<bool>,
+    warning: Option<&'static str>,
+}
+
+impl RewardChoice {
+    /// Take every drop which fits, in the order they dropped.
+    fn new(
+        rewards: &PendingRewards,
+        inventory_items: &InventoryItems,
+        gear_inventory: &GearInventory,
+    ) -> Self {
+        let mut choice = Self {
+            taken_items: vec![false; rewards.items.len()],
+            taken_gear: vec![false; rewards.gear.len()],
+            discarded_items: vec![false; inventory_items.0.len()],
+            discarded_gear: vec![false; gear_inventory.0.len()],
+            warning: None,
+        };
+
+        for index in 0..rewards.items.len() {
+            choice.taken_items[index] = true;
+            if choice.chosen_items(rewards, inventory_items).is_none() {
+                choice.taken_items[index] = false;
+            }
+        }
+        for index in 0..rewards.gear.len() {
+            choice.taken_gear[index] = true;
+            if choice.chosen_gear(rewards, gear_inventory).is_none() {
+                choice.taken_gear[index] = false;
+            }
+        }
+
+        choice
+    }
+
+    fn toggle(&mut self, slot: ChoiceSlot) {
+        let flag = match slot {
+            ChoiceSlot::DroppedItem(index) => &mut self.taken_items[index],
+            ChoiceSlot::DroppedGear(index) => &mut self.taken_gear[index],
+            ChoiceSlot::InventoryItem(index) => &mut self.discarded_items[index],
+            ChoiceSlot::RackGear(index) => &mut self.discarded_gear[index],
+        };
+        *flag = !*flag;
+    }
+
+    /// Toggle the slot unless the taken drops would not fit anymore, in which case the warning
+    /// tells why. Returns false if the toggle was rejected.
+    fn try_toggle(
+        &mut self,
+        slot: ChoiceSlot,
+        rewards: &PendingRewards,
+        inventory_items: &InventoryItems,
+        gear_inventory: &GearInventory,
+    ) -> bool {
+        self.toggle(slot);
+
+        self.warning = match slot {
+            ChoiceSlot::DroppedItem(_) | ChoiceSlot::InventoryItem(_)
+                if self.chosen_items(rewards, inventory_items).is_none() =>
+            {
+                Some("INVENTORY IS FULL, DISCARD AN ITEM OR LEAVE A DROP")
+            }
+            ChoiceSlot::DroppedGear(_) | ChoiceSlot::RackGear(_)
+                if self.chosen_gear(rewards, gear_inventory).is_none() =>
+            {
+                Some("GEAR RACK IS FULL, DISCARD GEAR OR LEAVE A DROP")
+            }
+            _ => None,
+        };
+        if self.warning.is_some() {
+            self.toggle(slot);
+            return false;
+        }
+
+        true
+    }
+
+    /// Inventory after the choice is applied, none if the taken items do not fit.
+    fn chosen_items(
+        &self,
+        rewards: &PendingRewards,
+        inventory_items: &InventoryItems,
+    ) -> Option<InventoryItems> {
+        let mut items = InventoryItems(
+            inventory_items
+                .0
+                .iter()
+                .zip(&self.discarded_items)
+                .filter(|(_, &discarded)| !discarded)
+                .map(|(item, _)| item.clone())
+                .collect(),
+        );
+
+        rewards
+            .items
+            .iter()
+            .zip(&self.taken_items)
+            .filter(|(_, &taken)| taken)
+            .all(|(item, _)| items.add(item))
+            .then_some(items)
+    }
+
+    /// Gear rack after the choice is applied, none if the taken gear does not fit.
+    fn chosen_gear(
+        &self,
+        rewards: &PendingRewards,
+        gear_inventory: &GearInventory,
+    ) -> Option<GearInventory> {
+        let mut gear = GearInventory(
+            gear_inventory
+                .0
+                .iter()
+                .zip(&self.discarded_gear)
+                .filter(|(_, &discarded)| !discarded)
+                .map(|(gear, _)| gear.clone())
+                .collect(),
+        );
+
+        rewards
+            .gear
+            .iter()
+            .zip(&self.taken_gear)
+            .filter(|(_, &taken)| taken)
+            .all(|(new_gear, _)| gear.add(new_gear))
+            .then_some(gear)
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq)]
+enum ChoiceSlot {
+    DroppedItem(usize),
+    DroppedGear(usize),
+    InventoryItem(usize),
+    RackGear(usize),
+}
+
+#[derive(Component)]
+struct ChoiceCard(ChoiceSlot);
+
+#[derive(Component)]
+struct ChoiceList;
+
+#[derive(Component)]
+struct ContinueButton;
+
+#[derive(Component)]
+struct RewardsScreenEntity;
+
+fn spawn_rewards_screen(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    fonts: Res<FontAssets>,
+    rewards: Res<PendingRewards>,
+    inventory_items: Res<InventoryItems>,
+    gear_inventory: Res<GearInventory>,
+) {
+    commands.insert_resource(RewardChoice::new(
+        &rewards,
+        &inventory_items,
+        &gear_inventory,
+    ));
+
+    // background
+    commands.spawn((
+        SpriteBundle {
+            texture: textures.dungeon_floor_background.clone(),
+            transform: Transform::from_xyz(0., 0., -2.),
+            ..Default::default()
+        },
+        RewardsScreenEntity,
+    ));
+    commands.spawn((
+        SpriteBundle {
+            texture: textures.square.clone(),
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(1920., 1080.)),
+                color: Color::BLACK.with_a(0.7),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0., 0., -1.),
+            ..Default::default()
+        },
+        RewardsScreenEntity,
+    ));
+
+    commands.spawn((
+        Text2dBundle {
+            text: Text::from_section(
+                rewards.title,
+                TextStyle {
+                    color: Color::WHITE,
+                    font: fonts.texts.clone(),
+                    font_size: 96.,
+                },
+            ),
+            transform: Transform::from_xyz(0., 1080. / 2. - 96., 0.),
+            ..Default::default()
+        },
+        RewardsScreenEntity,
+    ));
+
+    commands.spawn((SpatialBundle::default(), ChoiceList, RewardsScreenEntity));
+
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: textures.square.clone(),
+                sprite: Sprite {
+                    custom_size: Some(CONTINUE_BUTTON_SIZE),
+                    color: Color::DARK_GRAY,
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(CONTINUE_BUTTON_POS),
+                ..Default::default()
+            },
+            Clickable::default(),
+            ContinueButton,
+            RewardsScreenEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn(Text2dBundle {
+                text: Text::from_section(
+                    "Continue",
+                    TextStyle {
+                        font: fonts.texts.clone(),
+                        font_size: 80.,
+                        color: Color::WHITE,
+                    },
+                ),
+                transform: Transform::from_xyz(0., 0., 1.),
+                ..Default::default()
+            });
+        });
+}
+
+fn clean_rewards_screen(mut commands: Commands, query: Query<Entity, With<RewardsScreenEntity>>) {
+    commands.remove_resource::<RewardChoice>();
+
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn spawn_column_title(parent: &mut ChildBuilder, fonts: &FontAssets, x: f32, title: String) {
+    parent.spawn(Text2dBundle {
+        text: Text::from_section(
+            title,
+            TextStyle {
+                color: Color::WHITE,
+                font: fonts.texts.clone(),
+                font_size: 56.,
+            },
+        ),
+        transform: Transform::from_xyz(x, COLUMN_TITLE_Y, 0.),
+        ..Default::default()
+    });
+}
+
+/// Card of a single dropped or owned item or gear, its color and label tell whether it is kept.
+#[allow(clippy::too_many_arguments)]
+fn spawn_choice_card(
+    parent: &mut ChildBuilder,
+    textures: &TextureAssets,
+    fonts: &FontAssets,
+    slot: ChoiceSlot,
+    position: Vec3,
+    icon: Handle<Image>,
+    icon_color: Color,
+    tier: u8,
+    quantity: Option<usize>,
+    is_kept: bool,
+) {
+    let (color, label) = match (slot, is_kept) {
+        (ChoiceSlot::DroppedItem(_) | ChoiceSlot::DroppedGear(_), true) => {
+            (Color::DARK_GREEN.with_a(0.9), "TAKE")
+        }
+        (ChoiceSlot::DroppedItem(_) | ChoiceSlot::DroppedGear(_), false) => {
+            (Color::DARK_GRAY.with_a(0.7), "LEAVE")
+        }
+        (_, true) => (Color::BLACK.with_a(0.95), "KEEP"),
+        (_, false) => (Color::MAROON.with_a(0.9), "DISCARD"),
+    };
+
+    parent
+        .spawn((
+            SpriteBundle {
+                texture: textures.square.clone(),
+                sprite: Sprite {
+                    color,
+                    custom_size: Some(CHOICE_CARD_SIZE),
+                    ..Default::default()
+                },
+                transform: Transform::from_translation(position),
+                ..Default::default()
+            },
+            ChoiceCard(slot),
+            Clickable::default(),
+        ))
+        .with_children(|card| {
+            if let Some(quantity) = quantity {
+                card.spawn(Text2dBundle {
+                    text: Text::from_section(
+                        quantity.to_string(),
+                        TextStyle {
+                            color: Color::WHITE,
+                            font: fonts.quantity_numbers.clone(),
+                            font_size: CHOICE_CARD_SIZE.y * 0.8,
+                        },
+                    ),
+                    text_anchor: bevy::sprite::Anchor::CenterLeft,
+                    transform: Transform::from_xyz(-CHOICE_CARD_SIZE.x / 2. + 16., 0., 1.),
+                    ..Default::default()
+                });
+            }
+
+            // tier number
+            card.spawn(Text2dBundle {
+                text: Text::from_section(
+                    num_to_roman(tier),
+                    TextStyle {
+                        color: Color::WHITE,
+                        font: fonts.tier_numbers.clone(),
+                        font_size: CHOICE_CARD_SIZE.y,
+                    },
+                ),
+                transform: Transform::from_xyz(-72., 0., 1.),
+                ..Default::default()
+            });
+
+            card.spawn(SpriteBundle {
+                texture: icon,
+                sprite: Sprite {
+                    color: icon_color,
+                    custom_size: Some(Vec2::splat(CHOICE_CARD_SIZE.y * 0.8)),
+                    anchor: bevy::sprite::Anchor::CenterLeft,
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(-32., 0., 1.),
+                ..Default::default()
+            });
+
+            card.spawn(Text2dBundle {
+                text: Text::from_section(
+                    label,
+                    TextStyle {
+                        color: Color::WHITE,
+                        font: fonts.texts.clone(),
+                        font_size: CHOICE_CARD_SIZE.y * 0.6,
+                    },
+                ),
+                text_anchor: bevy::sprite::Anchor::CenterRight,
+                transform: Transform::from_xyz(CHOICE_CARD_SIZE.x / 2. - 16., 0., 1.),
+                ..Default::default()
+            });
+        });
+}
+
+fn card_position(x: f32, index: usize) -> Vec3 {
+    Vec3::new(x, FIRST_CARD_Y - CHOICE_CARD_SPACING * index as f32, 1.)
+}
+
+/// What the player gains and gives up with the current choice, a warning explains the last click
+/// which could not be done.
+fn summary_text(choice: &RewardChoice, rewards: &PendingRewards, fonts: &FontAssets) -> Text {
+    let count = |flags: &[bool], value: bool| flags.iter().filter(|&&flag| flag == value).count();
+
+    let mut lines = Vec::new();
+    if rewards.gold > 0 {
+        lines.push(format!("+{} GOLD", rewards.gold));
+    }
+    lines.push(format!(
+        "+{} ITEMS, +{} GEAR",
+        count(&choice.taken_items, true),
+        count(&choice.taken_gear, true)
+    ));
+    let left_behind = count(&choice.taken_items, false) + count(&choice.taken_gear, false);
+    if left_behind > 0 {
+        lines.push(format!("{} DROPS LEFT BEHIND", left_behind));
+    }
+    let discarded = count(&choice.discarded_items, true) + count(&choice.discarded_gear, true);
+    if discarded > 0 {
+        lines.push(format!("{} DISCARDED", discarded));
+    }
+
+    let style = |color| TextStyle {
+        color,
+        font: fonts.texts.clone(),
+        font_size: 40.,
+    };
+    let mut sections = vec![TextSection::new(lines.join("\n"), style(Color::WHITE))];
+    if let Some(warning) = choice.warning {
+        sections.push(TextSection::new(
+            format!("\n{}", warning),
+            style(Color::RED),
+        ));
+    }
+
+    Text::from_sections(sections).with_justify(JustifyText::Center)
+}
+
+/// Recreate the cards of all columns and the summary whenever the choice changes.
+#[allow(clippy::too_many_arguments)]
+fn spawn_choice_cards(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    fonts: Res<FontAssets>,
+    choice: Res<RewardChoice>,
+    rewards: Res<PendingRewards>,
+    inventory_items: Res<InventoryItems>,
+    gear_inventory: Res<GearInventory>,
+    list_query: Query<(Entity, Option<&Children>), With<ChoiceList>>,
+) {
+    if !choice.is_changed() {
+        return;
+    }
+    let Ok((list_entity, children)) = list_query.get_single() else {
+        return;
+    };
+
+    if let Some(children) = children {
+        for &child in children.iter() {
+            commands.entity(child).despawn_recursive();
+        }
+        commands.entity(list_entity).clear_children();
+    }
+
+    let kept_items = choice
+        .chosen_items(&rewards, &inventory_items)
+        .map_or(0, |items| items.0.len());
+    let kept_gear = choice
+        .chosen_gear(&rewards, &gear_inventory)
+        .map_or(0, |gear| gear.0.len());
+
+    commands.entity(list_entity).with_children(|parent| {
+        spawn_column_title(parent, &fonts, DROPS_X, "DROPS".to_string());
+        spawn_column_title(
+            parent,
+            &fonts,
+            INVENTORY_X,
+            format!("INVENTORY {}/{}", kept_items, MAX_ITEM_COUNT),
+        );
+        spawn_column_title(
+            parent,
+            &fonts,
+            GEAR_RACK_X,
+            format!("GEAR RACK {}/{}", kept_gear, MAX_GEAR_COUNT),
+        );
+
+        for (index, item) in rewards.items.iter().enumerate() {
+            spawn_choice_card(
+                parent,
+                &textures,
+                &fonts,
+                ChoiceSlot::DroppedItem(index),
+                card_position(DROPS_X, index),
+                item.item_type.icon(&textures),
+                Color::CYAN,
+                item.tier,
+                Some(item.quantity),
+                choice.taken_items[index],
+            );
+        }
+        for (index, gear) in rewards.gear.iter().enumerate() {
+            spawn_choice_card(
+                parent,
+                &textures,
+                &fonts,
+                ChoiceSlot::DroppedGear(index),
+                card_position(DROPS_X, rewards.items.len() + index),
+                gear.kind.icon(&textures),
+                GEAR_COLOR,
+                gear.tier,
+                None,
+                choice.taken_gear[index],
+            );
+        }
+        for (index, item) in inventory_items.0.iter().enumerate() {
+            spawn_choice_card(
+                parent,
+                &textures,
+                &fonts,
+                ChoiceSlot::InventoryItem(index),
+                card_position(INVENTORY_X, index),
+                item.item_type.icon(&textures),
+                Color::CYAN,
+                item.tier,
+                Some(item.quantity),
+                !choice.discarded_items[index],
+            );
+        }
+        for (index, gear) in gear_inventory.0.iter().enumerate() {
+            spawn_choice_card(
+                parent,
+                &textures,
+                &fonts,
+                ChoiceSlot::RackGear(index),
+                card_position(GEAR_RACK_X, index),
+                gear.kind.icon(&textures),
+                GEAR_COLOR,
+                gear.tier,
+                None,
+                !choice.discarded_gear[index],
+            );
+        }
+
+        parent.spawn(Text2dBundle {
+            text: summary_text(&choice, &rewards, &fonts),
+            text_anchor: bevy::sprite::Anchor::TopCenter,
+            transform: Transform::from_xyz(DROPS_X, SUMMARY_Y, 0.),
+            ..Default::default()
+        });
+    });
+}
+
+/// Clicks toggle whether a drop is taken or an owned item or gear is discarded, a click which
+/// would not leave enough room for the taken drops is undone.
+fn handle_choice_click(
+    mut choice: ResMut<RewardChoice>,
+    rewards: Res<PendingRewards>,
+    inventory_items: Res<InventoryItems>,
+    gear_inventory: Res<GearInventory>,
+    query: Query<(&Clickable, &ChoiceCard)>,
+) {
+    for (clickable, &ChoiceCard(slot)) in query.iter() {
+        if !clickable.just_left_clicked {
+            continue;
+        }
+
+        choice.try_toggle(slot, &rewards, &inventory_items, &gear_inventory);
+
+        // cards are recreated, so only a single click is handled per frame
+        break;
+    }
+}
+
+fn handle_continue(
+    mut next_screen: ResMut<NextState<GameScreen>>,
+    mut rewards: ResMut<PendingRewards>,
+    mut inventory_items: ResMut<InventoryItems>,
+    mut gear_inventory: ResMut<GearInventory>,
+    choice: Res<RewardChoice>,
+    button_query: Query<&Clickable, With<ContinueButton>>,
+) {
+    if !button_query.single().just_left_clicked {
+        return;
+    }
+
+    let (Some(items), Some(gear)) = (
+        choice.chosen_items(&rewards, &inventory_items),
+        choice.chosen_gear(&rewards, &gear_inventory),
+    ) else {
+        return;
+    };
+    *inventory_items = items;
+    *gear_inventory = gear;
+    *rewards = PendingRewards::default();
+
+    next_screen.set(GameScreen::Summoning);
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{gear::GearKind, summoning::SummoningItemType};
+
+    fn item(tier: u8) -> SummoningItem {
+        SummoningItem {
+            item_type: SummoningItemType::Damage,
+            tier,
+            quantity: 1,
+        }
+    }
+
+    fn gear(tier: u8) -> Gear {
+        Gear {
+            kind: GearKind::Axe,
+            tier,
+        }
+    }
+
+    /// Inventory and rack filled with items and gear of tiers from one up.
+    fn full_inventories() -> (InventoryItems, GearInventory) {
+        (
+            InventoryItems((1..=MAX_ITEM_COUNT as u8).map(item).collect()),
+            GearInventory((1..=MAX_GEAR_COUNT as u8).map(gear).collect()),
+        )
+    }
+
+    #[test]
+    fn drops_which_fit_are_taken() {
+        let rewards = PendingRewards {
+            items: vec![item(1), item(2)],
+            gear: vec![gear(1)],
+            ..Default::default()
+        };
+        let inventory_items = InventoryItems(vec![item(1)]);
+        let gear_inventory = GearInventory::default();
+
+        let choice = RewardChoice::new(&rewards, &inventory_items, &gear_inventory);
+
+        assert_eq!(choice.taken_items, vec![true, true]);
+        assert_eq!(choice.taken_gear, vec![true]);
+        let items = choice.chosen_items(&rewards, &inventory_items).unwrap();
+        assert_eq!(
+            items.0,
+            vec![
+                SummoningItem {
+                    quantity: 2,
+                    ..item(1)
+                },
+                item(2),
+            ]
+        );
+        let rack = choice.chosen_gear(&rewards, &gear_inventory).unwrap();
+        assert_eq!(rack.0, vec![gear(1)]);
+    }
+
+    #[test]
+    fn stacks_merge_into_a_full_inventory() {
+        let (inventory_items, gear_inventory) = full_inventories();
+        let rewards = PendingRewards {
+            items: vec![item(3), item(MAX_ITEM_COUNT as u8 + 1)],
+            ..Default::default()
+        };
+
+        let choice = RewardChoice::new(&rewards, &inventory_items, &gear_inventory);
+
+        assert_eq!(choice.taken_items, vec![true, false]);
+        let items = choice.chosen_items(&rewards, &inventory_items).unwrap();
+        assert_eq!(items.0.len(), MAX_ITEM_COUNT);
+        assert_eq!(items.0[2].quantity, 2);
+    }
+
+    #[test]
+    fn discarding_makes_room_for_drops() {
+        let (inventory_items, gear_inventory) = full_inventories();
+        let rewards = PendingRewards {
+            items: vec![item(MAX_ITEM_COUNT as u8 + 1)],
+            gear: vec![gear(MAX_GEAR_COUNT as u8 + 1)],
+            ..Default::default()
+        };
+        let mut choice = RewardChoice::new(&rewards, &inventory_items, &gear_inventory);
+        assert_eq!(choice.taken_items, vec![false]);
+        assert_eq!(choice.taken_gear, vec![false]);
+
+        for slot in [
+            ChoiceSlot::InventoryItem(0),
+            ChoiceSlot::DroppedItem(0),
+            ChoiceSlot::RackGear(0),
+            ChoiceSlot::DroppedGear(0),
+        ] {
+            assert!(choice.try_toggle(slot, &rewards, &inventory_items, &gear_inventory));
+        }
+
+        let items = choice.chosen_items(&rewards, &inventory_items).unwrap();
+        assert!(!items.0.contains(&item(1)));
+        assert!(items.0.contains(&item(MAX_ITEM_COUNT as u8 + 1)));
+        let rack = choice.chosen_gear(&rewards, &gear_inventory).unwrap();
+        assert!(!rack.0.contains(&gear(1)));
+        assert!(rack.0.contains(&gear(MAX_GEAR_COUNT as u8 + 1)));
+    }
+
+    #[test]
+    fn overflowing_toggles_are_rejected() {
+        let (inventory_items, gear_inventory) = full_inventories();
+        let rewards = PendingRewards {
+            items: vec![item(MAX_ITEM_COUNT as u8 + 1)],
+            gear: vec![gear(MAX_GEAR_COUNT as u8 + 1)],
+            ..Default::default()
+        };
+        let mut choice = RewardChoice::new(&rewards, &inventory_items, &gear_inventory);
+
+        assert!(!choice.try_toggle(
+            ChoiceSlot::DroppedItem(0),
+            &rewards,
+            &inventory_items,
+            &gear_inventory
+        ));
+        assert!(choice.warning.is_some());
+        assert!(!choice.try_toggle(
+            ChoiceSlot::DroppedGear(0),
+            &rewards,
+            &inventory_items,
+            &gear_inventory
+        ));
+        assert_eq!(choice.taken_items, vec![false]);
+        assert_eq!(choice.taken_gear, vec![false]);
+
+        // keeping a discarded item once the freed slot is taken would overflow as well
+        assert!(choice.try_toggle(
+            ChoiceSlot::InventoryItem(0),
+            &rewards,
+            &inventory_items,
+            &gear_inventory
+        ));
+        assert!(choice.warning.is_none());
+        assert!(choice.try_toggle(
+            ChoiceSlot::DroppedItem(0),
+            &rewards,
+            &inventory_items,
+            &gear_inventory
+        ));
+        assert!(!choice.try_toggle(
+            ChoiceSlot::InventoryItem(0),
+            &rewards,
+            &inventory_items,
+            &gear_inventory
+        ));
+        assert!(choice.discarded_items[0]);
+    }
+}
@@ -0,0 +1,404 @@
+use bevy::prelude::*;
+
+use crate::{
+    balance::Balance,
+    codex::CodexState,
+    experience::LevelUpChoice,
+    gear::{Equipment, GearInventory, GEAR_COLOR},
+    loading::{FontAssets, TextureAssets},
+    minions::Minion,
+    mouse_control::Clickable,
+    summoning::{Ingredients, InventoryItems, ShouldRecreateItemCards, SummoningItem},
+    utils::num_to_roman,
+    GameScreen, GameState,
+};
+
+const SACRIFICE_Z: f32 = 300.;
+const REFUND_CARD_SIZE: Vec2 = Vec2::new(320., 64.);
+const REFUND_CARD_SPACING: f32 = 72.;
+const REFUND_TOP_Y: f32 = 220.;
+const REFUND_COLUMN_LENGTH: usize = 5;
+const REFUND_COLUMN_SPACING: f32 = 360.;
+const BUTTON_SIZE: Vec2 = Vec2::new(320., 96.);
+const BUTTON_Y: f32 = -320.;
+const BUTTON_SPACING: f32 = 400.;
+const SACRIFICE_TEXT_SIZE: f32 = 48.;
+
+pub struct SacrificePlugin;
+
+/// This plugin lets the player sacrifice minions on the summoning screen to get back a part of
+/// the ingredients they were summoned from. The sacrifice has to be confirmed first.
+impl Plugin for SacrificePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(OnExit(GameScreen::Summoning), close_sacrifice_confirmation)
+            .add_systems(
+                Update,
+                (
+                    handle_open_sacrifice_confirmation.run_if(
+                        in_state(CodexState::Closed)
+                            .and_then(not(resource_exists::<LevelUpChoice>))
+                            .and_then(not(resource_exists::<SacrificeConfirmation>)),
+                    ),
+                    spawn_sacrifice_confirmation.run_if(resource_added::<SacrificeConfirmation>),
+                    handle_sacrifice_confirmation.run_if(resource_exists::<SacrificeConfirmation>),
+                )
+                    .run_if(in_state(GameState::Playing).and_then(in_state(GameScreen::Summoning))),
+            );
+    }
+}
+
+/// Minion waiting for its sacrifice to be confirmed, summoning screen ignores clicks meanwhile.
+#[derive(Resource)]
+pub struct SacrificeConfirmation {
+    minion: Entity,
+}
+
+#[derive(Component)]
+struct ConfirmSacrificeButton;
+
+#[derive(Component)]
+struct SacrificeEntity;
+
+/// Refunded ingredient or equipped gear listed in the confirmation.
+struct RefundCard {
+    quantity: usize,
+    tier: u8,
+    icon: Handle<Image>,
+    icon_color: Color,
+    /// Whether it fits into the inventory or the rack.
+    fits: bool,
+}
+
+/// Items given back for the ingredients of a sacrificed minion, ingredients of the highest tiers
+/// come back first. The refund is rounded down, so summoning can not be undone for free.
+fn refunded_items(ingredients: &[SummoningItem], refund_ratio: f32) -> Vec<SummoningItem> {
+    let total: usize = ingredients.iter().map(|item| item.quantity).sum();
+    let mut remaining = (total as f32 * refund_ratio).floor() as usize;
+
+    let mut ingredients = ingredients.to_vec();
+    ingredients.sort_by_key(|item| std::cmp::Reverse(item.tier));
+
+    let mut refund = Vec::new();
+    for item in ingredients {
+        if remaining == 0 {
+            break;
+        }
+        let quantity = item.quantity.min(remaining);
+        remaining -= quantity;
+        refund.push(SummoningItem { quantity, ..item });
+    }
+
+    refund
+}
+
+fn handle_open_sacrifice_confirmation(
+    mut commands: Commands,
+    minion_query: Query<(Entity, &Clickable), With<Minion>>,
+) {
+    for (entity, clickable) in minion_query.iter() {
+        if clickable.just_right_clicked {
+            commands.insert_resource(SacrificeConfirmation { minion: entity });
+            return;
+        }
+    }
+}
+
+fn spawn_button(
+    parent: &mut ChildBuilder,
+    textures: &TextureAssets,
+    fonts: &FontAssets,
+    label: &str,
+    color: Color,
+    x: f32,
+    bundle: impl Bundle,
+) {
+    parent
+        .spawn((
+            SpriteBundle {
+                texture: textures.square.clone(),
+                sprite: Sprite {
+                    custom_size: Some(BUTTON_SIZE),
+                    color,
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(x, BUTTON_Y, 1.),
+                ..Default::default()
+            },
+            bundle,
+        ))
+        .with_children(|button| {
+            button.spawn(Text2dBundle {
+                text: Text::from_section(
+                    label,
+                    TextStyle {
+                        font: fonts.texts.clone(),
+                        font_size: SACRIFICE_TEXT_SIZE,
+                        color: Color::WHITE,
+                    },
+                ),
+                transform: Transform::from_xyz(0., 0., 1.),
+                ..Default::default()
+            });
+        });
+}
+
+/// Refunded items and equipped gear are listed before the sacrifice is confirmed, the ones which
+/// would not fit into the inventory or the rack are marked red.
+#[allow(clippy::too_many_arguments)]
+fn spawn_sacrifice_confirmation(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    fonts: Res<FontAssets>,
+    balance: Res<Balance>,
+    inventory_items: Res<InventoryItems>,
+    gear_inventory: Res<GearInventory>,
+    confirmation: Res<SacrificeConfirmation>,
+    minion_query: Query<(&Ingredients, &Equipment), With<Minion>>,
+) {
+    let Ok((ingredients, equipment)) = minion_query.get(confirmation.minion) else {
+        return;
+    };
+    let refund = refunded_items(&ingredients.0, balance.sacrifice.refund_ratio);
+    let mut inventory = InventoryItems(inventory_items.0.clone());
+    let mut rack = GearInventory(gear_inventory.0.clone());
+    let mut cards: Vec<_> = refund
+        .iter()
+        .map(|item| RefundCard {
+            quantity: item.quantity,
+            tier: item.tier,
+            icon: item.item_type.icon(&textures),
+            icon_color: Color::CYAN,
+            fits: inventory.add(item),
+        })
+        .collect();
+    cards.extend(equipment.0.iter().map(|gear| RefundCard {
+        quantity: 1,
+        tier: gear.tier,
+        icon: gear.kind.icon(&textures),
+        icon_color: GEAR_COLOR,
+        fits: rack.add(gear),
+    }));
+    let text_style = |font_size: f32, color: Color| TextStyle {
+        font: fonts.texts.clone(),
+        font_size,
+        color,
+    };
+
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: textures.square.clone(),
+                sprite: Sprite {
+                    custom_size: Some(Vec2::new(1920., 1080.)),
+                    color: Color::BLACK.with_a(0.9),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0., 0., SACRIFICE_Z),
+                ..Default::default()
+            },
+            SacrificeEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn(Text2dBundle {
+                text: Text::from_section("SACRIFICE MINION?", text_style(96., Color::GOLD)),
+                transform: Transform::from_xyz(0., 1080. / 2. - 160., 1.),
+                ..Default::default()
+            });
+
+            let column_count = cards.len().div_ceil(REFUND_COLUMN_LENGTH);
+            let first_x = -REFUND_COLUMN_SPACING * (column_count as f32 - 1.) / 2.;
+            let any_lost = cards.iter().any(|card| !card.fits);
+            for (index, refund_card) in cards.into_iter().enumerate() {
+                let column = index / REFUND_COLUMN_LENGTH;
+                let row = index % REFUND_COLUMN_LENGTH;
+
+                parent
+                    .spawn(SpriteBundle {
+                        texture: textures.square.clone(),
+                        sprite: Sprite {
+                            custom_size: Some(REFUND_CARD_SIZE),
+                            color: if refund_card.fits {
+                                Color::DARK_GRAY
+                            } else {
+                                Color::MAROON
+                            },
+                            ..Default::default()
+                        },
+                        transform: Transform::from_xyz(
+                            first_x + REFUND_COLUMN_SPACING * column as f32,
+                            REFUND_TOP_Y - REFUND_CARD_SPACING * row as f32,
+                            1.,
+                        ),
+                        ..Default::default()
+                    })
+                    .with_children(|card| {
+                        card.spawn(Text2dBundle {
+                            text: Text::from_section(
+                                refund_card.quantity.to_string() + "x",
+                                TextStyle {
+                                    color: Color::WHITE,
+                                    font: fonts.quantity_numbers.clone(),
+                                    font_size: REFUND_CARD_SIZE.y,
+                                },
+                            ),
+                            text_anchor: bevy::sprite::Anchor::CenterLeft,
+                            transform: Transform::from_xyz(-REFUND_CARD_SIZE.x / 2. + 16., 0., 1.),
+                            ..Default::default()
+                        });
+                        card.spawn(Text2dBundle {
+                            text: Text::from_section(
+                                num_to_roman(refund_card.tier),
+                                TextStyle {
+                                    color: Color::WHITE,
+                                    font: fonts.tier_numbers.clone(),
+                                    font_size: REFUND_CARD_SIZE.y,
+                                },
+                            ),
+                            text_anchor: bevy::sprite::Anchor::CenterRight,
+                            transform: Transform::from_xyz(32., 0., 1.),
+                            ..Default::default()
+                        });
+                        card.spawn(SpriteBundle {
+                            texture: refund_card.icon,
+                            sprite: Sprite {
+                                color: refund_card.icon_color,
+                                custom_size: Some(Vec2::splat(REFUND_CARD_SIZE.y * 0.7)),
+                                anchor: bevy::sprite::Anchor::CenterLeft,
+                                ..Default::default()
+                            },
+                            transform: Transform::from_xyz(48., 0., 1.),
+                            ..Default::default()
+                        });
+                    });
+            }
+
+            let note = if any_lost {
+                "ITEMS IN RED DO NOT FIT INTO THE INVENTORY OR THE RACK AND ARE LOST"
+            } else if refund.is_empty() {
+                "NO INGREDIENTS ARE REFUNDED"
+            } else {
+                "REFUNDED INGREDIENTS GO TO THE INVENTORY, GEAR GOES TO THE RACK"
+            };
+            parent.spawn(Text2dBundle {
+                text: Text::from_section(note, text_style(SACRIFICE_TEXT_SIZE * 0.7, Color::WHITE)),
+                transform: Transform::from_xyz(0., BUTTON_Y + BUTTON_SIZE.y, 1.),
+                ..Default::default()
+            });
+
+            spawn_button(
+                parent,
+                &textures,
+                &fonts,
+                "SACRIFICE",
+                Color::MAROON,
+                -BUTTON_SPACING / 2.,
+                (Clickable::default(), ConfirmSacrificeButton),
+            );
+            // any click other than the confirmation cancels, the button only makes it obvious
+            spawn_button(
+                parent,
+                &textures,
+                &fonts,
+                "CANCEL",
+                Color::DARK_GRAY,
+                BUTTON_SPACING / 2.,
+                (),
+            );
+        });
+}
+
+/// Confirmed sacrifice refunds the ingredients and returns equipped gear to the rack, any other
+/// click keeps the minion.
+#[allow(clippy::too_many_arguments)]
+fn handle_sacrifice_confirmation(
+    mut commands: Commands,
+    mouse_input: Res<ButtonInput<MouseButton>>,
+    balance: Res<Balance>,
+    confirmation: Res<SacrificeConfirmation>,
+    mut inventory_items: ResMut<InventoryItems>,
+    mut gear_inventory: ResMut<GearInventory>,
+    mut recreate_items: ResMut<ShouldRecreateItemCards>,
+    button_query: Query<&Clickable, With<ConfirmSacrificeButton>>,
+    minion_query: Query<(&Ingredients, &Equipment), With<Minion>>,
+    entity_query: Query<Entity, With<SacrificeEntity>>,
+) {
+    if !mouse_input.any_just_pressed([MouseButton::Left, MouseButton::Right]) {
+        return;
+    }
+
+    let is_confirmed = button_query
+        .iter()
+        .any(|clickable| clickable.just_left_clicked);
+    if let (true, Ok((ingredients, equipment))) =
+        (is_confirmed, minion_query.get(confirmation.minion))
+    {
+        for item in refunded_items(&ingredients.0, balance.sacrifice.refund_ratio) {
+            inventory_items.add(&item);
+        }
+        for gear in equipment.0.iter() {
+            gear_inventory.add(gear);
+        }
+        recreate_items.should_recreate_inventory_items = true;
+        commands.entity(confirmation.minion).despawn_recursive();
+    }
+
+    commands.remove_resource::<SacrificeConfirmation>();
+    for entity in entity_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+fn close_sacrifice_confirmation(
+    mut commands: Commands,
+    query: Query<Entity, With<SacrificeEntity>>,
+) {
+    commands.remove_resource::<SacrificeConfirmation>();
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::summoning::SummoningItemType;
+
+    fn item(item_type: SummoningItemType, tier: u8, quantity: usize) -> SummoningItem {
+        SummoningItem {
+            item_type,
+            tier,
+            quantity,
+        }
+    }
+
+    #[test]
+    fn highest_tiers_are_refunded_first() {
+        let ingredients = [
+            item(SummoningItemType::Damage, 1, 3),
+            item(SummoningItemType::Speed, 3, 1),
+            item(SummoningItemType::Armor, 2, 2),
+        ];
+
+        assert_eq!(
+            refunded_items(&ingredients, 0.5),
+            vec![
+                item(SummoningItemType::Speed, 3, 1),
+                item(SummoningItemType::Armor, 2, 2),
+            ]
+        );
+    }
+
+    #[test]
+    fn refund_is_rounded_down() {
+        let ingredients = [
+            item(SummoningItemType::Damage, 2, 2),
+            item(SummoningItemType::Speed, 1, 3),
+        ];
+
+        assert_eq!(
+            refunded_items(&ingredients, 0.5),
+            vec![item(SummoningItemType::Damage, 2, 2)]
+        );
+        assert!(refunded_items(&[item(SummoningItemType::Damage, 4, 1)], 0.5).is_empty());
+    }
+}
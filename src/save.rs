@@ -0,0 +1,394 @@
+use std::fmt;
+
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    dungeon_map::DungeonMap,
+    experience::MinionLevel,
+    formation::FormationSlot,
+    gear::{Equipment, Gear, GearInventory},
+    loading::TextureAssets,
+    minions::{spawn_minion, Minion},
+    planning_screen::{start_next_act, RerollsUsed},
+    recovery::PendingRest,
+    run_seed::RunSeed,
+    shop::Gold,
+    statistics::Statistics,
+    stats::Stats,
+    summoning::{spawn_items, Ingredients, InventoryItems, SummoningItem},
+    BattleCount, GameScreen, GameState,
+};
+
+/// Version of the save format, saves with a different version are rejected.
+const SAVE_VERSION: u32 = 17;
+
+#[cfg(not(target_arch = "wasm32"))]
+const SAVE_FILE_PATH: &str = "save.ron";
+#[cfg(target_arch = "wasm32")]
+const SAVE_STORAGE_KEY: &str = "path_of_summoner_save";
+
+pub struct SavePlugin;
+
+/// This plugin saves the run at the start of every summoning and planning screen and resumes it
+/// from the menu. Native builds save to a file, the web build uses browser local storage.
+impl Plugin for SavePlugin {
+    fn build(&self, app: &mut App) {
+        app.add_event::<ContinueRunEvent>()
+            .add_systems(OnEnter(GameScreen::Summoning), save_run.after(spawn_items))
+            .add_systems(
+                OnEnter(GameScreen::Planning),
+                save_run.after(start_next_act),
+            )
+            .add_systems(OnEnter(GameState::GameOver), delete_run)
+            .add_systems(Update, continue_run.run_if(in_state(GameState::Menu)));
+    }
+}
+
+/// Sent by the menu when the player wants to continue the saved run.
+#[derive(Event)]
+pub struct ContinueRunEvent;
+
+#[derive(Debug)]
+pub enum SaveError {
+    NotFound,
+    Io(String),
+    Corrupt(String),
+    Outdated { version: u32 },
+}
+
+impl fmt::Display for SaveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SaveError::NotFound => write!(f, "no saved run found"),
+            SaveError::Io(error) => write!(f, "save could not be accessed ({error})"),
+            SaveError::Corrupt(error) => write!(f, "save is corrupted ({error})"),
+            SaveError::Outdated { version } => write!(
+                f,
+                "save version {version} is not supported, expected version {SAVE_VERSION}"
+            ),
+        }
+    }
+}
+
+#[derive(Serialize, Deserialize, Clone, Copy)]
+enum SavedScreen {
+    Summoning,
+    Planning,
+}
+
+/// Only the version is read first, so saves from other versions are reported as outdated instead
+/// of corrupted.
+#[derive(Deserialize)]
+struct SaveHeader {
+    version: u32,
+}
+
+/// Minion with everything it gained during the run.
+#[derive(Serialize, Deserialize)]
+struct SavedMinion {
+    stats: Stats,
+    level: MinionLevel,
+    equipment: Equipment,
+    ingredients: Ingredients,
+    slot: FormationSlot,
+}
+
+#[derive(Serialize, Deserialize)]
+pub struct SaveData {
+    version: u32,
+    screen: SavedScreen,
+    run_seed: u64,
+    battle_count: usize,
+    gold: usize,
+    rerolls_used: usize,
+    /// Rest of a visited rest site which was not taken yet.
+    pending_rest: bool,
+    dungeon_map: DungeonMap,
+    statistics: Statistics,
+    inventory_items: Vec<SummoningItem>,
+    /// Gear in the rack, equipped gear is saved with its minion.
+    gear_inventory: Vec<Gear>,
+    minions: Vec<SavedMinion>,
+}
+
+pub fn read_save() -> Result<SaveData, SaveError> {
+    parse_save(&read_save_text()?)
+}
+
+fn parse_save(text: &str) -> Result<SaveData, SaveError> {
+    let header: SaveHeader =
+        ron::from_str(text).map_err(|error| SaveError::Corrupt(error.to_string()))?;
+    if header.version != SAVE_VERSION {
+        return Err(SaveError::Outdated {
+            version: header.version,
+        });
+    }
+
+    ron::from_str(text).map_err(|error| SaveError::Corrupt(error.to_string()))
+}
+
+fn write_save(data: &SaveData) -> Result<(), SaveError> {
+    write_save_text(&serialize_save(data)?)
+}
+
+fn serialize_save(data: &SaveData) -> Result<String, SaveError> {
+    ron::ser::to_string_pretty(data, ron::ser::PrettyConfig::default())
+        .map_err(|error| SaveError::Corrupt(error.to_string()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn read_save_text() -> Result<String, SaveError> {
+    match std::fs::read_to_string(SAVE_FILE_PATH) {
+        Ok(text) => Ok(text),
+        Err(error) if error.kind() == std::io::ErrorKind::NotFound => Err(SaveError::NotFound),
+        Err(error) => Err(SaveError::Io(error.to_string())),
+    }
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn write_save_text(text: &str) -> Result<(), SaveError> {
+    std::fs::write(SAVE_FILE_PATH, text).map_err(|error| SaveError::Io(error.to_string()))
+}
+
+#[cfg(not(target_arch = "wasm32"))]
+fn delete_save_text() -> Result<(), SaveError> {
+    match std::fs::remove_file(SAVE_FILE_PATH) {
+        Err(error) if error.kind() != std::io::ErrorKind::NotFound => {
+            Err(SaveError::Io(error.to_string()))
+        }
+        _ => Ok(()),
+    }
+}
+
+#[cfg(target_arch = "wasm32")]
+fn local_storage() -> Result<web_sys::Storage, SaveError> {
+    web_sys::window()
+        .and_then(|window| window.local_storage().ok().flatten())
+        .ok_or_else(|| SaveError::Io("local storage is not available".to_string()))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn read_save_text() -> Result<String, SaveError> {
+    local_storage()?
+        .get_item(SAVE_STORAGE_KEY)
+        .map_err(|error| SaveError::Io(format!("{error:?}")))?
+        .ok_or(SaveError::NotFound)
+}
+
+#[cfg(target_arch = "wasm32")]
+fn write_save_text(text: &str) -> Result<(), SaveError> {
+    local_storage()?
+        .set_item(SAVE_STORAGE_KEY, text)
+        .map_err(|error| SaveError::Io(format!("{error:?}")))
+}
+
+#[cfg(target_arch = "wasm32")]
+fn delete_save_text() -> Result<(), SaveError> {
+    local_storage()?
+        .remove_item(SAVE_STORAGE_KEY)
+        .map_err(|error| SaveError::Io(format!("{error:?}")))
+}
+
+#[allow(clippy::too_many_arguments)]
+fn save_run(
+    game_screen: Res<State<GameScreen>>,
+    run_seed: Res<RunSeed>,
+    battle_count: Res<BattleCount>,
+    gold: Res<Gold>,
+    rerolls_used: Res<RerollsUsed>,
+    pending_rest: Res<PendingRest>,
+    dungeon_map: Res<DungeonMap>,
+    statistics: Res<Statistics>,
+    inventory_items: Res<InventoryItems>,
+    gear_inventory: Res<GearInventory>,
+    minion_query: Query<
+        (
+            &Stats,
+            &MinionLevel,
+            &Equipment,
+            &Ingredients,
+            &FormationSlot,
+        ),
+        With<Minion>,
+    >,
+) {
+    let screen = match game_screen.get() {
+        GameScreen::Summoning => SavedScreen::Summoning,
+        GameScreen::Planning => SavedScreen::Planning,
+        _ => return,
+    };
+
+    let data = SaveData {
+        version: SAVE_VERSION,
+        screen,
+        run_seed: run_seed.0,
+        battle_count: battle_count.0,
+        gold: gold.0,
+        rerolls_used: rerolls_used.0,
+        pending_rest: pending_rest.0,
+        dungeon_map: dungeon_map.clone(),
+        statistics: statistics.clone(),
+        inventory_items: inventory_items.0.clone(),
+        gear_inventory: gear_inventory.0.clone(),
+        minions: minion_query
+            .iter()
+            .map(
+                |(stats, level, equipment, ingredients, &slot)| SavedMinion {
+                    stats: stats.clone(),
+                    level: level.clone(),
+                    equipment: equipment.clone(),
+                    ingredients: ingredients.clone(),
+                    slot,
+                },
+            )
+            .collect(),
+    };
+
+    if let Err(error) = write_save(&data) {
+        error!("Failed to save the run: {error}");
+    }
+}
+
+fn delete_run() {
+    if let Err(error) = delete_save_text() {
+        error!("Failed to delete the saved run: {error}");
+    }
+}
+
+#[allow(clippy::too_many_arguments)]
+fn continue_run(
+    mut commands: Commands,
+    mut continue_run_event: EventReader<ContinueRunEvent>,
+    mut next_state: ResMut<NextState<GameState>>,
+    mut next_screen: ResMut<NextState<GameScreen>>,
+    mut run_seed: ResMut<RunSeed>,
+    mut battle_count: ResMut<BattleCount>,
+    mut gold: ResMut<Gold>,
+    mut rerolls_used: ResMut<RerollsUsed>,
+    mut pending_rest: ResMut<PendingRest>,
+    mut dungeon_map: ResMut<DungeonMap>,
+    mut statistics: ResMut<Statistics>,
+    mut inventory_items: ResMut<InventoryItems>,
+    mut gear_inventory: ResMut<GearInventory>,
+    textures: Res<TextureAssets>,
+) {
+    if continue_run_event.read().last().is_none() {
+        return;
+    }
+
+    let save = match read_save() {
+        Ok(save) => save,
+        Err(error) => {
+            error!("Failed to continue the run: {error}");
+            return;
+        }
+    };
+
+    *run_seed = RunSeed(save.run_seed);
+    battle_count.0 = save.battle_count;
+    gold.0 = save.gold;
+    rerolls_used.0 = save.rerolls_used;
+    pending_rest.0 = save.pending_rest;
+    *dungeon_map = save.dungeon_map;
+    *statistics = save.statistics;
+    inventory_items.0 = save.inventory_items;
+    gear_inventory.0 = save.gear_inventory;
+    for minion in save.minions {
+        let entity = spawn_minion(&mut commands, &textures, minion.stats, minion.slot);
+        commands
+            .entity(entity)
+            .insert((minion.level, minion.equipment, minion.ingredients));
+    }
+
+    next_state.set(GameState::Playing);
+    next_screen.set(match save.screen {
+        SavedScreen::Summoning => GameScreen::Summoning,
+        SavedScreen::Planning => GameScreen::Planning,
+    });
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::{stats::Row, summoning::SummoningItemType};
+
+    fn save_data(version: u32) -> SaveData {
+        let item = SummoningItem {
+            item_type: SummoningItemType::Damage,
+            tier: 2,
+            quantity: 3,
+        };
+
+        SaveData {
+            version,
+            screen: SavedScreen::Planning,
+            run_seed: 42,
+            battle_count: 4,
+            gold: 17,
+            rerolls_used: 1,
+            pending_rest: true,
+            dungeon_map: DungeonMap::default(),
+            statistics: Statistics::default(),
+            inventory_items: vec![item.clone()],
+            gear_inventory: Vec::new(),
+            minions: vec![SavedMinion {
+                stats: Stats {
+                    current_hp: 30.,
+                    max_hp: 50.,
+                    ..Default::default()
+                },
+                level: MinionLevel::default(),
+                equipment: Equipment::default(),
+                ingredients: Ingredients(vec![item]),
+                slot: FormationSlot {
+                    row: Row::Back,
+                    index: 1,
+                },
+            }],
+        }
+    }
+
+    #[test]
+    fn save_round_trips() {
+        let text = serialize_save(&save_data(SAVE_VERSION)).unwrap();
+        let save = parse_save(&text).unwrap();
+
+        assert_eq!(serialize_save(&save).unwrap(), text);
+        assert_eq!((save.run_seed, save.battle_count, save.gold), (42, 4, 17));
+        assert_eq!(
+            save.inventory_items,
+            save_data(SAVE_VERSION).inventory_items
+        );
+        assert_eq!(save.minions[0].stats.current_hp, 30.);
+        assert_eq!(save.minions[0].slot.row, Row::Back);
+    }
+
+    #[test]
+    fn other_versions_are_outdated() {
+        let text = serialize_save(&save_data(SAVE_VERSION - 1)).unwrap();
+        let error = parse_save(&text).err().unwrap();
+
+        assert!(matches!(error, SaveError::Outdated { version } if version == SAVE_VERSION - 1));
+        assert_eq!(
+            error.to_string(),
+            format!(
+                "save version {} is not supported, expected version {SAVE_VERSION}",
+                SAVE_VERSION - 1
+            )
+        );
+    }
+
+    #[test]
+    fn old_formats_are_outdated_instead_of_corrupt() {
+        let error = parse_save("(version: 1, screen: Summoning, enemy_hp: 20.)")
+            .err()
+            .unwrap();
+
+        assert!(matches!(error, SaveError::Outdated { version: 1 }));
+        assert!(matches!(
+            parse_save("not a save"),
+            Err(SaveError::Corrupt(_))
+        ));
+    }
+}
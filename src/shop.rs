@@ -0,0 +1,405 @@
+use bevy::prelude::*;
+
+use crate::{
+    balance::Balance,
+    loading::{FontAssets, TextureAssets},
+    mouse_control::{update_clickables, Clickable},
+    summoning::{InventoryItems, SummoningItem},
+    utils::num_to_roman,
+    GameScreen, GameState,
+};
+
+/// Most items a shop can offer, so that all of them fit on the screen.
+pub const MAX_STOCK_SIZE: usize = 6;
+
+const STOCK_POS: Vec3 = Vec3::new(0., 1080. / 2. - 220., 0.);
+const OFFER_CARD_SIZE: Vec2 = Vec2::new(600., 96.);
+const GOLD_ICON_SIZE: f32 = 48.;
+const SHOP_GOLD_COUNTER_POS: Vec3 = Vec3::new(1920. / 2. - 240., 1080. / 2. - 64., 0.);
+
+pub struct ShopPlugin;
+
+/// This plugin handles the run currency and the shop screen, where gold buys items.
+impl Plugin for ShopPlugin {
+    fn build(&self, app: &mut App) {
+        app.init_resource::<Gold>()
+            .init_resource::<ShopStock>()
+            .add_systems(OnEnter(GameScreen::Shop), spawn_shop_screen)
+            .add_systems(OnExit(GameScreen::Shop), clean_shop_screen)
+            .add_systems(
+                Update,
+                (handle_buy_item, handle_leave_shop)
+                    .run_if(in_state(GameState::Playing).and_then(in_state(GameScreen::Shop))),
+            )
+            .add_systems(
+                PreUpdate,
+                spawn_offer_cards
+                    .after(update_clickables)
+                    .run_if(in_state(GameState::Playing).and_then(in_state(GameScreen::Shop))),
+            )
+            .add_systems(
+                Update,
+                update_gold_counters.run_if(in_state(GameState::Playing)),
+            );
+    }
+}
+
+/// Currency of the run, earned by winning battles and selling items.
+#[derive(Resource, Default)]
+pub struct Gold(pub usize);
+
+/// Items offered by a shop node, the visited shop keeps its stock in a resource.
+#[derive(Component, Resource, Clone, Default)]
+pub struct ShopStock(pub Vec<SummoningItem>);
+
+#[derive(Component)]
+struct GoldText;
+
+#[derive(Component)]
+struct StockList;
+
+#[derive(Component)]
+struct OfferCard(usize);
+
+#[derive(Component)]
+struct LeaveButton;
+
+#[derive(Component)]
+struct ShopScreenEntity;
+
+/// Gold icon with the current amount of gold, it is kept up to date while the run is played.
+pub fn spawn_gold_counter(
+    commands: &mut Commands,
+    textures: &TextureAssets,
+    fonts: &FontAssets,
+    gold: &Gold,
+    position: Vec3,
+) -> Entity {
+    commands
+        .spawn(SpatialBundle::from_transform(Transform::from_translation(
+            position,
+        )))
+        .with_children(|parent| {
+            parent.spawn(SpriteBundle {
+                texture: textures.gold_icon.clone(),
+                sprite: Sprite {
+                    color: Color::GOLD,
+                    custom_size: Some(Vec2::splat(GOLD_ICON_SIZE)),
+                    anchor: bevy::sprite::Anchor::CenterRight,
+                    ..Default::default()
+                },
+                ..Default::default()
+            });
+            parent.spawn((
+                Text2dBundle {
+                    text: Text::from_section(
+                        gold.0.to_string(),
+                        TextStyle {
+                            color: Color::GOLD,
+                            font: fonts.quantity_numbers.clone(),
+                            font_size: GOLD_ICON_SIZE,
+                        },
+                    ),
+                    text_anchor: bevy::sprite::Anchor::CenterLeft,
+                    transform: Transform::from_xyz(8., 0., 0.),
+                    ..Default::default()
+                },
+                GoldText,
+            ));
+        })
+        .id()
+}
+
+fn update_gold_counters(gold: Res<Gold>, mut query: Query<&mut Text, With<GoldText>>) {
+    if !gold.is_changed() {
+        return;
+    }
+
+    for mut text in query.iter_mut() {
+        text.sections[0].value = gold.0.to_string();
+    }
+}
+
+fn spawn_shop_screen(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    fonts: Res<FontAssets>,
+    gold: Res<Gold>,
+) {
+    // background
+    commands.spawn((
+        SpriteBundle {
+            texture: textures.dungeon_floor_background.clone(),
+            transform: Transform::from_xyz(0., 0., -2.),
+            ..Default::default()
+        },
+        ShopScreenEntity,
+    ));
+    commands.spawn((
+        SpriteBundle {
+            texture: textures.square.clone(),
+            sprite: Sprite {
+                custom_size: Some(Vec2::new(1920., 1080.)),
+                color: Color::BLACK.with_a(0.7),
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0., 0., -1.),
+            ..Default::default()
+        },
+        ShopScreenEntity,
+    ));
+
+    commands.spawn((
+        Text2dBundle {
+            text: Text {
+                sections: vec![TextSection::new(
+                    "SHOP",
+                    TextStyle {
+                        color: Color::WHITE,
+                        font: fonts.texts.clone(),
+                        font_size: 96.,
+                    },
+                )],
+                ..Default::default()
+            },
+            transform: Transform::from_xyz(0., 1080. / 2. - 128., 0.),
+            ..Default::default()
+        },
+        ShopScreenEntity,
+    ));
+
+    let gold_counter = spawn_gold_counter(
+        &mut commands,
+        &textures,
+        &fonts,
+        &gold,
+        SHOP_GOLD_COUNTER_POS,
+    );
+    commands.entity(gold_counter).insert(ShopScreenEntity);
+
+    commands.spawn((
+        SpatialBundle::from_transform(Transform::from_translation(STOCK_POS)),
+        StockList,
+        ShopScreenEntity,
+    ));
+
+    // leave button
+    commands
+        .spawn((
+            SpriteBundle {
+                texture: textures.square.clone(),
+                sprite: Sprite {
+                    custom_size: Some(Vec2::new(384., 128.)),
+                    color: Color::DARK_GRAY,
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0., -400., 0.),
+                ..Default::default()
+            },
+            Clickable::default(),
+            LeaveButton,
+            ShopScreenEntity,
+        ))
+        .with_children(|parent| {
+            parent.spawn(Text2dBundle {
+                text: Text {
+                    sections: vec![TextSection {
+                        value: "Leave".to_string(),
+                        style: TextStyle {
+                            font: fonts.texts.clone(),
+                            font_size: 96.,
+                            color: Color::WHITE,
+                        },
+                    }],
+                    justify: JustifyText::Center,
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0., 0., 1.),
+                ..Default::default()
+            });
+        });
+}
+
+/// Card of a single offered item, its price is red while the player can not afford it.
+fn spawn_offer_card(
+    parent: &mut ChildBuilder,
+    textures: &TextureAssets,
+    fonts: &FontAssets,
+    index: usize,
+    item: &SummoningItem,
+    price: usize,
+    can_afford: bool,
+) {
+    parent
+        .spawn((
+            SpriteBundle {
+                texture: textures.square.clone(),
+                sprite: Sprite {
+                    color: Color::BLACK.with_a(0.95),
+                    custom_size: Some(OFFER_CARD_SIZE),
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(
+                    0.,
+                    -OFFER_CARD_SIZE.y * index as f32 - OFFER_CARD_SIZE.y / 2.,
+                    1.,
+                )
+                .with_scale(Vec3::new(0.99, 0.9, 1.)),
+                ..Default::default()
+            },
+            OfferCard(index),
+            Clickable::default(),
+        ))
+        .with_children(|card| {
+            let price_color = if can_afford { Color::GOLD } else { Color::RED };
+
+            // price
+            card.spawn(SpriteBundle {
+                texture: textures.gold_icon.clone(),
+                sprite: Sprite {
+                    color: price_color,
+                    custom_size: Some(Vec2::splat(OFFER_CARD_SIZE.y * 0.5)),
+                    anchor: bevy::sprite::Anchor::CenterLeft,
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(-OFFER_CARD_SIZE.x / 2. + 16., 0., 3.),
+                ..Default::default()
+            });
+            card.spawn(Text2dBundle {
+                text: Text::from_section(
+                    price.to_string(),
+                    TextStyle {
+                        color: price_color,
+                        font: fonts.quantity_numbers.clone(),
+                        font_size: OFFER_CARD_SIZE.y * 0.75,
+                    },
+                ),
+                text_anchor: bevy::sprite::Anchor::CenterLeft,
+                transform: Transform::from_xyz(
+                    -OFFER_CARD_SIZE.x / 2. + 24. + OFFER_CARD_SIZE.y * 0.5,
+                    0.,
+                    3.,
+                ),
+                ..Default::default()
+            });
+
+            // item category icon
+            card.spawn(SpriteBundle {
+                texture: textures.circle.clone(),
+                sprite: Sprite {
+                    color: Color::YELLOW,
+                    custom_size: Some(Vec2::splat(OFFER_CARD_SIZE.y * 0.5)),
+                    anchor: bevy::sprite::Anchor::CenterRight,
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(OFFER_CARD_SIZE.x / 2. - 16., 0., 3.),
+                ..Default::default()
+            });
+
+            // tier number
+            card.spawn(Text2dBundle {
+                text: Text::from_section(
+                    num_to_roman(item.tier),
+                    TextStyle {
+                        color: Color::WHITE,
+                        font: fonts.tier_numbers.clone(),
+                        font_size: OFFER_CARD_SIZE.y,
+                    },
+                ),
+                transform: Transform::from_xyz(-32., 0., 3.),
+                ..Default::default()
+            });
+
+            // item type icon
+            card.spawn(SpriteBundle {
+                texture: item.item_type.icon(textures),
+                sprite: Sprite {
+                    color: Color::CYAN,
+                    custom_size: Some(Vec2::splat(OFFER_CARD_SIZE.y * 0.7)),
+                    anchor: bevy::sprite::Anchor::CenterLeft,
+                    ..Default::default()
+                },
+                transform: Transform::from_xyz(0., 0., 3.),
+                ..Default::default()
+            });
+        });
+}
+
+/// Recreate cards of the offered items whenever the stock or gold changes.
+fn spawn_offer_cards(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    fonts: Res<FontAssets>,
+    balance: Res<Balance>,
+    stock: Res<ShopStock>,
+    gold: Res<Gold>,
+    stock_list: Query<(Entity, Option<&Children>), With<StockList>>,
+) {
+    if !stock.is_changed() && !gold.is_changed() {
+        return;
+    }
+
+    let (stock_list_entity, children) = stock_list.single();
+
+    if let Some(children) = children {
+        for &child in children.iter() {
+            commands.entity(child).despawn_recursive();
+        }
+        commands.entity(stock_list_entity).clear_children();
+    }
+
+    commands.entity(stock_list_entity).with_children(|parent| {
+        for (index, item) in stock.0.iter().enumerate() {
+            let price = balance.shop.price(item);
+            spawn_offer_card(
+                parent,
+                &textures,
+                &fonts,
+                index,
+                item,
+                price,
+                price <= gold.0,
+            );
+        }
+    });
+}
+
+fn handle_buy_item(
+    balance: Res<Balance>,
+    mut gold: ResMut<Gold>,
+    mut stock: ResMut<ShopStock>,
+    mut inventory_items: ResMut<InventoryItems>,
+    query: Query<(&Clickable, &OfferCard)>,
+) {
+    for (clickable, &OfferCard(index)) in query.iter() {
+        if !clickable.just_left_clicked {
+            continue;
+        }
+
+        let price = balance.shop.price(&stock.0[index]);
+        if price > gold.0 || !inventory_items.add(&stock.0[index]) {
+            continue;
+        }
+
+        gold.0 -= price;
+        stock.0.remove(index);
+
+        // cards are recreated, so only a single item can be bought per frame
+        break;
+    }
+}
+
+fn handle_leave_shop(
+    mut next_screen: ResMut<NextState<GameScreen>>,
+    button_query: Query<&Clickable, With<LeaveButton>>,
+) {
+    if button_query.single().just_left_clicked {
+        next_screen.set(GameScreen::Summoning);
+    }
+}
+
+fn clean_shop_screen(mut commands: Commands, query: Query<Entity, With<ShopScreenEntity>>) {
+    for entity in query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+}
@@ -1,4 +1,5 @@
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 use crate::GameState;
 
@@ -13,7 +14,7 @@ impl Plugin for StatisticsPlugin {
     }
 }
 
-#[derive(Resource, Default)]
+#[derive(Resource, Default, Clone, Serialize, Deserialize)]
 pub struct Statistics {
     pub summoned_minions: usize,
     pub elapsed_seconds: f32,
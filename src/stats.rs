@@ -1,51 +1,184 @@
-use crate::{GameScreen, GameState};
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
-pub const MINION_HP_BASE: f32 = 80.0;
-pub const MINION_HP_REGEN_BASE: f32 = 0.0;
-pub const MINION_DMG_BASE: f32 = 19.0;
-pub const MINION_SPEED_BASE: f32 = 0.5;
-
-pub const MINION_HP_INC: f32 = 15.0;
-pub const MINION_HP_REGEN_INC: f32 = 1.5;
-pub const MINION_DMG_INC: f32 = 7.0;
-pub const MINION_SPEED_INC: f32 = 0.1;
-
-pub const ENEMY_HP_BASE: f32 = 160.0;
-pub const ENEMY_HP_REGEN_BASE: f32 = 0.0;
-pub const ENEMY_DMG_BASE: f32 = 30.0;
-pub const ENEMY_SPEED_BASE: f32 = 0.6;
-
-pub const ENEMY_HP_INC: f32 = 18.0;
-pub const ENEMY_HP_REGEN_INC: f32 = 4.0;
-pub const ENEMY_DMG_INC: f32 = 7.0;
-pub const ENEMY_SPEED_INC: f32 = 0.15;
-
-pub const BATTLES_TO_ITEM_TIER_INC: usize = 2;
-pub const BATTLES_TO_ENEMY_TIER_INC: usize = 2;
-
-pub struct StatsPlugin;
-
-impl Plugin for StatsPlugin {
-    fn build(&self, app: &mut App) {
-        app.add_systems(
-            Update,
-            (regenerate_hp_and_mana,)
-                .run_if(in_state(GameState::Playing).and_then(in_state(GameScreen::Battle))),
-        );
+use crate::status_effects::StatusEffect;
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum AbilityKind {
+    /// Damage every opponent.
+    AreaHit,
+    /// Heal ally with the lowest HP.
+    Heal,
+    /// Give every ally a shield which absorbs damage.
+    Shield,
+    /// Stun an opponent, stunned participants neither attack nor cast.
+    Stun,
+}
+
+/// Ability which is cast whenever the mana pool is full. Power is damage, healed HP, shield HP or
+/// stun duration in seconds, depending on the kind.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct Ability {
+    pub kind: AbilityKind,
+    pub power: f32,
+}
+
+/// Attack of a boss which hits several opponents at once, taunting ones first.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct SpecialAttack {
+    /// Damage of every hit relative to the damage stat.
+    pub damage_multiplier: f32,
+    pub target_count: usize,
+    /// Seconds between two special attacks.
+    pub cooldown: f32,
+}
+
+/// Phase of a boss fight, it starts once HP of the boss drops to the threshold and lasts until
+/// the next phase starts.
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct BossPhase {
+    /// Fraction of max HP at which the phase starts.
+    pub hp_threshold: f32,
+    /// Damage and speed are multiplied by these when the phase starts.
+    pub damage_multiplier: f32,
+    pub speed_multiplier: f32,
+    pub special: Option<SpecialAttack>,
+}
+
+/// How a participant picks its target among alive opponents. Taunting opponents are always
+/// picked first.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Default, Serialize, Deserialize)]
+pub enum TargetingPolicy {
+    /// First opponent in the lineup.
+    #[default]
+    FrontMost,
+    LowestHp,
+    HighestDamage,
+    Random,
+}
+
+/// Row of a minion in the formation, enemies always fight in the front row.
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Debug, Default, Serialize, Deserialize)]
+pub enum Row {
+    /// Targeted first.
+    #[default]
+    Front,
+    /// Only targeted once the front row falls, attacks from range and takes less damage.
+    Back,
+}
+
+/// Special kinds of minions summoned by recipes.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum RecipeKind {
+    Berserker,
+    Guardian,
+    Assassin,
+    Duelist,
+    Plaguebearer,
+    Warlord,
+}
+
+impl RecipeKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            RecipeKind::Berserker => "BERSERKER",
+            RecipeKind::Guardian => "GUARDIAN",
+            RecipeKind::Assassin => "ASSASSIN",
+            RecipeKind::Duelist => "DUELIST",
+            RecipeKind::Plaguebearer => "PLAGUEBEARER",
+            RecipeKind::Warlord => "WARLORD",
+        }
+    }
+}
+
+/// Stats which are raised by tiers of ingredients and enemies.
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum StatKind {
+    Damage,
+    Speed,
+    MaxHp,
+    HpRegeneration,
+    Armor,
+    CritChance,
+    CritMultiplier,
+    Evasion,
+}
+
+impl StatKind {
+    pub const ALL: [StatKind; 8] = [
+        StatKind::Damage,
+        StatKind::Speed,
+        StatKind::MaxHp,
+        StatKind::HpRegeneration,
+        StatKind::Armor,
+        StatKind::CritChance,
+        StatKind::CritMultiplier,
+        StatKind::Evasion,
+    ];
+
+    pub fn get(&self, stats: &Stats) -> f32 {
+        match self {
+            StatKind::Damage => stats.damage,
+            StatKind::Speed => stats.speed,
+            StatKind::MaxHp => stats.max_hp,
+            StatKind::HpRegeneration => stats.hp_regeneration,
+            StatKind::Armor => stats.armor,
+            StatKind::CritChance => stats.crit_chance,
+            StatKind::CritMultiplier => stats.crit_multiplier,
+            StatKind::Evasion => stats.evasion,
+        }
+    }
+
+    pub fn get_mut<'a>(&self, stats: &'a mut Stats) -> &'a mut f32 {
+        match self {
+            StatKind::Damage => &mut stats.damage,
+            StatKind::Speed => &mut stats.speed,
+            StatKind::MaxHp => &mut stats.max_hp,
+            StatKind::HpRegeneration => &mut stats.hp_regeneration,
+            StatKind::Armor => &mut stats.armor,
+            StatKind::CritChance => &mut stats.crit_chance,
+            StatKind::CritMultiplier => &mut stats.crit_multiplier,
+            StatKind::Evasion => &mut stats.evasion,
+        }
     }
 }
 
-#[derive(Component, Clone)]
+#[derive(Component, Clone, Serialize, Deserialize)]
 pub struct Stats {
     pub current_hp: f32,
     pub max_hp: f32,
     pub hp_regeneration: f32,
     pub speed: f32,
     pub damage: f32,
+    /// Flat reduction of damage taken from hits.
+    pub armor: f32,
+    /// Chance of a hit to be critical, from 0 to 1.
+    pub crit_chance: f32,
+    /// Damage of critical hits is multiplied by this.
+    pub crit_multiplier: f32,
+    /// Chance to avoid a hit completely, from 0 to 1.
+    pub evasion: f32,
     pub current_mana: f32,
     pub max_mana: f32,
     pub mana_regeneration: f32,
+    pub ability: Option<Ability>,
+    pub targeting: TargetingPolicy,
+    /// Taunting participants draw attacks of all opponents.
+    pub taunt: bool,
+    /// Status effect applied to opponents hit by attacks and area hits.
+    pub on_hit: Option<StatusEffect>,
+    /// Fraction of damage dealt by hits which heals the participant.
+    pub lifesteal: f32,
+    /// Fraction of damage taken from hits which is dealt back to the attacker, armor does not
+    /// reduce it.
+    pub thorns: f32,
+    /// Damage of attacks is raised by this fraction for every fallen ally.
+    pub frenzy: f32,
+    /// Phases of a boss ordered by their HP thresholds, enraged elites have a single one and other
+    /// participants have none.
+    pub phases: Vec<BossPhase>,
+    /// Summoning recipe the minion was created by.
+    pub recipe: Option<RecipeKind>,
 }
 
 impl Default for Stats {
@@ -56,23 +189,41 @@ impl Default for Stats {
             hp_regeneration: 0.,
             speed: 1.,
             damage: 1.,
+            armor: 0.,
+            crit_chance: 0.,
+            crit_multiplier: 1.,
+            evasion: 0.,
             current_mana: 0.,
             max_mana: 0.,
             mana_regeneration: 0.,
+            ability: None,
+            targeting: TargetingPolicy::FrontMost,
+            taunt: false,
+            on_hit: None,
+            lifesteal: 0.,
+            thorns: 0.,
+            frenzy: 0.,
+            phases: Vec::new(),
+            recipe: None,
         }
     }
 }
 
-fn regenerate_hp_and_mana(time: Res<Time>, mut query: Query<&mut Stats>) {
-    for mut stats in query.iter_mut() {
-        stats.current_hp += stats.hp_regeneration * time.delta_seconds();
-        if stats.current_hp > stats.max_hp {
-            stats.current_hp = stats.max_hp;
+impl Stats {
+    /// Restore the fraction of max HP without going over the maximum.
+    pub fn heal(&mut self, fraction: f32) {
+        self.current_hp = (self.current_hp + self.max_hp * fraction).min(self.max_hp);
+    }
+
+    pub fn regenerate_hp_and_mana(&mut self, delta: f32) {
+        self.current_hp += self.hp_regeneration * delta;
+        if self.current_hp > self.max_hp {
+            self.current_hp = self.max_hp;
         }
 
-        stats.current_mana += stats.mana_regeneration * time.delta_seconds();
-        if stats.current_mana > stats.max_mana {
-            stats.current_mana = stats.max_mana;
+        self.current_mana += self.mana_regeneration * delta;
+        if self.current_mana > self.max_mana {
+            self.current_mana = self.max_mana;
         }
     }
 }
@@ -0,0 +1,347 @@
+use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    health_bar::HealthBar,
+    loading::{FontAssets, TextureAssets},
+    stats::Stats,
+    GameState,
+};
+
+/// Poison can stack up to this many times.
+pub const MAX_POISON_STACKS: u32 = 5;
+/// Slow can not take away more speed than this.
+const MAX_SLOW: f32 = 0.9;
+
+const STATUS_ICON_SIZE: f32 = 24.;
+const STATUS_ICON_MARGIN: f32 = 4.;
+
+pub struct StatusEffectsPlugin;
+
+/// This plugin shows icons of active status effects above the health bar. Status effects
+/// themselves are resolved by the battle simulation.
+impl Plugin for StatusEffectsPlugin {
+    fn build(&self, app: &mut App) {
+        app.add_systems(
+            Update,
+            update_status_icons.run_if(in_state(GameState::Playing)),
+        );
+    }
+}
+
+#[derive(Clone, Copy, PartialEq, Eq, Debug, Serialize, Deserialize)]
+pub enum StatusEffectKind {
+    /// Lowers HP regeneration by power for every stack. Applying it again adds a stack and
+    /// refreshes the duration.
+    Poison,
+    /// Cancels positive HP regeneration and lowers it by power. The strongest burn is kept and
+    /// applying it again refreshes the duration.
+    Burn,
+    /// Stunned participants neither attack nor cast. Applying it again keeps the longer duration.
+    Stun,
+    /// Lowers speed by power as a fraction. The strongest slow is kept and applying it again
+    /// refreshes the duration.
+    Slow,
+    /// Lowers HP regeneration by power. Every application bleeds on its own.
+    Bleed,
+}
+
+#[derive(Clone, Copy, PartialEq, Debug, Serialize, Deserialize)]
+pub struct StatusEffect {
+    pub kind: StatusEffectKind,
+    pub power: f32,
+    /// Remaining duration in seconds.
+    pub duration: f32,
+    pub stacks: u32,
+}
+
+impl StatusEffect {
+    pub fn new(kind: StatusEffectKind, power: f32, duration: f32) -> Self {
+        Self {
+            kind,
+            power,
+            duration,
+            stacks: 1,
+        }
+    }
+}
+
+/// Status effects active on a participant.
+#[derive(Component, Clone, Default, PartialEq, Debug)]
+pub struct StatusEffects(pub Vec<StatusEffect>);
+
+impl StatusEffects {
+    pub fn apply(&mut self, effect: StatusEffect) {
+        let existing = self
+            .0
+            .iter_mut()
+            .find(|existing| existing.kind == effect.kind);
+
+        match (effect.kind, existing) {
+            (StatusEffectKind::Bleed, _) | (_, None) => self.0.push(effect),
+            (StatusEffectKind::Poison, Some(existing)) => {
+                existing.stacks = (existing.stacks + effect.stacks).min(MAX_POISON_STACKS);
+                existing.power = existing.power.max(effect.power);
+                existing.duration = existing.duration.max(effect.duration);
+            }
+            (StatusEffectKind::Burn | StatusEffectKind::Slow, Some(existing)) => {
+                existing.power = existing.power.max(effect.power);
+                existing.duration = existing.duration.max(effect.duration);
+            }
+            (StatusEffectKind::Stun, Some(existing)) => {
+                existing.duration = existing.duration.max(effect.duration);
+            }
+        }
+    }
+
+    /// Count down durations, returns true if any effect expired.
+    pub fn tick(&mut self, delta: f32) -> bool {
+        let count = self.0.len();
+
+        for effect in self.0.iter_mut() {
+            effect.duration -= delta;
+        }
+        self.0.retain(|effect| effect.duration > 0.);
+
+        self.0.len() != count
+    }
+
+    pub fn is_stunned(&self) -> bool {
+        self.0
+            .iter()
+            .any(|effect| effect.kind == StatusEffectKind::Stun)
+    }
+
+    /// Stats changed by the active effects.
+    pub fn modify(&self, stats: &Stats) -> Stats {
+        let mut stats = stats.clone();
+
+        for effect in self.0.iter() {
+            match effect.kind {
+                StatusEffectKind::Poison | StatusEffectKind::Bleed => {
+                    stats.hp_regeneration -= effect.power * effect.stacks as f32;
+                }
+                StatusEffectKind::Burn => {
+                    stats.hp_regeneration = stats.hp_regeneration.min(0.) - effect.power;
+                }
+                StatusEffectKind::Slow => stats.speed *= 1. - effect.power.min(MAX_SLOW),
+                StatusEffectKind::Stun => {}
+            }
+        }
+
+        stats
+    }
+
+    /// Number of stacks or separate applications of every active kind, in order of application.
+    fn counts(&self) -> Vec<(StatusEffectKind, u32)> {
+        let mut counts: Vec<(StatusEffectKind, u32)> = Vec::new();
+
+        for effect in self.0.iter() {
+            if let Some((_, count)) = counts.iter_mut().find(|(kind, _)| *kind == effect.kind) {
+                *count += effect.stacks;
+            } else {
+                counts.push((effect.kind, effect.stacks));
+            }
+        }
+
+        counts
+    }
+}
+
+pub fn status_effect_icon(textures: &TextureAssets, kind: StatusEffectKind) -> Handle<Image> {
+    match kind {
+        StatusEffectKind::Poison => textures.poison_icon.clone(),
+        StatusEffectKind::Burn => textures.burn_icon.clone(),
+        StatusEffectKind::Stun => textures.stun_icon.clone(),
+        StatusEffectKind::Slow => textures.slow_icon.clone(),
+        StatusEffectKind::Bleed => textures.bleed_icon.clone(),
+    }
+}
+
+fn status_effect_color(kind: StatusEffectKind) -> Color {
+    match kind {
+        StatusEffectKind::Poison => Color::LIME_GREEN,
+        StatusEffectKind::Burn => Color::ORANGE,
+        StatusEffectKind::Stun => Color::YELLOW,
+        StatusEffectKind::Slow => Color::CYAN,
+        StatusEffectKind::Bleed => Color::CRIMSON,
+    }
+}
+
+/// Marks the row of status icons above a health bar.
+#[derive(Component)]
+struct StatusIcons;
+
+fn update_status_icons(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    fonts: Res<FontAssets>,
+    query: Query<(&HealthBar, &StatusEffects), Or<(Changed<StatusEffects>, Changed<HealthBar>)>>,
+    children_query: Query<&Children>,
+    status_icons_query: Query<(), With<StatusIcons>>,
+) {
+    for (health_bar, status_effects) in query.iter() {
+        let Some(health_bar_entity) = health_bar.health_bar_background_entity else {
+            continue;
+        };
+
+        // recreate the whole row
+        if let Ok(children) = children_query.get(health_bar_entity) {
+            for &child in children.iter() {
+                if status_icons_query.get(child).is_ok() {
+                    commands.entity(child).despawn_recursive();
+                }
+            }
+        }
+
+        let y = health_bar.height / 2. + STATUS_ICON_MARGIN + STATUS_ICON_SIZE / 2.;
+        let left_x = -health_bar.width / 2. + STATUS_ICON_SIZE / 2.;
+
+        let row_entity = commands
+            .spawn((
+                SpatialBundle::from_transform(Transform::from_xyz(0., y, 1.)),
+                StatusIcons,
+            ))
+            .with_children(|row| {
+                for (index, (kind, count)) in status_effects.counts().into_iter().enumerate() {
+                    let x = left_x + index as f32 * (STATUS_ICON_SIZE + STATUS_ICON_MARGIN);
+
+                    row.spawn(SpriteBundle {
+                        texture: status_effect_icon(&textures, kind),
+                        sprite: Sprite {
+                            color: status_effect_color(kind),
+                            custom_size: Some(Vec2::splat(STATUS_ICON_SIZE)),
+                            ..Default::default()
+                        },
+                        transform: Transform::from_xyz(x, 0., 0.),
+                        ..Default::default()
+                    });
+
+                    if count > 1 {
+                        row.spawn(Text2dBundle {
+                            text: Text::from_section(
+                                count.to_string(),
+                                TextStyle {
+                                    color: Color::WHITE,
+                                    font: fonts.quantity_numbers.clone(),
+                                    font_size: STATUS_ICON_SIZE * 0.75,
+                                },
+                            ),
+                            text_anchor: bevy::sprite::Anchor::BottomRight,
+                            transform: Transform::from_xyz(
+                                x + STATUS_ICON_SIZE / 2.,
+                                -STATUS_ICON_SIZE / 2.,
+                                1.,
+                            ),
+                            ..Default::default()
+                        });
+                    }
+                }
+            })
+            .id();
+
+        commands
+            .entity(health_bar_entity)
+            .push_children(&[row_entity]);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn poison_stacks_up_to_the_limit() {
+        let mut effects = StatusEffects::default();
+        for _ in 0..MAX_POISON_STACKS + 2 {
+            effects.apply(StatusEffect::new(StatusEffectKind::Poison, 2., 3.));
+        }
+
+        assert_eq!(effects.0.len(), 1);
+        assert_eq!(effects.0[0].stacks, MAX_POISON_STACKS);
+
+        let stats = effects.modify(&Stats::default());
+        assert_eq!(stats.hp_regeneration, -2. * MAX_POISON_STACKS as f32);
+    }
+
+    #[test]
+    fn poison_refreshes_duration() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusEffect::new(StatusEffectKind::Poison, 1., 3.));
+        effects.tick(2.);
+        effects.apply(StatusEffect::new(StatusEffectKind::Poison, 1., 3.));
+
+        assert_eq!(effects.0[0].duration, 3.);
+        assert_eq!(effects.0[0].stacks, 2);
+    }
+
+    #[test]
+    fn burn_and_slow_keep_the_strongest() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusEffect::new(StatusEffectKind::Burn, 5., 2.));
+        effects.apply(StatusEffect::new(StatusEffectKind::Burn, 3., 4.));
+        effects.apply(StatusEffect::new(StatusEffectKind::Slow, 0.5, 1.));
+        effects.apply(StatusEffect::new(StatusEffectKind::Slow, 0.2, 1.));
+
+        assert_eq!(effects.0.len(), 2);
+        assert_eq!(effects.0[0].power, 5.);
+        assert_eq!(effects.0[0].duration, 4.);
+        assert_eq!(effects.0[0].stacks, 1);
+        assert_eq!(effects.0[1].power, 0.5);
+
+        let stats = effects.modify(&Stats {
+            hp_regeneration: 2.,
+            speed: 2.,
+            ..Default::default()
+        });
+        assert_eq!(stats.hp_regeneration, -5.);
+        assert_eq!(stats.speed, 1.);
+    }
+
+    #[test]
+    fn stun_keeps_the_longer_duration() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusEffect::new(StatusEffectKind::Stun, 0., 2.));
+        effects.apply(StatusEffect::new(StatusEffectKind::Stun, 0., 1.));
+
+        assert_eq!(effects.0.len(), 1);
+        assert_eq!(effects.0[0].duration, 2.);
+        assert!(effects.is_stunned());
+    }
+
+    #[test]
+    fn bleeds_are_independent() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusEffect::new(StatusEffectKind::Bleed, 1., 1.));
+        effects.apply(StatusEffect::new(StatusEffectKind::Bleed, 2., 3.));
+
+        assert_eq!(effects.0.len(), 2);
+        assert_eq!(effects.modify(&Stats::default()).hp_regeneration, -3.);
+
+        // the first bleed expires on its own
+        assert!(effects.tick(1.5));
+        assert_eq!(effects.0.len(), 1);
+        assert_eq!(effects.modify(&Stats::default()).hp_regeneration, -2.);
+    }
+
+    #[test]
+    fn effects_expire() {
+        let mut effects = StatusEffects::default();
+        effects.apply(StatusEffect::new(StatusEffectKind::Stun, 0., 1.));
+        effects.apply(StatusEffect::new(StatusEffectKind::Slow, 0.5, 2.));
+
+        assert!(!effects.tick(0.5));
+        assert!(effects.is_stunned());
+
+        assert!(effects.tick(0.5));
+        assert!(!effects.is_stunned());
+        assert_eq!(effects.0.len(), 1);
+
+        assert!(effects.tick(1.));
+        assert!(effects.0.is_empty());
+        assert_eq!(
+            effects.modify(&Stats::default()).speed,
+            Stats::default().speed
+        );
+    }
+}
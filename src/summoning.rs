@@ -1,28 +1,46 @@
 use crate::{
-    health_bar::HealthBar,
+    balance::{Balance, StatsBalance},
+    codex::CodexState,
+    experience::{LevelUpChoice, MinionLevel},
+    formation::FormationSlot,
     loading::{FontAssets, TextureAssets},
-    minions::{Minion, MAX_MINION_COUNT, MINION_SIZE},
+    minions::{spawn_minion, spawn_stats_window, Minion, MAX_MINION_COUNT},
     mouse_control::{update_clickables, Clickable},
+    rewards::PendingRewards,
+    sacrifice::SacrificeConfirmation,
+    shop::{spawn_gold_counter, Gold},
     statistics::Statistics,
-    stats::{
-        Stats, MINION_DMG_BASE, MINION_DMG_INC, MINION_HP_BASE, MINION_HP_INC,
-        MINION_HP_REGEN_BASE, MINION_HP_REGEN_INC, MINION_SPEED_BASE, MINION_SPEED_INC,
-    },
+    stats::{AbilityKind, StatKind, Stats, TargetingPolicy},
+    status_effects::StatusEffectKind,
     utils::num_to_roman,
     BattleCount, GameScreen, GameState,
 };
 use bevy::prelude::*;
+use serde::{Deserialize, Serialize};
 
 const INVENTORY_POS: Vec3 = Vec3::new(-1920. / 4. - 128., 1080. / 2. - 64., 0.);
 const INVENTORY_SIZE: Vec2 = Vec2::new(600., 800.);
 const INGREDIENTS_POS: Vec3 = Vec3::new(1920. / 4. + 128., 1080. / 2. - 64., 0.);
 const SUMMONING_CIRCLE_POS: Vec3 = Vec3::new(0., 220., 0.);
+/// Tiers of the previewed minion are shown left in the summoning circle and values of its main
+/// stats right, positions are relative to the circle.
+const SUMMONING_PREVIEW_BOTTOM: Vec3 = Vec3::new(-100., -96., 1.);
+const SUMMONING_PREVIEW_VALUES_POS: Vec3 = Vec3::new(16., 72., 1.);
+const SUMMONING_PREVIEW_ROW_HEIGHT: f32 = 44.;
+const SUMMONING_PREVIEW_ICON_SIZE: f32 = 32.;
+/// Stats listed with their values in the preview.
+const PREVIEW_STATS: [StatKind; 4] = [
+    StatKind::MaxHp,
+    StatKind::Damage,
+    StatKind::Speed,
+    StatKind::HpRegeneration,
+];
+const GOLD_COUNTER_POS: Vec3 = Vec3::new(0., 1080. / 2. - 40., 0.);
 
 pub const MAX_ITEM_COUNT: usize = 10;
+const MAX_ITEM_TIER: u8 = 10;
 const ITEM_CARD_SIZE: Vec2 = Vec2::new(INVENTORY_SIZE.x, INVENTORY_SIZE.y / MAX_ITEM_COUNT as f32);
 
-const MINIONS_Y: f32 = -460.;
-
 pub struct SummoningPlugin;
 
 impl Plugin for SummoningPlugin {
@@ -52,11 +70,18 @@ impl Plugin for SummoningPlugin {
                 (
                     summon_minion,
                     move_to_preparation_screen,
-                    handle_remove_minion,
-                    handle_delete_item,
+                    handle_sell_item,
+                    handle_craft_item,
                     handle_move_item,
+                    update_summoning_preview.after(handle_move_item),
                 )
-                    .run_if(in_state(GameState::Playing).and_then(in_state(GameScreen::Summoning))),
+                    .run_if(
+                        in_state(GameState::Playing)
+                            .and_then(in_state(GameScreen::Summoning))
+                            .and_then(in_state(CodexState::Closed))
+                            .and_then(not(resource_exists::<LevelUpChoice>))
+                            .and_then(not(resource_exists::<SacrificeConfirmation>)),
+                    ),
             )
             .add_systems(
                 PreUpdate,
@@ -67,21 +92,191 @@ impl Plugin for SummoningPlugin {
     }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy)]
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Copy, Debug, Serialize, Deserialize)]
 pub enum SummoningItemType {
     Damage,
     Speed,
     MaxHP,
     HPRegeneration,
+    Armor,
+    CritChance,
+    CritMultiplier,
+    Evasion,
+    AreaHit,
+    Heal,
+    Shield,
+    Stun,
+    TargetLowestHp,
+    TargetHighestDamage,
+    Taunt,
+    Poison,
+    Burn,
+    Slow,
+    Bleed,
+    HealingHerb,
+}
+
+impl SummoningItemType {
+    pub const ALL: [SummoningItemType; 20] = [
+        SummoningItemType::Damage,
+        SummoningItemType::Speed,
+        SummoningItemType::MaxHP,
+        SummoningItemType::HPRegeneration,
+        SummoningItemType::Armor,
+        SummoningItemType::CritChance,
+        SummoningItemType::CritMultiplier,
+        SummoningItemType::Evasion,
+        SummoningItemType::AreaHit,
+        SummoningItemType::Heal,
+        SummoningItemType::Shield,
+        SummoningItemType::Stun,
+        SummoningItemType::TargetLowestHp,
+        SummoningItemType::TargetHighestDamage,
+        SummoningItemType::Taunt,
+        SummoningItemType::Poison,
+        SummoningItemType::Burn,
+        SummoningItemType::Slow,
+        SummoningItemType::Bleed,
+        SummoningItemType::HealingHerb,
+    ];
+
+    /// Stat raised by the item.
+    pub fn stat_kind(&self) -> Option<StatKind> {
+        match self {
+            SummoningItemType::Damage => Some(StatKind::Damage),
+            SummoningItemType::Speed => Some(StatKind::Speed),
+            SummoningItemType::MaxHP => Some(StatKind::MaxHp),
+            SummoningItemType::HPRegeneration => Some(StatKind::HpRegeneration),
+            SummoningItemType::Armor => Some(StatKind::Armor),
+            SummoningItemType::CritChance => Some(StatKind::CritChance),
+            SummoningItemType::CritMultiplier => Some(StatKind::CritMultiplier),
+            SummoningItemType::Evasion => Some(StatKind::Evasion),
+            _ => None,
+        }
+    }
+
+    /// Ability granted by the item, a minion can only have one.
+    pub fn ability_kind(&self) -> Option<AbilityKind> {
+        match self {
+            SummoningItemType::AreaHit => Some(AbilityKind::AreaHit),
+            SummoningItemType::Heal => Some(AbilityKind::Heal),
+            SummoningItemType::Shield => Some(AbilityKind::Shield),
+            SummoningItemType::Stun => Some(AbilityKind::Stun),
+            _ => None,
+        }
+    }
+
+    /// Status effect applied on hit by minions summoned with the item, a minion can only have one.
+    pub fn on_hit_kind(&self) -> Option<StatusEffectKind> {
+        match self {
+            SummoningItemType::Poison => Some(StatusEffectKind::Poison),
+            SummoningItemType::Burn => Some(StatusEffectKind::Burn),
+            SummoningItemType::Slow => Some(StatusEffectKind::Slow),
+            SummoningItemType::Bleed => Some(StatusEffectKind::Bleed),
+            _ => None,
+        }
+    }
+
+    /// Targeting policy granted by the item, a minion can only have one.
+    pub fn targeting_policy(&self) -> Option<TargetingPolicy> {
+        match self {
+            SummoningItemType::TargetLowestHp => Some(TargetingPolicy::LowestHp),
+            SummoningItemType::TargetHighestDamage => Some(TargetingPolicy::HighestDamage),
+            _ => None,
+        }
+    }
+
+    /// Items which can not be summoned together, a minion can only have a single item of each type
+    /// and a single ability, targeting policy and on hit effect.
+    pub fn excludes(&self, other: &SummoningItemType) -> bool {
+        self == other
+            || (self.ability_kind().is_some() && other.ability_kind().is_some())
+            || (self.targeting_policy().is_some() && other.targeting_policy().is_some())
+            || (self.on_hit_kind().is_some() && other.on_hit_kind().is_some())
+    }
+
+    /// Tactic items change behaviour of the minion instead of its stats, so they only have a
+    /// single tier.
+    pub fn is_tactic(&self) -> bool {
+        self.targeting_policy().is_some() || *self == SummoningItemType::Taunt
+    }
+
+    /// Consumable items are used on the summoning screen, they can not be summoning ingredients.
+    pub fn is_consumable(&self) -> bool {
+        *self == SummoningItemType::HealingHerb
+    }
+
+    pub fn icon(&self, textures: &TextureAssets) -> Handle<Image> {
+        match self {
+            SummoningItemType::Damage
+            | SummoningItemType::Speed
+            | SummoningItemType::MaxHP
+            | SummoningItemType::HPRegeneration
+            | SummoningItemType::Armor
+            | SummoningItemType::CritChance
+            | SummoningItemType::CritMultiplier
+            | SummoningItemType::Evasion => stat_icon(textures, self.stat_kind().unwrap()),
+            SummoningItemType::AreaHit => textures.area_hit_icon.clone(),
+            SummoningItemType::Heal => textures.heal_icon.clone(),
+            SummoningItemType::Shield => textures.shield_icon.clone(),
+            SummoningItemType::Stun => textures.stun_icon.clone(),
+            SummoningItemType::TargetLowestHp => textures.target_weakest_icon.clone(),
+            SummoningItemType::TargetHighestDamage => textures.target_strongest_icon.clone(),
+            SummoningItemType::Taunt => textures.taunt_icon.clone(),
+            SummoningItemType::Poison => textures.poison_icon.clone(),
+            SummoningItemType::Burn => textures.burn_icon.clone(),
+            SummoningItemType::Slow => textures.slow_icon.clone(),
+            SummoningItemType::Bleed => textures.bleed_icon.clone(),
+            SummoningItemType::HealingHerb => textures.herb_icon.clone(),
+        }
+    }
 }
 
-#[derive(PartialEq, Eq, PartialOrd, Ord, Clone)]
+pub fn stat_icon(textures: &TextureAssets, stat: StatKind) -> Handle<Image> {
+    match stat {
+        StatKind::Damage => textures.sword_icon.clone(),
+        StatKind::Speed => textures.boot_icon.clone(),
+        StatKind::MaxHp => textures.hearth_icon.clone(),
+        StatKind::HpRegeneration => textures.hp_regeneration_icon.clone(),
+        StatKind::Armor => textures.armor_icon.clone(),
+        StatKind::CritChance => textures.crit_chance_icon.clone(),
+        StatKind::CritMultiplier => textures.crit_multiplier_icon.clone(),
+        StatKind::Evasion => textures.evasion_icon.clone(),
+    }
+}
+
+pub fn ability_icon(textures: &TextureAssets, kind: AbilityKind) -> Handle<Image> {
+    match kind {
+        AbilityKind::AreaHit => textures.area_hit_icon.clone(),
+        AbilityKind::Heal => textures.heal_icon.clone(),
+        AbilityKind::Shield => textures.shield_icon.clone(),
+        AbilityKind::Stun => textures.stun_icon.clone(),
+    }
+}
+
+pub fn targeting_icon(textures: &TextureAssets, targeting: TargetingPolicy) -> Handle<Image> {
+    match targeting {
+        TargetingPolicy::FrontMost => textures.target_front_icon.clone(),
+        TargetingPolicy::LowestHp => textures.target_weakest_icon.clone(),
+        TargetingPolicy::HighestDamage => textures.target_strongest_icon.clone(),
+        TargetingPolicy::Random => textures.target_random_icon.clone(),
+    }
+}
+
+#[derive(PartialEq, Eq, PartialOrd, Ord, Clone, Debug, Serialize, Deserialize)]
 pub struct SummoningItem {
     pub item_type: SummoningItemType,
     pub tier: u8,
     pub quantity: usize,
 }
 
+impl SummoningItem {
+    /// Tactic items and items of the highest tier can not be crafted into anything.
+    fn can_craft(&self, ratio: usize) -> bool {
+        !self.item_type.is_tactic() && self.tier < MAX_ITEM_TIER && self.quantity >= ratio
+    }
+}
+
 #[derive(Component)]
 struct ItemInventory;
 
@@ -97,12 +292,70 @@ struct SummoningCircle;
 #[derive(Component)]
 struct ReadyButton;
 
+#[derive(Component)]
+struct SummoningPreview;
+
 #[derive(Resource, Default)]
 pub struct InventoryItems(pub Vec<SummoningItem>);
 
+impl InventoryItems {
+    /// Stack the item with the same one or put it into a free slot, it is lost when the inventory
+    /// is full. Returns false if the item was lost.
+    pub fn add(&mut self, new_item: &SummoningItem) -> bool {
+        if let Some(item) = self
+            .0
+            .iter_mut()
+            .find(|item| item.item_type == new_item.item_type && item.tier == new_item.tier)
+        {
+            item.quantity += new_item.quantity;
+        } else if self.0.len() < MAX_ITEM_COUNT {
+            self.0.push(new_item.clone());
+        } else {
+            return false;
+        }
+
+        true
+    }
+
+    /// Merge items of the stack into a single item of the next tier. Returns false if the stack
+    /// can not be crafted or the crafted item does not fit into the inventory.
+    pub fn craft(&mut self, index: usize, ratio: usize) -> bool {
+        let item = &mut self.0[index];
+        if !item.can_craft(ratio) {
+            return false;
+        }
+
+        let crafted = SummoningItem {
+            item_type: item.item_type,
+            tier: item.tier + 1,
+            quantity: 1,
+        };
+        item.quantity -= ratio;
+        let removed = (item.quantity == 0).then(|| self.0.remove(index));
+
+        if !self.add(&crafted) {
+            // no free slot for the crafted item, give the used items back
+            match removed {
+                Some(mut item) => {
+                    item.quantity = ratio;
+                    self.0.insert(index, item);
+                }
+                None => self.0[index].quantity += ratio,
+            }
+            return false;
+        }
+
+        true
+    }
+}
+
 #[derive(Resource, Default)]
 struct IngredientItems(Vec<SummoningItem>);
 
+/// Items a minion was summoned from, a part of them is refunded when it is sacrificed.
+#[derive(Component, Serialize, Deserialize, Clone, Default)]
+pub struct Ingredients(pub Vec<SummoningItem>);
+
 #[derive(Component)]
 struct SummoningScreenEntity;
 
@@ -110,11 +363,12 @@ struct SummoningScreenEntity;
 struct InInventoryItem;
 
 #[derive(Resource, Default)]
-struct ShouldRecreateItemCards {
-    should_recreate_inventory_items: bool,
+pub struct ShouldRecreateItemCards {
+    pub should_recreate_inventory_items: bool,
     should_recreate_ingredient_items: bool,
 }
 
+#[allow(clippy::too_many_arguments)]
 fn spawn_item_card(
     commands: &mut Commands,
     textures: &Res<TextureAssets>,
@@ -122,6 +376,8 @@ fn spawn_item_card(
     index: usize,
     item: &SummoningItem,
     is_ingredient: bool,
+    can_craft: bool,
+    increase: Option<String>,
 ) -> Entity {
     let card_entity = commands
         .spawn((
@@ -173,11 +429,39 @@ fn spawn_item_card(
             .push_children(&[quantity_entity]);
     }
 
+    // ingredients show how much they raise their stat
+    if let Some(increase) = increase {
+        let increase_entity = commands
+            .spawn(Text2dBundle {
+                text: Text::from_section(
+                    increase,
+                    TextStyle {
+                        color: Color::LIME_GREEN,
+                        font: fonts.quantity_numbers.clone(),
+                        font_size: ITEM_CARD_SIZE.y * 0.8,
+                    },
+                ),
+                text_anchor: bevy::sprite::Anchor::CenterLeft,
+                transform: Transform::from_xyz(-ITEM_CARD_SIZE.x / 2. + 16., 0., 3.),
+                ..Default::default()
+            })
+            .id();
+
+        commands
+            .entity(card_entity)
+            .push_children(&[increase_entity]);
+    }
+
+    // craftable items are marked by a green circle
     let base_stat_icon_entity = commands
         .spawn(SpriteBundle {
             texture: textures.circle.clone(),
             sprite: Sprite {
-                color: Color::YELLOW,
+                color: if can_craft {
+                    Color::LIME_GREEN
+                } else {
+                    Color::YELLOW
+                },
                 custom_size: Some(Vec2::splat(ITEM_CARD_SIZE.y * 0.5)),
                 anchor: bevy::sprite::Anchor::CenterRight,
                 ..Default::default()
@@ -208,12 +492,7 @@ fn spawn_item_card(
 
     let effect_icon_entity = commands
         .spawn(SpriteBundle {
-            texture: match item.item_type {
-                SummoningItemType::Damage => textures.sword_icon.clone(),
-                SummoningItemType::Speed => textures.boot_icon.clone(),
-                SummoningItemType::MaxHP => textures.hearth_icon.clone(),
-                SummoningItemType::HPRegeneration => textures.hp_regeneration_icon.clone(),
-            },
+            texture: item.item_type.icon(textures),
             sprite: Sprite {
                 color: Color::CYAN,
                 custom_size: Some(Vec2::splat(ITEM_CARD_SIZE.y * 0.7)),
@@ -238,6 +517,7 @@ fn spawn_inventory_cards(
     mut commands: Commands,
     textures: Res<TextureAssets>,
     fonts: Res<FontAssets>,
+    balance: Res<Balance>,
     items: Res<InventoryItems>,
     mut recreate_items: ResMut<ShouldRecreateItemCards>,
     inventory: Query<(Entity, Option<&Children>), With<ItemInventory>>,
@@ -248,15 +528,25 @@ fn spawn_inventory_cards(
 
     let (inventory_entity, children) = inventory.single();
 
-    if children.is_some() {
-        for &child in children.unwrap().iter() {
+    if let Some(children) = children {
+        for &child in children.iter() {
             commands.entity(child).despawn_recursive();
         }
         commands.entity(inventory_entity).clear_children();
     }
 
     for (index, item) in items.0.iter().enumerate() {
-        let card_entity = spawn_item_card(&mut commands, &textures, &fonts, index, item, false);
+        let can_craft = item.can_craft(balance.crafting.ratio(item.tier));
+        let card_entity = spawn_item_card(
+            &mut commands,
+            &textures,
+            &fonts,
+            index,
+            item,
+            false,
+            can_craft,
+            None,
+        );
         commands
             .entity(inventory_entity)
             .push_children(&[card_entity]);
@@ -269,6 +559,7 @@ fn spawn_ingredient_cards(
     mut commands: Commands,
     textures: Res<TextureAssets>,
     fonts: Res<FontAssets>,
+    balance: Res<Balance>,
     items: Res<IngredientItems>,
     mut recreate_items: ResMut<ShouldRecreateItemCards>,
     inventory: Query<(Entity, Option<&Children>), With<IngredientInventory>>,
@@ -279,15 +570,26 @@ fn spawn_ingredient_cards(
 
     let (inventory_entity, children) = inventory.single();
 
-    if children.is_some() {
-        for &child in children.unwrap().iter() {
+    if let Some(children) = children {
+        for &child in children.iter() {
             commands.entity(child).despawn_recursive();
         }
         commands.entity(inventory_entity).clear_children();
     }
 
     for (index, item) in items.0.iter().enumerate() {
-        let card_entity = spawn_item_card(&mut commands, &textures, &fonts, index, item, true);
+        let increase = ingredient_increase(item, &balance)
+            .map(|(stat, increase)| format!("+{}", format_stat(stat, increase)));
+        let card_entity = spawn_item_card(
+            &mut commands,
+            &textures,
+            &fonts,
+            index,
+            item,
+            true,
+            false,
+            increase,
+        );
         commands
             .entity(inventory_entity)
             .push_children(&[card_entity]);
@@ -300,6 +602,7 @@ fn spawn_inventories_and_circle(
     mut commands: Commands,
     textures: Res<TextureAssets>,
     fonts: Res<FontAssets>,
+    gold: Res<Gold>,
 ) {
     // background
     commands.spawn((
@@ -358,6 +661,10 @@ fn spawn_inventories_and_circle(
         SummoningScreenEntity,
     ));
 
+    let gold_counter =
+        spawn_gold_counter(&mut commands, &textures, &fonts, &gold, GOLD_COUNTER_POS);
+    commands.entity(gold_counter).insert(SummoningScreenEntity);
+
     // Summoning circle
     commands.spawn((
         SpriteBundle {
@@ -411,66 +718,222 @@ fn spawn_inventories_and_circle(
         });
 }
 
+/// Stats of a minion summoned from the ingredients, including the bonuses of a matching recipe.
+pub fn minion_stats(ingredients: &[SummoningItem], balance: &Balance) -> Stats {
+    let mut stats = balance.minion.stats(&[]);
+    for item in ingredients.iter() {
+        add_ingredient(&mut stats, item, balance);
+    }
+
+    if let Some(recipe) = balance.recipe(ingredients) {
+        for &(stat, tier) in recipe.stat_bonuses.iter() {
+            add_stat_tiers(&mut stats, &balance.minion, stat, tier);
+        }
+        if stats.on_hit.is_none() {
+            stats.on_hit = recipe
+                .on_hit
+                .and_then(|(kind, tier)| balance.status_effects.effect(kind, tier));
+        }
+        stats.recipe = Some(recipe.kind);
+    }
+
+    stats
+}
+
+/// Stat raised by a single ingredient and by how much, other than stat items raise none.
+fn ingredient_increase(item: &SummoningItem, balance: &Balance) -> Option<(StatKind, f32)> {
+    let stat = item.item_type.stat_kind()?;
+    let base = balance.minion.stats(&[]);
+    let mut stats = base.clone();
+    add_ingredient(&mut stats, item, balance);
+
+    Some((stat, stat.get(&stats) - stat.get(&base)))
+}
+
+fn add_ingredient(stats: &mut Stats, item: &SummoningItem, balance: &Balance) {
+    let abilities = &balance.abilities;
+
+    match item.item_type {
+        SummoningItemType::Damage
+        | SummoningItemType::Speed
+        | SummoningItemType::MaxHP
+        | SummoningItemType::HPRegeneration
+        | SummoningItemType::Armor
+        | SummoningItemType::CritChance
+        | SummoningItemType::CritMultiplier
+        | SummoningItemType::Evasion => {
+            let stat = item.item_type.stat_kind().unwrap();
+            add_stat_tiers(stats, &balance.minion, stat, item.tier);
+        }
+        SummoningItemType::AreaHit
+        | SummoningItemType::Heal
+        | SummoningItemType::Shield
+        | SummoningItemType::Stun => {
+            let kind = item.item_type.ability_kind().unwrap();
+            stats.ability = Some(abilities.ability(kind, item.tier));
+            stats.max_mana = abilities.mana;
+            stats.mana_regeneration = abilities.mana_regeneration;
+        }
+        SummoningItemType::TargetLowestHp | SummoningItemType::TargetHighestDamage => {
+            stats.targeting = item.item_type.targeting_policy().unwrap();
+        }
+        SummoningItemType::Taunt => stats.taunt = true,
+        SummoningItemType::Poison
+        | SummoningItemType::Burn
+        | SummoningItemType::Slow
+        | SummoningItemType::Bleed => {
+            let kind = item.item_type.on_hit_kind().unwrap();
+            stats.on_hit = balance.status_effects.effect(kind, item.tier);
+        }
+        SummoningItemType::HealingHerb => {}
+    }
+}
+
+pub fn add_stat_tiers(stats: &mut Stats, balance: &StatsBalance, stat: StatKind, tier: u8) {
+    let (_, inc) = balance.base_and_inc(stat);
+    *stat.get_mut(stats) += inc * tier as f32;
+    if stat == StatKind::MaxHp {
+        stats.current_hp += inc * tier as f32;
+    }
+}
+
+/// Value of a stat rounded for display, chances are shown in percent.
+pub fn format_stat(stat: StatKind, value: f32) -> String {
+    match stat {
+        StatKind::Damage | StatKind::MaxHp | StatKind::Armor => format!("{value:.0}"),
+        StatKind::HpRegeneration => format!("{value:.1}"),
+        StatKind::Speed | StatKind::CritMultiplier => format!("{value:.2}"),
+        StatKind::CritChance | StatKind::Evasion => format!("{:.0}%", value * 100.),
+    }
+}
+
+/// Stats of the minion which would be summoned from the current ingredients are shown in the
+/// summoning circle, main stats with their values and how much the ingredients raised them.
+fn update_summoning_preview(
+    mut commands: Commands,
+    textures: Res<TextureAssets>,
+    fonts: Res<FontAssets>,
+    balance: Res<Balance>,
+    ingredient_items: Res<IngredientItems>,
+    circle_query: Query<Entity, With<SummoningCircle>>,
+    preview_query: Query<Entity, With<SummoningPreview>>,
+) {
+    if !ingredient_items.is_changed() {
+        return;
+    }
+
+    for entity in preview_query.iter() {
+        commands.entity(entity).despawn_recursive();
+    }
+
+    if ingredient_items.0.is_empty() {
+        return;
+    }
+
+    let circle_entity = circle_query.single();
+    let base = balance.minion.stats(&[]);
+    let stats = minion_stats(&ingredient_items.0, &balance);
+
+    let tiers_entity = spawn_stats_window(
+        &mut commands,
+        &textures,
+        &fonts,
+        &balance,
+        &stats,
+        None,
+        &[],
+        SUMMONING_PREVIEW_BOTTOM,
+    );
+    commands
+        .entity(tiers_entity)
+        .insert(SummoningPreview)
+        .set_parent(circle_entity);
+
+    commands
+        .spawn((
+            SpatialBundle::from_transform(Transform::from_translation(
+                SUMMONING_PREVIEW_VALUES_POS,
+            )),
+            SummoningPreview,
+        ))
+        .with_children(|parent| {
+            for (row, stat) in PREVIEW_STATS.into_iter().enumerate() {
+                let y = -SUMMONING_PREVIEW_ROW_HEIGHT * row as f32;
+                let value = stat.get(&stats);
+                let increase = value - stat.get(&base);
+
+                parent.spawn(SpriteBundle {
+                    texture: stat_icon(&textures, stat),
+                    sprite: Sprite {
+                        color: Color::CYAN,
+                        custom_size: Some(Vec2::splat(SUMMONING_PREVIEW_ICON_SIZE)),
+                        anchor: bevy::sprite::Anchor::CenterLeft,
+                        ..Default::default()
+                    },
+                    transform: Transform::from_xyz(0., y, 0.),
+                    ..Default::default()
+                });
+
+                let style = |color: Color| TextStyle {
+                    color,
+                    font: fonts.quantity_numbers.clone(),
+                    font_size: SUMMONING_PREVIEW_ICON_SIZE,
+                };
+                let mut sections = vec![TextSection::new(
+                    format_stat(stat, value),
+                    style(Color::WHITE),
+                )];
+                if increase > 0. {
+                    sections.push(TextSection::new(
+                        format!(" +{}", format_stat(stat, increase)),
+                        style(Color::LIME_GREEN),
+                    ));
+                }
+                parent.spawn(Text2dBundle {
+                    text: Text::from_sections(sections),
+                    text_anchor: bevy::sprite::Anchor::CenterLeft,
+                    transform: Transform::from_xyz(SUMMONING_PREVIEW_ICON_SIZE + 8., y, 0.),
+                    ..Default::default()
+                });
+            }
+        })
+        .set_parent(circle_entity);
+}
+
+#[allow(clippy::too_many_arguments)]
 fn summon_minion(
     mut commands: Commands,
     textures: Res<TextureAssets>,
     mut ingredient_items: ResMut<IngredientItems>,
     mut should_recreate_item_cards: ResMut<ShouldRecreateItemCards>,
     mut statistics: ResMut<Statistics>,
+    balance: Res<Balance>,
     summoning_circle_query: Query<&Clickable, With<SummoningCircle>>,
-    minion_query: Query<(), With<Minion>>,
+    minion_query: Query<&FormationSlot, With<Minion>>,
 ) {
     let clickable = summoning_circle_query.single();
-    let minion_count = minion_query.iter().count();
+    let taken_slots: Vec<_> = minion_query.iter().copied().collect();
 
     let is_clicked = clickable.just_left_clicked;
-    let free_slot_exist = minion_count < MAX_MINION_COUNT;
-    let at_least_one_ingredient_used = ingredient_items.0.first().is_some();
+    let free_slot_exist = taken_slots.len() < MAX_MINION_COUNT;
+    let at_least_one_ingredient_used = !ingredient_items.0.is_empty();
     if !is_clicked || !free_slot_exist || !at_least_one_ingredient_used {
         return;
     }
-
-    let mut stats = Stats {
-        current_hp: MINION_HP_BASE,
-        max_hp: MINION_HP_BASE,
-        speed: MINION_SPEED_BASE,
-        damage: MINION_DMG_BASE,
-        hp_regeneration: MINION_HP_REGEN_BASE,
-        ..Default::default()
+    let Some(slot) = FormationSlot::first_free(&taken_slots) else {
+        return;
     };
-    for item in ingredient_items.0.iter() {
-        match item.item_type {
-            SummoningItemType::MaxHP => {
-                stats.max_hp += MINION_HP_INC * item.tier as f32;
-                stats.current_hp += MINION_HP_INC * item.tier as f32;
-            }
-            SummoningItemType::HPRegeneration => {
-                stats.hp_regeneration += MINION_HP_REGEN_INC * item.tier as f32
-            }
-            SummoningItemType::Speed => stats.speed += MINION_SPEED_INC * item.tier as f32,
-            SummoningItemType::Damage => stats.damage += MINION_DMG_INC * item.tier as f32,
-        }
-    }
-    let stats = stats;
 
-    commands.spawn((
-        SpriteBundle {
-            texture: textures.minion.clone(),
-            sprite: Sprite {
-                custom_size: Some(Vec2::splat(MINION_SIZE)),
-                ..Default::default()
-            },
-            transform: Transform::from_xyz(
-                1920. / (MAX_MINION_COUNT as f32 + 2.) * (minion_count as f32 + 1.) - 1920. / 2.,
-                MINIONS_Y,
-                0.,
-            ),
+    let stats = minion_stats(&ingredient_items.0, &balance);
+
+    let minion_entity = spawn_minion(&mut commands, &textures, stats, slot);
+    commands.entity(minion_entity).insert((
+        Clickable::default(),
+        Ingredients(ingredient_items.0.clone()),
+        MinionLevel {
+            summon_index: statistics.summoned_minions,
             ..Default::default()
         },
-        Minion,
-        stats,
-        HealthBar::default(),
-        Clickable::default(),
     ));
 
     statistics.summoned_minions += 1;
@@ -478,9 +941,12 @@ fn summon_minion(
     should_recreate_item_cards.should_recreate_ingredient_items = true;
 }
 
+/// Unused ingredients go back to the inventory, those which do not fit are offered on the rewards
+/// screen instead of being lost.
 fn move_to_preparation_screen(
     mut inventory_items: ResMut<InventoryItems>,
     mut ingredient_items: ResMut<IngredientItems>,
+    mut pending_rewards: ResMut<PendingRewards>,
     mut next_screen: ResMut<NextState<GameScreen>>,
     button_query: Query<&Clickable, With<ReadyButton>>,
     minion_query: Query<(), With<Minion>>,
@@ -491,18 +957,21 @@ fn move_to_preparation_screen(
         return;
     }
 
-    next_screen.set(GameScreen::Planning);
-
-    for ingredient_item in ingredient_items.0.iter() {
-        if let Some(item) = inventory_items.0.iter_mut().find(|item| {
-            item.item_type == ingredient_item.item_type && item.tier == ingredient_item.tier
-        }) {
-            item.quantity += ingredient_item.quantity;
-        } else {
-            inventory_items.0.push(ingredient_item.clone());
-        }
+    let overflow: Vec<_> = ingredient_items
+        .0
+        .drain(..)
+        .filter(|ingredient| !inventory_items.add(ingredient))
+        .collect();
+    if overflow.is_empty() {
+        next_screen.set(GameScreen::Planning);
+    } else {
+        *pending_rewards = PendingRewards {
+            title: "INGREDIENTS",
+            items: overflow,
+            ..Default::default()
+        };
+        next_screen.set(GameScreen::Rewards);
     }
-    ingredient_items.0.clear();
 }
 
 fn clean_summoning_screen(
@@ -514,22 +983,20 @@ fn clean_summoning_screen(
     }
 }
 
-fn reposition_minions(mut query: Query<&mut Transform, With<Minion>>) {
-    for (index, mut transform) in query.iter_mut().enumerate() {
-        transform.translation = Vec3::new(
-            1920. / (MAX_MINION_COUNT as f32 + 2.) * (index as f32 + 1.) - 1920. / 2.,
-            MINIONS_Y,
-            0.,
-        );
+/// Put minions back into their formation slots at the bottom of the screen.
+pub fn reposition_minions(mut query: Query<(&mut Transform, &FormationSlot), With<Minion>>) {
+    for (mut transform, slot) in query.iter_mut() {
+        transform.translation = slot.position();
     }
 }
 
-fn spawn_items(
+pub fn spawn_items(
     mut inventory_items: ResMut<InventoryItems>,
     mut recreate_items: ResMut<ShouldRecreateItemCards>,
     battle_count: Res<BattleCount>,
 ) {
-    if battle_count.0 == 1 {
+    // resumed runs already have their starting items
+    if battle_count.0 == 1 && inventory_items.0.is_empty() {
         inventory_items.0.push(SummoningItem {
             item_type: SummoningItemType::Damage,
             tier: 1,
@@ -562,84 +1029,115 @@ fn unmake_minions_clickable(mut commands: Commands, query: Query<Entity, With<Mi
     }
 }
 
-fn handle_remove_minion(
+/// Sell the whole stack of items.
+fn handle_sell_item(
     mut commands: Commands,
-    mut query: Query<(Entity, &mut Transform, &Clickable), With<Minion>>,
+    balance: Res<Balance>,
+    mut gold: ResMut<Gold>,
+    mut inventory_items: ResMut<InventoryItems>,
+    mut recreate_items: ResMut<ShouldRecreateItemCards>,
+    query: Query<(Entity, &Clickable, &ItemCard), With<InInventoryItem>>,
 ) {
-    let mut removed = None;
-
-    for (entity, _, clickable) in query.iter() {
+    for (entity, clickable, &ItemCard(index)) in query.iter() {
         if !clickable.just_right_clicked {
             continue;
         }
 
+        let item = inventory_items.0.remove(index);
+        gold.0 += balance.shop.sell_price(&item);
         commands.entity(entity).despawn_recursive();
-        removed = Some(entity);
-    }
-
-    if removed.is_none() {
-        return;
+        recreate_items.should_recreate_inventory_items = true;
     }
+}
 
-    for (index, (_, mut transform, _)) in query
-        .iter_mut()
-        .filter(|&(e, _, _)| e != removed.unwrap())
-        .enumerate()
-    {
-        transform.translation = Vec3::new(
-            1920. / (MAX_MINION_COUNT as f32 + 2.) * (index as f32 + 1.) - 1920. / 2.,
-            MINIONS_Y,
-            0.,
-        );
-    }
+/// Shift clicked items are crafted into the next tier instead of being moved.
+fn is_crafting(keyboard_input: &ButtonInput<KeyCode>) -> bool {
+    keyboard_input.any_pressed([KeyCode::ShiftLeft, KeyCode::ShiftRight])
 }
 
-fn handle_delete_item(
-    mut commands: Commands,
+fn handle_craft_item(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    balance: Res<Balance>,
     mut inventory_items: ResMut<InventoryItems>,
     mut recreate_items: ResMut<ShouldRecreateItemCards>,
-    query: Query<(Entity, &Clickable, &ItemCard), With<InInventoryItem>>,
+    query: Query<(&Clickable, &ItemCard), With<InInventoryItem>>,
 ) {
-    for (entity, clickable, &ItemCard(index)) in query.iter() {
-        if !clickable.just_right_clicked {
+    if !is_crafting(&keyboard_input) {
+        return;
+    }
+
+    for (clickable, &ItemCard(index)) in query.iter() {
+        if !clickable.just_left_clicked {
             continue;
         }
 
-        inventory_items.0.remove(index);
-        commands.entity(entity).despawn_recursive();
-        recreate_items.should_recreate_inventory_items = true;
+        let Some(item) = inventory_items.0.get(index) else {
+            continue;
+        };
+
+        let ratio = balance.crafting.ratio(item.tier);
+        if inventory_items.craft(index, ratio) {
+            recreate_items.should_recreate_inventory_items = true;
+        }
     }
 }
 
+#[allow(clippy::too_many_arguments)]
 fn handle_move_item(
+    keyboard_input: Res<ButtonInput<KeyCode>>,
+    balance: Res<Balance>,
     mut recreate_items: ResMut<ShouldRecreateItemCards>,
     mut inventory_items: ResMut<InventoryItems>,
     mut ingredient_items: ResMut<IngredientItems>,
     inventory_query: Query<(&Clickable, &ItemCard), With<InInventoryItem>>,
     ingredient_query: Query<(&Clickable, &ItemCard), Without<InInventoryItem>>,
+    mut minion_query: Query<&mut Stats, With<Minion>>,
 ) {
-    // handle move of inventory items
+    // handle move of inventory items, consumables are used instead
     for (clickable, &ItemCard(index)) in inventory_query.iter() {
-        if !clickable.just_left_clicked {
+        if !clickable.just_left_clicked || is_crafting(&keyboard_input) {
             continue;
         }
 
-        let item = &mut inventory_items.0[index];
-        let is_duplicate = ingredient_items
-            .0
-            .iter()
-            .any(|ingredient| ingredient.item_type == item.item_type);
-        if !is_duplicate {
-            ingredient_items.0.push(item.clone());
-
-            item.quantity -= 1;
-            if item.quantity == 0 {
-                inventory_items.0.remove(index);
+        let Some(item) = inventory_items.0.get_mut(index) else {
+            continue;
+        };
+
+        if item.item_type.is_consumable() {
+            // healing herbs heal every wounded minion, the herb is kept when nobody needs healing
+            if item.item_type != SummoningItemType::HealingHerb
+                || minion_query
+                    .iter()
+                    .all(|stats| stats.current_hp >= stats.max_hp)
+            {
+                continue;
             }
 
-            recreate_items.should_recreate_inventory_items = true;
+            let heal = balance.recovery.herb_heal(item.tier);
+            for mut stats in minion_query.iter_mut() {
+                stats.heal(heal);
+            }
+        } else {
+            let is_duplicate = ingredient_items
+                .0
+                .iter()
+                .any(|ingredient| ingredient.item_type.excludes(&item.item_type));
+            if is_duplicate {
+                continue;
+            }
+
+            ingredient_items.0.push(SummoningItem {
+                quantity: 1,
+                ..item.clone()
+            });
             recreate_items.should_recreate_ingredient_items = true;
         }
+
+        item.quantity -= 1;
+        if item.quantity == 0 {
+            inventory_items.0.remove(index);
+        }
+        recreate_items.should_recreate_inventory_items = true;
     }
 
     // handle move of ingredient items
@@ -648,18 +1146,350 @@ fn handle_move_item(
             continue;
         }
 
-        let ingredient = ingredient_items.0.remove(index);
-        if let Some(item) = inventory_items
-            .0
-            .iter_mut()
-            .find(|item| item.item_type == ingredient.item_type && item.tier == ingredient.tier)
-        {
-            item.quantity += ingredient.quantity;
-        } else if inventory_items.0.len() < MAX_ITEM_COUNT {
-            inventory_items.0.push(ingredient);
+        // the ingredient stays in the circle when there is no room for it
+        let Some(ingredient) = ingredient_items.0.get(index) else {
+            continue;
+        };
+        if !inventory_items.add(ingredient) {
+            continue;
         }
+        ingredient_items.0.remove(index);
 
         recreate_items.should_recreate_inventory_items = true;
         recreate_items.should_recreate_ingredient_items = true;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use bevy::ecs::system::RunSystemOnce;
+
+    use crate::{balance::CraftingBalance, stats::RecipeKind};
+
+    fn item(item_type: SummoningItemType, tier: u8, quantity: usize) -> SummoningItem {
+        SummoningItem {
+            item_type,
+            tier,
+            quantity,
+        }
+    }
+
+    fn summoning_world(items: Vec<SummoningItem>, ingredients: Vec<SummoningItem>) -> World {
+        let mut world = World::new();
+        world.insert_resource(ButtonInput::<KeyCode>::default());
+        world.insert_resource::<Balance>(
+            ron::from_str(include_str!("../assets/balance.ron")).unwrap(),
+        );
+        world.init_resource::<ShouldRecreateItemCards>();
+        world.init_resource::<PendingRewards>();
+        world.init_resource::<NextState<GameScreen>>();
+        world.insert_resource(InventoryItems(items));
+        world.insert_resource(IngredientItems(ingredients));
+        world
+    }
+
+    /// World after the left click on the inventory card with the given index.
+    fn click_inventory_card(items: Vec<SummoningItem>, index: usize, minion: Stats) -> World {
+        let mut world = summoning_world(items, Vec::new());
+        world.spawn((
+            Clickable {
+                just_left_clicked: true,
+                ..Default::default()
+            },
+            ItemCard(index),
+            InInventoryItem,
+        ));
+        world.spawn((Minion, minion));
+
+        world.run_system_once(handle_move_item);
+        world
+    }
+
+    fn full_inventory() -> InventoryItems {
+        InventoryItems(
+            SummoningItemType::ALL
+                .into_iter()
+                .take(MAX_ITEM_COUNT)
+                .map(|item_type| item(item_type, 1, 4))
+                .collect(),
+        )
+    }
+
+    #[test]
+    fn shipped_recipes_change_stats_of_their_minions() {
+        let balance: Balance = ron::from_str(include_str!("../assets/balance.ron")).unwrap();
+
+        for recipe in balance.recipes.iter() {
+            let ingredients: Vec<_> = recipe
+                .ingredients
+                .iter()
+                .map(|&item_type| item(item_type, 1, 1))
+                .collect();
+            // ingredients of the recipe can be summoned together
+            for (i, first) in ingredients.iter().enumerate() {
+                assert!(ingredients[i + 1..]
+                    .iter()
+                    .all(|second| !first.item_type.excludes(&second.item_type)));
+            }
+            assert_eq!(
+                balance.recipe(&ingredients).map(|recipe| recipe.kind),
+                Some(recipe.kind)
+            );
+
+            let mut expected = balance.minion.stats(&[]);
+            for ingredient in ingredients.iter() {
+                add_ingredient(&mut expected, ingredient, &balance);
+            }
+            for &(stat, tier) in recipe.stat_bonuses.iter() {
+                add_stat_tiers(&mut expected, &balance.minion, stat, tier);
+            }
+            if expected.on_hit.is_none() {
+                expected.on_hit = recipe
+                    .on_hit
+                    .and_then(|(kind, tier)| balance.status_effects.effect(kind, tier));
+            }
+
+            let stats = minion_stats(&ingredients, &balance);
+            for stat in StatKind::ALL {
+                assert_eq!(stat.get(&stats), stat.get(&expected), "{:?}", recipe.kind);
+            }
+            assert_eq!(stats.current_hp, stats.max_hp);
+            assert_eq!(stats.on_hit, expected.on_hit);
+            assert_eq!(stats.recipe, Some(recipe.kind));
+        }
+    }
+
+    #[test]
+    fn summoned_minion_gets_the_previewed_stats() {
+        let balance: Balance = ron::from_str(include_str!("../assets/balance.ron")).unwrap();
+        let ingredients = vec![
+            item(SummoningItemType::Damage, 3, 1),
+            item(SummoningItemType::Speed, 2, 1),
+            item(SummoningItemType::Heal, 2, 1),
+            item(SummoningItemType::Poison, 1, 1),
+            item(SummoningItemType::TargetLowestHp, 1, 1),
+        ];
+
+        // the preview and the summoning both compute stats from the ingredients in the circle
+        let preview = minion_stats(&ingredients, &balance);
+        let summoned = minion_stats(&ingredients, &balance);
+
+        assert_eq!(
+            ron::to_string(&preview).unwrap(),
+            ron::to_string(&summoned).unwrap()
+        );
+        assert_eq!(preview.recipe, Some(RecipeKind::Berserker));
+        // the on hit effect of the ingredient wins over the one of the recipe
+        assert_eq!(
+            preview.on_hit,
+            balance.status_effects.effect(StatusEffectKind::Poison, 1)
+        );
+        assert_eq!(preview.targeting, TargetingPolicy::LowestHp);
+        assert!(preview.ability.is_some());
+    }
+
+    #[test]
+    fn herb_of_the_last_stack_heals_minions() {
+        let wounded = Stats {
+            current_hp: 5.,
+            max_hp: 10.,
+            ..Default::default()
+        };
+        let mut world = click_inventory_card(
+            vec![
+                item(SummoningItemType::Damage, 1, 1),
+                item(SummoningItemType::HealingHerb, 1, 1),
+            ],
+            1,
+            wounded,
+        );
+
+        assert_eq!(
+            world.resource::<InventoryItems>().0,
+            vec![item(SummoningItemType::Damage, 1, 1)]
+        );
+        assert!(world.resource::<IngredientItems>().0.is_empty());
+        let stats = world
+            .query_filtered::<&Stats, With<Minion>>()
+            .single(&world);
+        assert!(stats.current_hp > 5.);
+    }
+
+    #[test]
+    fn herb_is_kept_when_nobody_is_wounded() {
+        let herbs = vec![item(SummoningItemType::HealingHerb, 1, 1)];
+        let world = click_inventory_card(herbs.clone(), 0, Stats::default());
+
+        assert_eq!(world.resource::<InventoryItems>().0, herbs);
+    }
+
+    #[test]
+    fn last_stack_is_moved_to_ingredients() {
+        let world = click_inventory_card(
+            vec![
+                item(SummoningItemType::Damage, 1, 2),
+                item(SummoningItemType::Speed, 1, 1),
+            ],
+            1,
+            Stats::default(),
+        );
+
+        assert_eq!(
+            world.resource::<InventoryItems>().0,
+            vec![item(SummoningItemType::Damage, 1, 2)]
+        );
+        assert_eq!(
+            world.resource::<IngredientItems>().0,
+            vec![item(SummoningItemType::Speed, 1, 1)]
+        );
+    }
+
+    #[test]
+    fn ingredient_stays_when_the_inventory_is_full() {
+        let inventory = full_inventory();
+        let stun = item(SummoningItemType::Stun, 1, 1);
+        let mut world = summoning_world(inventory.0.clone(), vec![stun.clone()]);
+        world.spawn((
+            Clickable {
+                just_left_clicked: true,
+                ..Default::default()
+            },
+            ItemCard(0),
+        ));
+
+        world.run_system_once(handle_move_item);
+
+        assert_eq!(world.resource::<InventoryItems>().0, inventory.0);
+        assert_eq!(world.resource::<IngredientItems>().0, vec![stun]);
+    }
+
+    /// World after the ready button is clicked with a single minion summoned.
+    fn click_ready(items: Vec<SummoningItem>, ingredients: Vec<SummoningItem>) -> World {
+        let mut world = summoning_world(items, ingredients);
+        world.spawn((
+            Clickable {
+                just_left_clicked: true,
+                ..Default::default()
+            },
+            ReadyButton,
+        ));
+        world.spawn(Minion);
+
+        world.run_system_once(move_to_preparation_screen);
+        world
+    }
+
+    #[test]
+    fn unused_ingredients_return_to_the_inventory() {
+        let world = click_ready(
+            vec![item(SummoningItemType::Damage, 1, 2)],
+            vec![
+                item(SummoningItemType::Damage, 1, 1),
+                item(SummoningItemType::Stun, 1, 1),
+            ],
+        );
+
+        assert_eq!(
+            world.resource::<InventoryItems>().0,
+            vec![
+                item(SummoningItemType::Damage, 1, 3),
+                item(SummoningItemType::Stun, 1, 1),
+            ]
+        );
+        assert!(world.resource::<IngredientItems>().0.is_empty());
+        assert!(matches!(
+            world.resource::<NextState<GameScreen>>().0,
+            Some(GameScreen::Planning)
+        ));
+    }
+
+    #[test]
+    fn ingredients_which_do_not_fit_are_offered_as_rewards() {
+        let mut inventory = full_inventory();
+        let world = click_ready(
+            inventory.0.clone(),
+            vec![
+                item(SummoningItemType::Damage, 1, 1),
+                item(SummoningItemType::Stun, 1, 1),
+            ],
+        );
+
+        inventory.0[0].quantity += 1;
+        assert_eq!(world.resource::<InventoryItems>().0, inventory.0);
+        assert!(world.resource::<IngredientItems>().0.is_empty());
+        assert_eq!(
+            world.resource::<PendingRewards>().items,
+            vec![item(SummoningItemType::Stun, 1, 1)]
+        );
+        assert!(matches!(
+            world.resource::<NextState<GameScreen>>().0,
+            Some(GameScreen::Rewards)
+        ));
+    }
+
+    #[test]
+    fn crafting_uses_the_ratio_of_the_tier() {
+        let crafting = CraftingBalance {
+            ratios: vec![3, 3, 3, 4],
+        };
+        assert_eq!(crafting.ratio(1), 3);
+        assert_eq!(crafting.ratio(4), 4);
+        // higher tiers use the last ratio
+        assert_eq!(crafting.ratio(7), 4);
+
+        let mut inventory = InventoryItems(vec![item(SummoningItemType::Damage, 4, 4)]);
+        assert!(inventory.craft(0, crafting.ratio(4)));
+        assert_eq!(inventory.0, vec![item(SummoningItemType::Damage, 5, 1)]);
+    }
+
+    #[test]
+    fn crafting_keeps_the_leftover_items() {
+        let mut inventory = InventoryItems(vec![
+            item(SummoningItemType::Damage, 1, 5),
+            item(SummoningItemType::Damage, 2, 1),
+        ]);
+
+        assert!(inventory.craft(0, 3));
+        assert_eq!(
+            inventory.0,
+            vec![
+                item(SummoningItemType::Damage, 1, 2),
+                item(SummoningItemType::Damage, 2, 2),
+            ]
+        );
+        // not enough items left for another one
+        assert!(!inventory.craft(0, 3));
+    }
+
+    #[test]
+    fn top_tier_and_tactics_can_not_be_crafted() {
+        let mut inventory = InventoryItems(vec![
+            item(SummoningItemType::Damage, MAX_ITEM_TIER, 5),
+            item(SummoningItemType::Taunt, 1, 5),
+        ]);
+
+        assert!(!inventory.craft(0, 3));
+        assert!(!inventory.craft(1, 3));
+        assert_eq!(inventory.0[0].quantity, 5);
+        assert_eq!(inventory.0[1].quantity, 5);
+    }
+
+    #[test]
+    fn crafting_into_a_full_inventory_is_rolled_back() {
+        let mut inventory = full_inventory();
+        let items = inventory.0.clone();
+
+        assert!(!inventory.craft(3, 3));
+        assert_eq!(inventory.0, items);
+
+        // a used up stack frees the slot for the crafted item
+        inventory.0[3].quantity = 3;
+        assert!(inventory.craft(3, 3));
+        assert_eq!(inventory.0.len(), MAX_ITEM_COUNT);
+        assert_eq!(
+            inventory.0.last(),
+            Some(&item(SummoningItemType::HPRegeneration, 2, 1))
+        );
+    }
+}
@@ -15,7 +15,7 @@ const INGREDIENTS_TEXT_POS: Vec2 = Vec2::new(615., 60.);
 const SUMMONING_TEXT_POS: Vec2 = Vec2::new(0., 210.);
 const MINIONS_TEXT_POS: Vec2 = Vec2::new(0., -440.);
 
-const ENEMY_CARDS_TEXT_POS: Vec2 = Vec2::ZERO;
+const MAP_TEXT_POS: Vec2 = Vec2::ZERO;
 
 pub struct TutorialPlugin;
 
@@ -118,7 +118,7 @@ fn show_summoning_tutorial(
         Text2dBundle {
             text: Text {
                 sections: vec![TextSection::new(
-                    "INVENTORY\nLMB - ADD ITEM TO SUMMONING CIRCLE\nRMB - DELETE ITEM",
+                    "INVENTORY\nLMB - ADD ITEM TO SUMMONING CIRCLE OR USE HERB\nSHIFT + LMB - CRAFT HIGHER TIER\nRMB - SELL ITEM",
                     TextStyle {
                         font: fonts.texts.clone(),
                         color: Color::BLACK,
@@ -181,7 +181,7 @@ fn show_summoning_tutorial(
         Text2dBundle {
             text: Text {
                 sections: vec![TextSection::new(
-                    "SUMMONED MINIONS\nRMB - DESTROY MINION",
+                    "SUMMONED MINIONS\nLMB - CHOOSE LEVEL UP STAT OR EQUIP SELECTED GEAR\nDRAG - MOVE TO FRONT OR BACK ROW\nRMB - SACRIFICE MINION OR UNEQUIP GEAR",
                     TextStyle {
                         font: fonts.texts.clone(),
                         color: Color::BLACK,
@@ -264,12 +264,12 @@ fn show_planning_tutorial(
         TutorialEntity,
     ));
 
-    // minions text
+    // map text
     commands.spawn((
         Text2dBundle {
             text: Text {
                 sections: vec![TextSection::new(
-                    "ENEMY CARDS\nEACH SHOW ENEMY STATS AND REWARDS\nLMB - SELECT ENEMY",
+                    "DUNGEON MAP\nHOVER A NODE TO SEE ENEMIES, REWARDS AND WIN CHANCE\nLMB - GO TO NODE\nRMB - SKIP FIGHT FOR A PART OF MINION HP",
                     TextStyle {
                         font: fonts.texts.clone(),
                         color: Color::BLACK,
@@ -279,7 +279,7 @@ fn show_planning_tutorial(
                 justify: JustifyText::Center,
                 ..Default::default()
             },
-            transform: Transform::from_translation(ENEMY_CARDS_TEXT_POS.extend(TEXT_Z)),
+            transform: Transform::from_translation(MAP_TEXT_POS.extend(TEXT_Z)),
             ..Default::default()
         },
         TutorialEntity,